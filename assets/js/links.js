@@ -0,0 +1,44 @@
+document.addEventListener("DOMContentLoaded", () => {
+  const $input = document.getElementById("link-target");
+  const $list = document.getElementById("ticket-lookup");
+
+  if (!$input || !$list) {
+    return;
+  }
+
+  let timeout = null;
+
+  // Offer the found tickets as options, with the ticket ID as value and the title as label
+  const render = tickets => {
+    $list.textContent = "";
+
+    tickets.forEach(ticket => {
+      const $option = document.createElement("option");
+      $option.value = ticket.id;
+      $option.label = `#${ticket.id} ${ticket.title}`;
+      $list.append($option);
+    });
+  };
+
+  const lookup = () => {
+    const term = $input.value.trim();
+
+    if (!term) {
+      render([]);
+      return;
+    }
+
+    const params = new URLSearchParams({ term });
+
+    fetch(`/tickets/lookup?${params}`, { credentials: "same-origin" })
+      .then(res => (res.ok ? res.json() : []))
+      .then(render)
+      .catch(() => render([]));
+  };
+
+  // Wait for the user to stop typing before searching
+  $input.addEventListener("input", () => {
+    clearTimeout(timeout);
+    timeout = setTimeout(lookup, 300);
+  });
+});
@@ -0,0 +1,52 @@
+// Same limit as the server enforces for a single medium
+const maxLocations = 20;
+
+document.addEventListener("DOMContentLoaded", () => {
+  document.querySelectorAll("[data-locations]").forEach($locations => {
+    const $add = $locations.querySelector("[data-add-location]");
+
+    if (!$add) {
+      return;
+    }
+
+    const rows = () => $locations.querySelectorAll("[data-location]");
+
+    // The last location can't be removed, as every ticket needs at least one
+    const update = () => {
+      const count = rows().length;
+      $locations.querySelectorAll("[data-remove-location]").forEach($button => {
+        $button.disabled = count === 1;
+      });
+      $add.disabled = count >= maxLocations;
+    };
+
+    $locations.addEventListener("click", event => {
+      const $remove = event.target.closest("[data-remove-location]");
+
+      if ($remove && rows().length > 1) {
+        $remove.closest("[data-location]").remove();
+        update();
+      }
+    });
+
+    // New locations are copies of the first one without its values, so every row sends the same
+    // fields and the server can match them up by their order
+    $add.addEventListener("click", () => {
+      const all = rows();
+
+      if (all.length >= maxLocations) {
+        return;
+      }
+      const $row = all[0].cloneNode(true);
+
+      $row.querySelectorAll("input, textarea").forEach($input => {
+        $input.value = "";
+      });
+
+      all[all.length - 1].after($row);
+      update();
+    });
+
+    update();
+  });
+});
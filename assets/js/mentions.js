@@ -0,0 +1,73 @@
+document.addEventListener("DOMContentLoaded", () => {
+  const $textareas = document.querySelectorAll("textarea[data-mentions]");
+
+  // Find the mention that is currently being typed, right before the cursor
+  const currentMention = $textarea => {
+    const before = $textarea.value.slice(0, $textarea.selectionStart);
+    const match = /(^|[^\w.@-])@([\w.-]*)$/u.exec(before);
+
+    return match ? { term: match[2], start: before.length - match[2].length } : null;
+  };
+
+  $textareas.forEach($textarea => {
+    const $list = document.createElement("div");
+    $list.className = "buttons are-small mt-2 is-hidden";
+
+    let timeout = null;
+
+    // Offer the found users as buttons, only using text nodes to never interpret any names as HTML
+    const render = users => {
+      $list.textContent = "";
+
+      users.forEach(user => {
+        const $button = document.createElement("button");
+        $button.className = "button is-light";
+        $button.type = "button";
+        $button.textContent = `@${user.username} (${user.name})`;
+        $button.addEventListener("click", () => {
+          const mention = currentMention($textarea);
+          if (!mention) {
+            return;
+          }
+
+          const end = $textarea.selectionStart;
+          const value = $textarea.value;
+          const inserted = `${user.username} `;
+
+          $textarea.value = value.slice(0, mention.start) + inserted + value.slice(end);
+          $textarea.selectionStart = $textarea.selectionEnd = mention.start + inserted.length;
+          $textarea.focus();
+          render([]);
+        });
+
+        $list.append($button);
+      });
+
+      $list.classList.toggle("is-hidden", users.length === 0);
+    };
+
+    const lookup = () => {
+      const mention = currentMention($textarea);
+
+      if (!mention || !mention.term) {
+        render([]);
+        return;
+      }
+
+      const params = new URLSearchParams({ term: mention.term });
+
+      fetch(`/tickets/mentions?${params}`, { credentials: "same-origin" })
+        .then(res => (res.ok ? res.json() : []))
+        .then(render)
+        .catch(() => render([]));
+    };
+
+    // Wait for the user to stop typing before searching
+    $textarea.addEventListener("input", () => {
+      clearTimeout(timeout);
+      timeout = setTimeout(lookup, 300);
+    });
+
+    $textarea.after($list);
+  });
+});
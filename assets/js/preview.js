@@ -0,0 +1,59 @@
+document.addEventListener("DOMContentLoaded", () => {
+  const $textareas = document.querySelectorAll("textarea[data-preview]");
+
+  const tab = (label, active) => {
+    const $item = document.createElement("li");
+    $item.classList.toggle("is-active", active);
+
+    const $link = document.createElement("a");
+    $link.textContent = label;
+
+    $item.append($link);
+    return $item;
+  };
+
+  // Add "write" and "preview" tabs to each Markdown text area, rendering the preview on the
+  // server so it matches the final output exactly, including the sanitizing
+  $textareas.forEach($textarea => {
+    const $tabs = document.createElement("div");
+    $tabs.className = "tabs is-small mb-2";
+
+    const $list = document.createElement("ul");
+    const $write = tab("Schreiben", true);
+    const $show = tab("Vorschau", false);
+    $list.append($write, $show);
+    $tabs.append($list);
+
+    const $preview = document.createElement("div");
+    $preview.className = "content box is-shadowless has-background-light is-hidden";
+
+    const select = showPreview => {
+      $write.classList.toggle("is-active", !showPreview);
+      $show.classList.toggle("is-active", showPreview);
+      $textarea.classList.toggle("is-hidden", showPreview);
+      $preview.classList.toggle("is-hidden", !showPreview);
+    };
+
+    $write.addEventListener("click", () => select(false));
+    $show.addEventListener("click", () => {
+      $preview.textContent = "Vorschau wird geladen...";
+      select(true);
+
+      fetch("/tickets/preview", {
+        method: "POST",
+        credentials: "same-origin",
+        body: new URLSearchParams({ text: $textarea.value }),
+      })
+        .then(res => (res.ok ? res.text() : Promise.reject(res.status)))
+        .then(html => {
+          $preview.innerHTML = html;
+        })
+        .catch(() => {
+          $preview.textContent = "Vorschau konnte nicht geladen werden";
+        });
+    });
+
+    $textarea.before($tabs);
+    $textarea.after($preview);
+  });
+});
@@ -0,0 +1,80 @@
+document.addEventListener("DOMContentLoaded", () => {
+  const $form = document.getElementById("new-ticket");
+  const $box = document.getElementById("similar-tickets");
+  const $list = document.getElementById("similar-tickets-list");
+
+  if (!$form || !$box || !$list) {
+    return;
+  }
+
+  const fields = ["course", "ty", "title", "page", "line", "hour", "minute", "second", "url", "question"];
+  let timeout = null;
+
+  // Render the found tickets, only using text nodes to never interpret any ticket content as HTML
+  const render = tickets => {
+    $list.textContent = "";
+
+    tickets.forEach(ticket => {
+      const $entry = document.createElement("div");
+      $entry.className = "level";
+
+      const $left = document.createElement("div");
+      $left.className = "level-left";
+
+      const $link = document.createElement("a");
+      $link.className = "level-item";
+      $link.href = `/tickets/${ticket.id}`;
+      $link.textContent = `#${ticket.id} ${ticket.title}`;
+
+      const $status = document.createElement("span");
+      $status.className = "level-item tag";
+      $status.textContent = ticket.status;
+
+      $left.append($link, $status);
+
+      const $right = document.createElement("form");
+      $right.className = "level-right";
+      $right.method = "POST";
+      $right.action = `/tickets/${ticket.id}/me-too`;
+
+      const $button = document.createElement("button");
+      $button.className = "button is-small is-info is-light level-item";
+      $button.type = "submit";
+      $button.textContent = "Das ist auch mein Problem";
+
+      $right.append($button);
+      $entry.append($left, $right);
+      $list.append($entry);
+    });
+
+    $box.classList.toggle("is-hidden", tickets.length === 0);
+  };
+
+  const lookup = () => {
+    const data = new FormData($form);
+    const params = new URLSearchParams();
+
+    fields.forEach(field => {
+      const value = data.get(field);
+      if (value) {
+        params.append(field, value);
+      }
+    });
+
+    if (!params.has("course") || !params.has("title")) {
+      render([]);
+      return;
+    }
+
+    fetch(`/tickets/similar?${params}`, { credentials: "same-origin" })
+      .then(res => (res.ok ? res.json() : []))
+      .then(render)
+      .catch(() => render([]));
+  };
+
+  // Wait for the user to stop typing before searching
+  $form.addEventListener("input", () => {
+    clearTimeout(timeout);
+    timeout = setTimeout(lookup, 500);
+  });
+});
@@ -0,0 +1,4 @@
+DROP TABLE program_managers;
+DROP TABLE course_programs;
+DROP TABLE programs;
+DROP TABLE faculties;
@@ -0,0 +1,23 @@
+CREATE TABLE faculties (
+    id    INTEGER NOT NULL PRIMARY KEY,
+    name  TEXT    NOT NULL UNIQUE
+);
+
+CREATE TABLE programs (
+    id         INTEGER NOT NULL PRIMARY KEY,
+    faculty_id INTEGER NOT NULL REFERENCES faculties(id),
+    code       TEXT    NOT NULL UNIQUE,
+    title      TEXT    NOT NULL
+);
+
+CREATE TABLE course_programs (
+    course_id  INTEGER NOT NULL REFERENCES courses(id),
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    PRIMARY KEY (course_id, program_id)
+);
+
+CREATE TABLE program_managers (
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    user_id    INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (program_id, user_id)
+);
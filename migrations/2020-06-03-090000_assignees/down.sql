@@ -0,0 +1,58 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE tickets_backup AS
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id
+FROM tickets;
+
+DROP TABLE tickets;
+
+CREATE TABLE tickets
+(
+    id          INTEGER NOT NULL PRIMARY KEY,
+    type        TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    description TEXT    NOT NULL,
+    category    TEXT    NOT NULL,
+    priority    TEXT    NOT NULL,
+    status      TEXT    NOT NULL DEFAULT 'open',
+    forwarded   BOOLEAN NOT NULL DEFAULT FALSE,
+    course_id   INTEGER NOT NULL REFERENCES courses(id),
+    creator_id  INTEGER NOT NULL REFERENCES users(id),
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    )),
+    CHECK (priority IN (
+        'critical',
+        'high',
+        'medium',
+        'low'
+    )),
+    CHECK (status IN (
+        'open',
+        'in-progress',
+        'accepted',
+        'refused',
+        'completed'
+    ))
+);
+
+INSERT INTO tickets
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id
+FROM tickets_backup;
+
+DROP TABLE tickets_backup;
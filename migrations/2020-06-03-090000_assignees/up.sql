@@ -0,0 +1,8 @@
+ALTER TABLE tickets ADD COLUMN assignee_id INTEGER REFERENCES users(id);
+
+UPDATE tickets
+SET assignee_id = (
+    SELECT CASE WHEN tickets.forwarded THEN courses.author_id ELSE courses.tutor_id END
+    FROM courses
+    WHERE courses.id = tickets.course_id
+);
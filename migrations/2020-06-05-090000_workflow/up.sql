@@ -0,0 +1,101 @@
+CREATE TABLE workflow_statuses (
+    key      TEXT    NOT NULL PRIMARY KEY,
+    name     TEXT    NOT NULL,
+    color    TEXT    NOT NULL,
+    icon     TEXT    NOT NULL,
+    terminal BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL,
+    CHECK (color IN (
+        'primary',
+        'link',
+        'info',
+        'success',
+        'warning',
+        'danger',
+        'light',
+        'dark'
+    ))
+);
+
+CREATE TABLE workflow_transitions (
+    from_status TEXT NOT NULL REFERENCES workflow_statuses(key),
+    to_status   TEXT NOT NULL REFERENCES workflow_statuses(key),
+    role        TEXT NOT NULL,
+    PRIMARY KEY (from_status, to_status, role),
+    CHECK (from_status <> to_status),
+    CHECK (role IN ('admin', 'author', 'tutor', 'student'))
+);
+
+INSERT INTO workflow_statuses (key, name, color, icon, terminal, position)
+VALUES ('open', 'Offen', 'primary', 'fa-envelope', FALSE, 1),
+       ('in-progress', 'In Bearbeitung', 'info', 'fa-cogs', FALSE, 2),
+       ('accepted', 'Akzeptiert', 'success', 'fa-check', FALSE, 3),
+       ('refused', 'Abgelehnt', 'danger', 'fa-times', TRUE, 4),
+       ('completed', 'Abgeschlossen', 'light', 'fa-archive', TRUE, 5);
+
+INSERT INTO workflow_transitions (from_status, to_status, role)
+VALUES ('open', 'in-progress', 'admin'),
+       ('open', 'in-progress', 'author'),
+       ('open', 'in-progress', 'tutor'),
+       ('in-progress', 'accepted', 'admin'),
+       ('in-progress', 'accepted', 'author'),
+       ('in-progress', 'accepted', 'tutor'),
+       ('in-progress', 'refused', 'admin'),
+       ('in-progress', 'refused', 'author'),
+       ('in-progress', 'refused', 'tutor'),
+       ('accepted', 'completed', 'admin'),
+       ('accepted', 'completed', 'author'),
+       ('accepted', 'completed', 'tutor');
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE tickets_backup AS
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id, assignee_id
+FROM tickets;
+
+DROP TABLE tickets;
+
+CREATE TABLE tickets
+(
+    id          INTEGER NOT NULL PRIMARY KEY,
+    type        TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    description TEXT    NOT NULL,
+    category    TEXT    NOT NULL,
+    priority    TEXT    NOT NULL,
+    status      TEXT    NOT NULL DEFAULT 'open' REFERENCES workflow_statuses(key),
+    forwarded   BOOLEAN NOT NULL DEFAULT FALSE,
+    course_id   INTEGER NOT NULL REFERENCES courses(id),
+    creator_id  INTEGER NOT NULL REFERENCES users(id),
+    assignee_id INTEGER REFERENCES users(id),
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    )),
+    CHECK (priority IN (
+        'critical',
+        'high',
+        'medium',
+        'low'
+    ))
+);
+
+INSERT INTO tickets
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id, assignee_id
+FROM tickets_backup;
+
+DROP TABLE tickets_backup;
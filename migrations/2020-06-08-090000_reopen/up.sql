@@ -0,0 +1,8 @@
+ALTER TABLE tickets ADD COLUMN closed_at TEXT;
+ALTER TABLE tickets ADD COLUMN reopen_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE reopen_requests (
+    ticket_id    INTEGER NOT NULL PRIMARY KEY REFERENCES tickets(id),
+    requester_id INTEGER NOT NULL REFERENCES users(id),
+    requested_at TEXT    NOT NULL
+);
@@ -0,0 +1,18 @@
+CREATE TABLE ticket_events (
+    id        INTEGER NOT NULL PRIMARY KEY,
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    actor_id  INTEGER NOT NULL REFERENCES users(id),
+    timestamp TEXT    NOT NULL,
+    field     TEXT    NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    CHECK (field IN (
+        'status',
+        'priority',
+        'forwarded',
+        'assignee',
+        'reopened',
+        'reopen-requested',
+        'reopen-refused'
+    ))
+);
@@ -0,0 +1,10 @@
+CREATE TABLE attachments (
+    id         INTEGER NOT NULL PRIMARY KEY,
+    ticket_id  INTEGER NOT NULL REFERENCES tickets(id),
+    comment_id INTEGER REFERENCES comments(id),
+    creator_id INTEGER NOT NULL REFERENCES users(id),
+    file_name  TEXT    NOT NULL,
+    mime_type  TEXT    NOT NULL,
+    size       INTEGER NOT NULL,
+    timestamp  TEXT    NOT NULL
+);
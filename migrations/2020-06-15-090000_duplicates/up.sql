@@ -0,0 +1,22 @@
+ALTER TABLE tickets ADD COLUMN duplicate_of INTEGER REFERENCES tickets(id);
+ALTER TABLE comments ADD COLUMN source_ticket_id INTEGER REFERENCES tickets(id);
+
+CREATE TABLE ticket_watchers (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    user_id   INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (ticket_id, user_id)
+);
+
+INSERT INTO workflow_statuses (key, name, color, icon, terminal, position)
+VALUES ('duplicate', 'Duplikat', 'dark', 'fa-clone', TRUE, (SELECT MAX(position) + 1 FROM workflow_statuses));
+
+INSERT INTO workflow_transitions (from_status, to_status, role)
+VALUES ('open', 'duplicate', 'admin'),
+       ('open', 'duplicate', 'author'),
+       ('open', 'duplicate', 'tutor'),
+       ('in-progress', 'duplicate', 'admin'),
+       ('in-progress', 'duplicate', 'author'),
+       ('in-progress', 'duplicate', 'tutor'),
+       ('accepted', 'duplicate', 'admin'),
+       ('accepted', 'duplicate', 'author'),
+       ('accepted', 'duplicate', 'tutor');
@@ -0,0 +1,14 @@
+CREATE TABLE ticket_links (
+    id        INTEGER NOT NULL PRIMARY KEY,
+    source_id INTEGER NOT NULL REFERENCES tickets(id),
+    target_id INTEGER NOT NULL REFERENCES tickets(id),
+    kind      TEXT    NOT NULL,
+    UNIQUE (source_id, target_id, kind),
+    CHECK (source_id <> target_id),
+    CHECK (kind IN (
+        'relates-to',
+        'duplicates',
+        'blocks',
+        'follows-up'
+    ))
+);
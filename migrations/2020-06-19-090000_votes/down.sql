@@ -0,0 +1,59 @@
+PRAGMA defer_foreign_keys = ON;
+
+DROP TABLE ticket_votes;
+
+CREATE TABLE tickets_backup AS
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id, assignee_id,
+       closed_at, reopen_count, duplicate_of
+FROM tickets;
+
+DROP TABLE tickets;
+
+CREATE TABLE tickets
+(
+    id           INTEGER NOT NULL PRIMARY KEY,
+    type         TEXT    NOT NULL,
+    title        TEXT    NOT NULL,
+    description  TEXT    NOT NULL,
+    category     TEXT    NOT NULL,
+    priority     TEXT    NOT NULL,
+    status       TEXT    NOT NULL DEFAULT 'open' REFERENCES workflow_statuses(key),
+    forwarded    BOOLEAN NOT NULL DEFAULT FALSE,
+    course_id    INTEGER NOT NULL REFERENCES courses(id),
+    creator_id   INTEGER NOT NULL REFERENCES users(id),
+    assignee_id  INTEGER REFERENCES users(id),
+    closed_at    TEXT,
+    reopen_count INTEGER NOT NULL DEFAULT 0,
+    duplicate_of INTEGER REFERENCES tickets(id),
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    )),
+    CHECK (priority IN (
+        'critical',
+        'high',
+        'medium',
+        'low'
+    ))
+);
+
+INSERT INTO tickets
+SELECT id, type, title, description, category, priority, status, forwarded, course_id, creator_id, assignee_id,
+       closed_at, reopen_count, duplicate_of
+FROM tickets_backup;
+
+DROP TABLE tickets_backup;
@@ -0,0 +1,7 @@
+ALTER TABLE tickets ADD COLUMN votes INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE ticket_votes (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    user_id   INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (ticket_id, user_id)
+);
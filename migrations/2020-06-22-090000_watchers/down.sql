@@ -0,0 +1,2 @@
+-- Watchers added by the up migration can't be told apart from the ones that subscribed themselves,
+-- so all of them are kept.
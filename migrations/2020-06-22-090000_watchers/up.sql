@@ -0,0 +1,12 @@
+INSERT OR IGNORE INTO ticket_watchers (ticket_id, user_id)
+SELECT id, creator_id
+FROM tickets;
+
+INSERT OR IGNORE INTO ticket_watchers (ticket_id, user_id)
+SELECT id, assignee_id
+FROM tickets
+WHERE assignee_id IS NOT NULL;
+
+INSERT OR IGNORE INTO ticket_watchers (ticket_id, user_id)
+SELECT DISTINCT ticket_id, creator_id
+FROM comments;
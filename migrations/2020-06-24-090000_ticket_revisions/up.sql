@@ -0,0 +1,41 @@
+CREATE TABLE ticket_revisions (
+    id          INTEGER NOT NULL PRIMARY KEY,
+    ticket_id   INTEGER NOT NULL REFERENCES tickets(id),
+    editor_id   INTEGER NOT NULL REFERENCES users(id),
+    timestamp   TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    description TEXT    NOT NULL,
+    location    TEXT    NOT NULL
+);
+
+CREATE TABLE ticket_events_backup AS
+SELECT id, ticket_id, actor_id, timestamp, field, old_value, new_value
+FROM ticket_events;
+
+DROP TABLE ticket_events;
+
+CREATE TABLE ticket_events (
+    id        INTEGER NOT NULL PRIMARY KEY,
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    actor_id  INTEGER NOT NULL REFERENCES users(id),
+    timestamp TEXT    NOT NULL,
+    field     TEXT    NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    CHECK (field IN (
+        'status',
+        'priority',
+        'forwarded',
+        'assignee',
+        'reopened',
+        'reopen-requested',
+        'reopen-refused',
+        'edited'
+    ))
+);
+
+INSERT INTO ticket_events
+SELECT id, ticket_id, actor_id, timestamp, field, old_value, new_value
+FROM ticket_events_backup;
+
+DROP TABLE ticket_events_backup;
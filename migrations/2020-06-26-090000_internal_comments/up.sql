@@ -0,0 +1 @@
+ALTER TABLE comments ADD COLUMN internal BOOLEAN NOT NULL DEFAULT FALSE;
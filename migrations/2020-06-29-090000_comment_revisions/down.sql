@@ -0,0 +1,25 @@
+PRAGMA defer_foreign_keys = ON;
+
+DROP TABLE comment_revisions;
+
+CREATE TABLE comments_backup AS
+SELECT id, ticket_id, creator_id, timestamp, message, source_ticket_id, internal
+FROM comments;
+
+DROP TABLE comments;
+
+CREATE TABLE comments (
+    id               INTEGER NOT NULL PRIMARY KEY,
+    ticket_id        INTEGER NOT NULL REFERENCES tickets(id),
+    creator_id       INTEGER NOT NULL REFERENCES users(id),
+    timestamp        TEXT    NOT NULL,
+    message          TEXT    NOT NULL,
+    source_ticket_id INTEGER REFERENCES tickets(id),
+    internal         BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO comments
+SELECT id, ticket_id, creator_id, timestamp, message, source_ticket_id, internal
+FROM comments_backup;
+
+DROP TABLE comments_backup;
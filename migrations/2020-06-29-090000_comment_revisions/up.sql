@@ -0,0 +1,10 @@
+ALTER TABLE comments ADD COLUMN edited_at TEXT;
+ALTER TABLE comments ADD COLUMN deleted BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE comment_revisions (
+    id         INTEGER NOT NULL PRIMARY KEY,
+    comment_id INTEGER NOT NULL REFERENCES comments(id),
+    editor_id  INTEGER NOT NULL REFERENCES users(id),
+    timestamp  TEXT    NOT NULL,
+    message    TEXT    NOT NULL
+);
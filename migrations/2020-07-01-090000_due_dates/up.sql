@@ -0,0 +1,8 @@
+ALTER TABLE tickets ADD COLUMN created_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE tickets ADD COLUMN first_response_at TEXT;
+ALTER TABLE tickets ADD COLUMN escalation_level INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tickets ADD COLUMN escalated_at TEXT;
+
+-- The real creation time of existing tickets is unknown. Using the current time gives them a fresh
+-- start, instead of escalating all of them right away.
+UPDATE tickets SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now');
@@ -0,0 +1,77 @@
+CREATE TABLE ticket_rules (
+    id          INTEGER NOT NULL PRIMARY KEY,
+    name        TEXT    NOT NULL,
+    course_id   INTEGER REFERENCES courses(id),
+    type        TEXT,
+    category    TEXT,
+    medium      TEXT,
+    keywords    TEXT    NOT NULL DEFAULT '',
+    priority    TEXT,
+    forward     BOOLEAN NOT NULL DEFAULT FALSE,
+    assignee_id INTEGER REFERENCES users(id),
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    )),
+    CHECK (medium IN (
+        'text',
+        'recording',
+        'interactive',
+        'questionaire'
+    )),
+    CHECK (priority IN (
+        'critical',
+        'high',
+        'medium',
+        'low'
+    ))
+);
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE ticket_events_backup AS
+SELECT id, ticket_id, actor_id, timestamp, field, old_value, new_value
+FROM ticket_events;
+
+DROP TABLE ticket_events;
+
+CREATE TABLE ticket_events (
+    id        INTEGER NOT NULL PRIMARY KEY,
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    actor_id  INTEGER NOT NULL REFERENCES users(id),
+    timestamp TEXT    NOT NULL,
+    field     TEXT    NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    CHECK (field IN (
+        'status',
+        'priority',
+        'forwarded',
+        'assignee',
+        'reopened',
+        'reopen-requested',
+        'reopen-refused',
+        'edited',
+        'rule'
+    ))
+);
+
+INSERT INTO ticket_events
+SELECT id, ticket_id, actor_id, timestamp, field, old_value, new_value
+FROM ticket_events_backup;
+
+DROP TABLE ticket_events_backup;
@@ -0,0 +1,3 @@
+DROP TABLE rule_labels;
+DROP TABLE ticket_labels;
+DROP TABLE labels;
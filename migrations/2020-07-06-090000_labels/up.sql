@@ -0,0 +1,28 @@
+CREATE TABLE labels (
+    id        INTEGER NOT NULL PRIMARY KEY,
+    name      TEXT    NOT NULL,
+    color     TEXT    NOT NULL,
+    course_id INTEGER REFERENCES courses(id),
+    CHECK (color IN (
+        'primary',
+        'link',
+        'info',
+        'success',
+        'warning',
+        'danger',
+        'light',
+        'dark'
+    ))
+);
+
+CREATE TABLE ticket_labels (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    label_id  INTEGER NOT NULL REFERENCES labels(id),
+    PRIMARY KEY (ticket_id, label_id)
+);
+
+CREATE TABLE rule_labels (
+    rule_id  INTEGER NOT NULL REFERENCES ticket_rules(id),
+    label_id INTEGER NOT NULL REFERENCES labels(id),
+    PRIMARY KEY (rule_id, label_id)
+);
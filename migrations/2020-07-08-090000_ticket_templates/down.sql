@@ -0,0 +1,2 @@
+DROP TABLE ticket_fields;
+DROP TABLE ticket_templates;
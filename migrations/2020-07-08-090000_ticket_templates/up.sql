@@ -0,0 +1,26 @@
+CREATE TABLE ticket_templates (
+    type        TEXT NOT NULL PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    help        TEXT NOT NULL DEFAULT '',
+    fields      TEXT NOT NULL DEFAULT '',
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    ))
+);
+
+CREATE TABLE ticket_fields (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    position  INTEGER NOT NULL,
+    label     TEXT    NOT NULL,
+    value     TEXT    NOT NULL,
+    PRIMARY KEY (ticket_id, position)
+);
@@ -0,0 +1,25 @@
+DROP TABLE public_report_fields;
+DROP TABLE public_reports;
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE courses_backup AS
+SELECT id, code, title, author_id, tutor_id, active
+FROM courses;
+
+DROP TABLE courses;
+
+CREATE TABLE courses (
+    id         INTEGER NOT NULL PRIMARY KEY,
+    code       TEXT    NOT NULL UNIQUE,
+    title      TEXT    NOT NULL,
+    author_id  INTEGER NOT NULL REFERENCES users(id),
+    tutor_id   INTEGER NOT NULL REFERENCES users(id),
+    active     BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+INSERT INTO courses
+SELECT id, code, title, author_id, tutor_id, active
+FROM courses_backup;
+
+DROP TABLE courses_backup;
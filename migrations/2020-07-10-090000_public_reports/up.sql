@@ -0,0 +1,47 @@
+ALTER TABLE courses ADD COLUMN public_reports BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE public_reports (
+    id            INTEGER NOT NULL PRIMARY KEY,
+    course_id     INTEGER NOT NULL REFERENCES courses(id),
+    type          TEXT    NOT NULL,
+    category      TEXT    NOT NULL,
+    title         TEXT    NOT NULL,
+    description   TEXT    NOT NULL,
+    page          INTEGER,
+    line          INTEGER,
+    time          TEXT,
+    url           TEXT,
+    question      INTEGER,
+    answer        TEXT,
+    contact_email TEXT,
+    ip_address    TEXT    NOT NULL,
+    created_at    TEXT    NOT NULL,
+    ticket_id     INTEGER REFERENCES tickets(id),
+    rejected      BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    ))
+);
+
+CREATE TABLE public_report_fields (
+    report_id INTEGER NOT NULL REFERENCES public_reports(id),
+    position  INTEGER NOT NULL,
+    label     TEXT    NOT NULL,
+    value     TEXT    NOT NULL,
+    PRIMARY KEY (report_id, position)
+);
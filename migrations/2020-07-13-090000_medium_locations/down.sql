@@ -0,0 +1,114 @@
+-- Only the first location of each medium survives the way back, without its range.
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE public_reports_backup AS
+SELECT r.id, r.course_id, r.type, r.category, r.title, r.description, l.page, l.line, l.time,
+    l.url, l.question, l.answer, r.contact_email, r.ip_address, r.created_at, r.ticket_id,
+    r.rejected
+FROM public_reports r
+LEFT JOIN public_report_locations l ON l.report_id = r.id AND l.position = 0;
+
+DROP TABLE public_report_locations;
+DROP TABLE public_reports;
+
+CREATE TABLE public_reports (
+    id            INTEGER NOT NULL PRIMARY KEY,
+    course_id     INTEGER NOT NULL REFERENCES courses(id),
+    type          TEXT    NOT NULL,
+    category      TEXT    NOT NULL,
+    title         TEXT    NOT NULL,
+    description   TEXT    NOT NULL,
+    page          INTEGER,
+    line          INTEGER,
+    time          TEXT,
+    url           TEXT,
+    question      INTEGER,
+    answer        TEXT,
+    contact_email TEXT,
+    ip_address    TEXT    NOT NULL,
+    created_at    TEXT    NOT NULL,
+    ticket_id     INTEGER REFERENCES tickets(id),
+    rejected      BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    ))
+);
+
+INSERT INTO public_reports
+SELECT * FROM public_reports_backup;
+
+DROP TABLE public_reports_backup;
+
+ALTER TABLE medium_questionaires RENAME TO medium_questionaires_backup;
+
+CREATE TABLE medium_questionaires (
+    ticket_id INTEGER NOT NULL PRIMARY KEY REFERENCES tickets(id),
+    question  INTEGER NOT NULL,
+    answer    TEXT    NOT NULL
+);
+
+INSERT INTO medium_questionaires
+SELECT ticket_id, question, answer
+FROM medium_questionaires_backup
+WHERE position = 0;
+
+DROP TABLE medium_questionaires_backup;
+
+ALTER TABLE medium_interactives RENAME TO medium_interactives_backup;
+
+CREATE TABLE medium_interactives (
+    ticket_id INTEGER NOT NULL PRIMARY KEY REFERENCES tickets(id),
+    url       TEXT    NOT NULL
+);
+
+INSERT INTO medium_interactives
+SELECT ticket_id, url
+FROM medium_interactives_backup
+WHERE position = 0;
+
+DROP TABLE medium_interactives_backup;
+
+ALTER TABLE medium_recordings RENAME TO medium_recordings_backup;
+
+CREATE TABLE medium_recordings (
+    ticket_id INTEGER NOT NULL PRIMARY KEY REFERENCES tickets(id),
+    time      TEXT    NOT NULL
+);
+
+INSERT INTO medium_recordings
+SELECT ticket_id, time
+FROM medium_recordings_backup
+WHERE position = 0;
+
+DROP TABLE medium_recordings_backup;
+
+ALTER TABLE medium_texts RENAME TO medium_texts_backup;
+
+CREATE TABLE medium_texts (
+    ticket_id INTEGER NOT NULL PRIMARY KEY REFERENCES tickets(id),
+    page      INTEGER NOT NULL,
+    line      INTEGER NOT NULL
+);
+
+INSERT INTO medium_texts
+SELECT ticket_id, page, line
+FROM medium_texts_backup
+WHERE position = 0;
+
+DROP TABLE medium_texts_backup;
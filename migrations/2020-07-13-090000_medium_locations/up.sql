@@ -0,0 +1,139 @@
+-- Every medium can hold several locations now, each of them optionally being a range. Existing
+-- tickets keep their single location at the first position.
+
+ALTER TABLE medium_texts RENAME TO medium_texts_backup;
+
+CREATE TABLE medium_texts (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    position  INTEGER NOT NULL,
+    page      INTEGER NOT NULL,
+    line      INTEGER NOT NULL,
+    end_page  INTEGER,
+    end_line  INTEGER,
+    PRIMARY KEY (ticket_id, position),
+    CHECK ((end_page IS NULL) = (end_line IS NULL))
+);
+
+INSERT INTO medium_texts (ticket_id, position, page, line)
+SELECT ticket_id, 0, page, line
+FROM medium_texts_backup;
+
+DROP TABLE medium_texts_backup;
+
+ALTER TABLE medium_recordings RENAME TO medium_recordings_backup;
+
+CREATE TABLE medium_recordings (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    position  INTEGER NOT NULL,
+    time      TEXT    NOT NULL,
+    end_time  TEXT,
+    PRIMARY KEY (ticket_id, position)
+);
+
+INSERT INTO medium_recordings (ticket_id, position, time)
+SELECT ticket_id, 0, time
+FROM medium_recordings_backup;
+
+DROP TABLE medium_recordings_backup;
+
+ALTER TABLE medium_interactives RENAME TO medium_interactives_backup;
+
+CREATE TABLE medium_interactives (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id),
+    position  INTEGER NOT NULL,
+    url       TEXT    NOT NULL,
+    PRIMARY KEY (ticket_id, position)
+);
+
+INSERT INTO medium_interactives (ticket_id, position, url)
+SELECT ticket_id, 0, url
+FROM medium_interactives_backup;
+
+DROP TABLE medium_interactives_backup;
+
+ALTER TABLE medium_questionaires RENAME TO medium_questionaires_backup;
+
+CREATE TABLE medium_questionaires (
+    ticket_id    INTEGER NOT NULL REFERENCES tickets(id),
+    position     INTEGER NOT NULL,
+    question     INTEGER NOT NULL,
+    end_question INTEGER,
+    answer       TEXT    NOT NULL,
+    PRIMARY KEY (ticket_id, position)
+);
+
+INSERT INTO medium_questionaires (ticket_id, position, question, answer)
+SELECT ticket_id, 0, question, answer
+FROM medium_questionaires_backup;
+
+DROP TABLE medium_questionaires_backup;
+
+-- Public reports get the same kind of locations, in a single table for all medium types.
+
+CREATE TABLE public_report_locations (
+    report_id    INTEGER NOT NULL REFERENCES public_reports(id),
+    position     INTEGER NOT NULL,
+    page         INTEGER,
+    line         INTEGER,
+    end_page     INTEGER,
+    end_line     INTEGER,
+    time         TEXT,
+    end_time     TEXT,
+    url          TEXT,
+    question     INTEGER,
+    end_question INTEGER,
+    answer       TEXT,
+    PRIMARY KEY (report_id, position)
+);
+
+INSERT INTO public_report_locations (report_id, position, page, line, time, url, question, answer)
+SELECT id, 0, page, line, time, url, question, answer
+FROM public_reports;
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE public_reports_backup AS
+SELECT id, course_id, type, category, title, description, contact_email, ip_address, created_at,
+    ticket_id, rejected
+FROM public_reports;
+
+DROP TABLE public_reports;
+
+CREATE TABLE public_reports (
+    id            INTEGER NOT NULL PRIMARY KEY,
+    course_id     INTEGER NOT NULL REFERENCES courses(id),
+    type          TEXT    NOT NULL,
+    category      TEXT    NOT NULL,
+    title         TEXT    NOT NULL,
+    description   TEXT    NOT NULL,
+    contact_email TEXT,
+    ip_address    TEXT    NOT NULL,
+    created_at    TEXT    NOT NULL,
+    ticket_id     INTEGER REFERENCES tickets(id),
+    rejected      BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK (type IN (
+        'course-book',
+        'reading-list',
+        'interactive-book',
+        'practice-exam',
+        'practice-exam-solution',
+        'vodcast',
+        'podcast',
+        'presentation',
+        'live-tutorial-recording',
+        'online-test'
+    )),
+    CHECK (category IN (
+        'editorial',
+        'content',
+        'improvement',
+        'addition'
+    ))
+);
+
+INSERT INTO public_reports
+SELECT id, course_id, type, category, title, description, contact_email, ip_address, created_at,
+    ticket_id, rejected
+FROM public_reports_backup;
+
+DROP TABLE public_reports_backup;
@@ -4,6 +4,8 @@ use anyhow::Result;
 use rocket::config::{Config as RocketConfig, Environment};
 use serde::Deserialize;
 
+use crate::models::Priority;
+
 /// Configuration values that are read from a configuration file.
 #[derive(Deserialize)]
 pub struct Config {
@@ -20,10 +22,16 @@ pub struct Config {
     pub host: String,
     /// Settings for an email SMTP client.
     pub smtp: SmtpConfig,
+    /// Settings for the handling of tickets.
+    #[serde(default)]
+    pub tickets: TicketConfig,
+    /// Settings for file attachments of tickets and comments.
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
 }
 
 /// Configuration values to configure a SMTP client for sending emails.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct SmtpConfig {
     /// Domain name of the server.
     pub domain: String,
@@ -35,6 +43,212 @@ pub struct SmtpConfig {
     pub password: String,
 }
 
+/// Configuration values for the handling of tickets.
+#[derive(Deserialize)]
+pub struct TicketConfig {
+    /// Amount of days, after a ticket was closed, in which its creator can still reopen it.
+    /// Defaults to `14` if not set.
+    #[serde(default = "default_reopen_window")]
+    pub reopen_window: u32,
+    /// Amounts of votes at which the priority of a ticket is raised by one level each. Empty if
+    /// not set, which disables any automatic priority changes by votes.
+    #[serde(default)]
+    pub vote_thresholds: Vec<u32>,
+    /// Amount of minutes, after a comment was written, in which its writer can still edit it.
+    /// Defaults to `30` if not set.
+    #[serde(default = "default_comment_edit_window")]
+    pub comment_edit_window: u32,
+    /// Service level targets for each priority and settings for the escalation of tickets that
+    /// miss them.
+    #[serde(default)]
+    pub sla: SlaConfig,
+    /// Amount of public reports that can be sent from the same IP address within an hour.
+    /// Defaults to `5` if not set.
+    #[serde(default = "default_public_report_limit")]
+    pub public_report_limit: u32,
+}
+
+impl Default for TicketConfig {
+    fn default() -> Self {
+        Self {
+            reopen_window: default_reopen_window(),
+            vote_thresholds: Vec::new(),
+            comment_edit_window: default_comment_edit_window(),
+            sla: SlaConfig::default(),
+            public_report_limit: default_public_report_limit(),
+        }
+    }
+}
+
+/// Configuration values for service level agreements (SLA), that define until when tickets
+/// should be answered and resolved, depending on their priority.
+#[derive(Clone, Deserialize)]
+pub struct SlaConfig {
+    /// Targets for [`Priority::Critical`] tickets. Defaults to `4` hours for the first response
+    /// and `24` hours for the resolution if not set.
+    #[serde(default = "default_sla_critical")]
+    pub critical: SlaTarget,
+    /// Targets for [`Priority::High`] tickets. Defaults to `24` hours for the first response and
+    /// `72` hours for the resolution if not set.
+    #[serde(default = "default_sla_high")]
+    pub high: SlaTarget,
+    /// Targets for [`Priority::Medium`] tickets. Defaults to `48` hours for the first response
+    /// and `168` hours for the resolution if not set.
+    #[serde(default = "default_sla_medium")]
+    pub medium: SlaTarget,
+    /// Targets for [`Priority::Low`] tickets. Defaults to `72` hours for the first response and
+    /// `336` hours for the resolution if not set.
+    #[serde(default = "default_sla_low")]
+    pub low: SlaTarget,
+    /// Amount of minutes between two checks for overdue tickets. Defaults to `15` if not set.
+    #[serde(default = "default_check_interval")]
+    pub check_interval: u64,
+    /// Amount of hours after notifying the assignee about an overdue ticket, before the course's
+    /// author and the admins are notified as well. Defaults to `24` if not set.
+    #[serde(default = "default_escalation_delay")]
+    pub escalation_delay: u32,
+}
+
+impl SlaConfig {
+    /// Get the targets for the given priority.
+    pub const fn target(&self, priority: Priority) -> &SlaTarget {
+        match priority {
+            Priority::Critical => &self.critical,
+            Priority::High => &self.high,
+            Priority::Medium => &self.medium,
+            Priority::Low => &self.low,
+        }
+    }
+}
+
+impl Default for SlaConfig {
+    fn default() -> Self {
+        Self {
+            critical: default_sla_critical(),
+            high: default_sla_high(),
+            medium: default_sla_medium(),
+            low: default_sla_low(),
+            check_interval: default_check_interval(),
+            escalation_delay: default_escalation_delay(),
+        }
+    }
+}
+
+/// Service level targets for a single priority.
+#[derive(Clone, Deserialize)]
+pub struct SlaTarget {
+    /// Amount of hours, after a ticket was created, in which a staff member should answer it.
+    pub first_response: u32,
+    /// Amount of hours, after a ticket was created, in which it should be closed.
+    pub resolution: u32,
+}
+
+/// Default value for [`SlaConfig::critical`].
+const fn default_sla_critical() -> SlaTarget {
+    SlaTarget {
+        first_response: 4,
+        resolution: 24,
+    }
+}
+
+/// Default value for [`SlaConfig::high`].
+const fn default_sla_high() -> SlaTarget {
+    SlaTarget {
+        first_response: 24,
+        resolution: 72,
+    }
+}
+
+/// Default value for [`SlaConfig::medium`].
+const fn default_sla_medium() -> SlaTarget {
+    SlaTarget {
+        first_response: 48,
+        resolution: 168,
+    }
+}
+
+/// Default value for [`SlaConfig::low`].
+const fn default_sla_low() -> SlaTarget {
+    SlaTarget {
+        first_response: 72,
+        resolution: 336,
+    }
+}
+
+/// Default value for [`SlaConfig::check_interval`].
+const fn default_check_interval() -> u64 {
+    15
+}
+
+/// Default value for [`SlaConfig::escalation_delay`].
+const fn default_escalation_delay() -> u32 {
+    24
+}
+
+/// Default value for [`TicketConfig::reopen_window`].
+const fn default_reopen_window() -> u32 {
+    14
+}
+
+/// Default value for [`TicketConfig::comment_edit_window`].
+const fn default_comment_edit_window() -> u32 {
+    30
+}
+
+/// Default value for [`TicketConfig::public_report_limit`].
+const fn default_public_report_limit() -> u32 {
+    5
+}
+
+/// Configuration values for file attachments.
+#[derive(Clone, Deserialize)]
+pub struct AttachmentConfig {
+    /// Maximum size of a single file in bytes. Defaults to 5 MiB if not set.
+    #[serde(default = "default_max_size")]
+    pub max_size: u64,
+    /// Maximum amount of files in a single upload. Defaults to `5` if not set.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    /// List of allowed MIME types. The type is detected from the file content, not its name.
+    /// Defaults to common image formats and PDF files if not set.
+    #[serde(default = "default_allowed_types")]
+    pub allowed_types: Vec<String>,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            max_size: default_max_size(),
+            max_files: default_max_files(),
+            allowed_types: default_allowed_types(),
+        }
+    }
+}
+
+/// Default value for [`AttachmentConfig::max_size`].
+const fn default_max_size() -> u64 {
+    5 * 1024 * 1024
+}
+
+/// Default value for [`AttachmentConfig::max_files`].
+const fn default_max_files() -> usize {
+    5
+}
+
+/// Default value for [`AttachmentConfig::allowed_types`].
+fn default_allowed_types() -> Vec<String> {
+    [
+        "image/png",
+        "image/jpeg",
+        "image/gif",
+        "image/webp",
+        "application/pdf",
+    ]
+    .iter()
+    .map(|&t| t.to_owned())
+    .collect()
+}
+
 /// Load a Rocket [`RocketConfig`] based on custom configuration file.
 pub fn load() -> Result<(RocketConfig, Config)> {
     let file_config = load_file()?;
@@ -94,5 +308,7 @@ fn load_file() -> Result<Config> {
             username: String::new(),
             password: String::new(),
         },
+        tickets: TicketConfig::default(),
+        attachments: AttachmentConfig::default(),
     })
 }
@@ -4,7 +4,7 @@ use std::fs;
 use std::ops::Deref;
 
 use diesel::r2d2::{
-    ConnectionManager, CustomizeConnection, ManageConnection, Pool, PooledConnection,
+    ConnectionManager, CustomizeConnection, ManageConnection, Pool, PoolError, PooledConnection,
 };
 use diesel::{Connection, SqliteConnection};
 use rocket::fairing::{AdHoc, Fairing};
@@ -93,6 +93,25 @@ impl DbConn {
     }
 }
 
+/// Handle to the database pool, to get connections outside of requests, like in background
+/// threads.
+#[derive(Clone)]
+pub struct DbPool(Pool<ConnectionManager<SqliteConnection>>);
+
+impl DbPool {
+    /// Get the pool from a Rocket instance, if the [`DbConn::fairing`] was attached to it.
+    pub fn from_rocket(rocket: &rocket::Rocket) -> Option<Self> {
+        rocket
+            .state::<DbConnPool>()
+            .map(|pool| Self(pool.0.clone()))
+    }
+
+    /// Get a single connection from the pool.
+    pub fn get(&self) -> Result<DbConn, PoolError> {
+        self.0.get().map(DbConn)
+    }
+}
+
 impl Deref for DbConn {
     type Target = SqliteConnection;
 
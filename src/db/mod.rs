@@ -280,3 +280,156 @@ where
         self
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::prelude::*;
+    use diesel::SqliteConnection;
+
+    use super::models::{InitCourseEntity, InitUserEntity};
+    use crate::models::Role;
+
+    /// Create a fresh in-memory database with all migrations applied, but without any samples.
+    pub fn connection() -> SqliteConnection {
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.batch_execute("PRAGMA foreign_keys = ON;").unwrap();
+        super::embedded_migrations::run(&conn).unwrap();
+        conn
+    }
+
+    /// Insert an active user with the given role, returning its ID.
+    pub fn insert_user(conn: &SqliteConnection, username: &str, role: Role) -> i32 {
+        use super::schema::users;
+
+        diesel::insert_into(users::table)
+            .values(&InitUserEntity {
+                username: username.to_owned(),
+                password: String::new(),
+                name: username.to_owned(),
+                role: role.as_ref().to_owned(),
+                active: true,
+            })
+            .execute(conn)
+            .unwrap();
+
+        users::table
+            .filter(users::username.eq(username))
+            .select(users::id)
+            .get_result(conn)
+            .unwrap()
+    }
+
+    /// Insert an active course, returning its ID.
+    pub fn insert_course(
+        conn: &SqliteConnection,
+        code: &str,
+        author_id: i32,
+        tutor_id: i32,
+    ) -> i32 {
+        use super::schema::courses;
+
+        diesel::insert_into(courses::table)
+            .values(&InitCourseEntity {
+                code: code.to_owned(),
+                title: code.to_owned(),
+                author_id,
+                tutor_id,
+                active: true,
+            })
+            .execute(conn)
+            .unwrap();
+
+        courses::table
+            .filter(courses::code.eq(code))
+            .select(courses::id)
+            .get_result(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn migrate_single_locations() {
+        use std::fs;
+        use std::path::Path;
+
+        use super::schema::{
+            medium_interactives, medium_questionaires, medium_recordings, medium_texts,
+            public_report_locations,
+        };
+
+        const LOCATIONS: &str = "2020-07-13-090000_medium_locations";
+
+        let mut dirs = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.join("up.sql").exists())
+            .collect::<Vec<_>>();
+        dirs.sort();
+
+        let up = |dir: &Path| fs::read_to_string(dir.join("up.sql")).unwrap();
+
+        // Foreign keys are disabled, so the old rows don't need any tickets or courses.
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.batch_execute("PRAGMA foreign_keys = OFF;").unwrap();
+
+        for dir in dirs.iter().take_while(|dir| !dir.ends_with(LOCATIONS)) {
+            conn.batch_execute(&up(dir)).unwrap();
+        }
+
+        conn.batch_execute(
+            "INSERT INTO medium_texts (ticket_id, page, line) VALUES (1, 3, 7);
+            INSERT INTO medium_recordings (ticket_id, time) VALUES (2, '00:01:30');
+            INSERT INTO medium_interactives (ticket_id, url) VALUES (3, 'https://example.com/');
+            INSERT INTO medium_questionaires (ticket_id, question, answer) VALUES (4, 5, 'B');
+            INSERT INTO public_reports
+                (id, course_id, type, category, title, description, page, line, ip_address,
+                created_at)
+            VALUES
+                (1, 1, 'course-book', 'content', 'a', 'b', 8, 2, '127.0.0.1',
+                '2020-07-01T00:00:00+00:00');",
+        )
+        .unwrap();
+
+        let locations = dirs.iter().find(|dir| dir.ends_with(LOCATIONS)).unwrap();
+        conn.batch_execute(&up(locations)).unwrap();
+
+        assert_eq!(
+            vec![(1, 0, 3, 7, None, None)],
+            medium_texts::table
+                .load::<(i32, i32, i32, i32, Option<i32>, Option<i32>)>(&conn)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(2, 0, "00:01:30".to_owned(), None)],
+            medium_recordings::table
+                .load::<(i32, i32, String, Option<String>)>(&conn)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(3, 0, "https://example.com/".to_owned())],
+            medium_interactives::table
+                .load::<(i32, i32, String)>(&conn)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(4, 0, 5, None, "B".to_owned())],
+            medium_questionaires::table
+                .load::<(i32, i32, i32, Option<i32>, String)>(&conn)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![(1, 0, Some(8), Some(2), None, None)],
+            public_report_locations::table
+                .select((
+                    public_report_locations::report_id,
+                    public_report_locations::position,
+                    public_report_locations::page,
+                    public_report_locations::line,
+                    public_report_locations::end_page,
+                    public_report_locations::end_line,
+                ))
+                .load::<(i32, i32, Option<i32>, Option<i32>, Option<i32>, Option<i32>)>(&conn)
+                .unwrap()
+        );
+    }
+}
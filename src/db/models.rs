@@ -2,7 +2,7 @@
 
 use std::convert::{TryFrom, TryInto};
 
-use chrono::{DateTime, NaiveTime};
+use chrono::{DateTime, NaiveTime, Utc};
 use serde::Deserialize;
 
 use super::schema::*;
@@ -111,6 +111,7 @@ pub struct CourseEntity {
     pub author_id: i32,
     pub tutor_id: i32,
     pub active: bool,
+    pub public_reports: bool,
 }
 
 impl TryFrom<CourseEntity> for Course {
@@ -124,10 +125,83 @@ impl TryFrom<CourseEntity> for Course {
             author_id: value.author_id,
             tutor_id: value.tutor_id,
             active: value.active,
+            public_reports: value.public_reports,
         })
     }
 }
 
+/// A new faculty to be added to the database.
+#[derive(Insertable)]
+#[table_name = "faculties"]
+pub struct NewFacultyEntity {
+    pub name: String,
+}
+
+/// A full faculty entity equivalent to the `faculties` table.
+#[derive(Queryable)]
+pub struct FacultyEntity {
+    pub id: i32,
+    pub name: String,
+}
+
+impl TryFrom<FacultyEntity> for Faculty {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FacultyEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            name: value.name,
+        })
+    }
+}
+
+/// A new program to be added to the database.
+#[derive(Insertable)]
+#[table_name = "programs"]
+pub struct NewProgramEntity {
+    pub faculty_id: i32,
+    pub code: String,
+    pub title: String,
+}
+
+/// A full program entity equivalent to the `programs` table.
+#[derive(Queryable)]
+pub struct ProgramEntity {
+    pub id: i32,
+    pub faculty_id: i32,
+    pub code: String,
+    pub title: String,
+}
+
+impl TryFrom<ProgramEntity> for Program {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ProgramEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            faculty_id: value.faculty_id,
+            code: value.code,
+            title: value.title,
+        })
+    }
+}
+
+/// The assignment of a course to a program, equivalent to the `course_programs` table.
+#[derive(Insertable)]
+#[table_name = "course_programs"]
+pub struct CourseProgramEntity {
+    pub course_id: i32,
+    pub program_id: i32,
+}
+
+/// The assignment of a manager to a program, equivalent to the `program_managers` table.
+#[derive(Insertable)]
+#[table_name = "program_managers"]
+pub struct ProgramManagerEntity {
+    pub program_id: i32,
+    pub user_id: i32,
+}
+
 /// A special new ticket that is used during first initialization of the database.
 #[derive(Insertable, Deserialize)]
 #[table_name = "tickets"]
@@ -142,6 +216,7 @@ pub struct InitTicketEntity {
     pub forwarded: bool,
     pub course_id: i32,
     pub creator_id: i32,
+    pub assignee_id: Option<i32>,
 }
 
 /// A new ticket to be added to the database.
@@ -155,10 +230,11 @@ pub struct NewTicketEntity {
     pub priority: String,
     pub course_id: i32,
     pub creator_id: i32,
+    pub created_at: String,
 }
 
-impl From<(NewTicket, Priority)> for NewTicketEntity {
-    fn from(value: (NewTicket, Priority)) -> Self {
+impl From<(NewTicket, Priority, DateTime<Utc>)> for NewTicketEntity {
+    fn from(value: (NewTicket, Priority, DateTime<Utc>)) -> Self {
         Self {
             type_: value.0.type_.to_string(),
             title: value.0.title,
@@ -167,6 +243,7 @@ impl From<(NewTicket, Priority)> for NewTicketEntity {
             priority: value.1.to_string(),
             course_id: value.0.course_id,
             creator_id: value.0.creator_id,
+            created_at: value.2.to_rfc3339(),
         }
     }
 }
@@ -184,6 +261,15 @@ pub struct TicketEntity {
     pub forwarded: bool,
     pub course_id: i32,
     pub creator_id: i32,
+    pub assignee_id: Option<i32>,
+    pub closed_at: Option<String>,
+    pub reopen_count: i32,
+    pub duplicate_of: Option<i32>,
+    pub votes: i32,
+    pub created_at: String,
+    pub first_response_at: Option<String>,
+    pub escalation_level: i32,
+    pub escalated_at: Option<String>,
 }
 
 impl TryFrom<TicketEntity> for Ticket {
@@ -201,89 +287,160 @@ impl TryFrom<TicketEntity> for Ticket {
             forwarded: value.forwarded,
             course_id: value.course_id,
             creator_id: value.creator_id,
+            assignee_id: value.assignee_id,
+            closed_at: value
+                .closed_at
+                .map(|ts| DateTime::parse_from_rfc3339(&ts).map(Into::into))
+                .transpose()?,
+            reopen_count: value.reopen_count.try_into()?,
+            duplicate_of: value.duplicate_of,
+            votes: value.votes.try_into()?,
+            created_at: DateTime::parse_from_rfc3339(&value.created_at)?.into(),
+            first_response_at: value
+                .first_response_at
+                .map(|ts| DateTime::parse_from_rfc3339(&ts).map(Into::into))
+                .transpose()?,
+            escalation_level: value.escalation_level.try_into()?,
+            escalated_at: value
+                .escalated_at
+                .map(|ts| DateTime::parse_from_rfc3339(&ts).map(Into::into))
+                .transpose()?,
         })
     }
 }
 
 /// A full text medium entity equivalent to the`medium_texts` table, also representing a new entry
-/// that can be added to the system.
+/// that can be added to the system. Each entry is a single location of a ticket's medium.
 #[derive(Queryable, Insertable, Deserialize)]
 #[table_name = "medium_texts"]
 pub struct MediumTextEntity {
     pub ticket_id: i32,
+    #[serde(default)]
+    pub position: i32,
     pub page: i32,
     pub line: i32,
+    pub end_page: Option<i32>,
+    pub end_line: Option<i32>,
 }
 
-impl TryFrom<MediumTextEntity> for Medium {
+impl From<(i32, i32, Span<TextPosition>)> for MediumTextEntity {
+    fn from((ticket_id, position, span): (i32, i32, Span<TextPosition>)) -> Self {
+        Self {
+            ticket_id,
+            position,
+            page: span.start.page.into(),
+            line: span.start.line.into(),
+            end_page: span.end.map(|end| end.page.into()),
+            end_line: span.end.map(|end| end.line.into()),
+        }
+    }
+}
+
+impl TryFrom<MediumTextEntity> for Span<TextPosition> {
     type Error = anyhow::Error;
 
     fn try_from(value: MediumTextEntity) -> Result<Self, Self::Error> {
-        Ok(Self::Text {
-            ticket_id: value.ticket_id,
-            page: value.page.try_into()?,
-            line: value.line.try_into()?,
+        Ok(Self {
+            start: TextPosition {
+                page: value.page.try_into()?,
+                line: value.line.try_into()?,
+            },
+            end: match (value.end_page, value.end_line) {
+                (Some(page), Some(line)) => Some(TextPosition {
+                    page: page.try_into()?,
+                    line: line.try_into()?,
+                }),
+                _ => None,
+            },
         })
     }
 }
 
 /// A full recording medium entity equivalent to the`medium_recordings` table, also representing a
-/// new entry that can be added to the system.
+/// new entry that can be added to the system. Each entry is a single location of a ticket's
+/// medium.
 #[derive(Queryable, Insertable, Deserialize)]
 #[table_name = "medium_recordings"]
 pub struct MediumRecordingEntity {
     pub ticket_id: i32,
+    #[serde(default)]
+    pub position: i32,
     pub time: String,
+    pub end_time: Option<String>,
+}
+
+impl From<(i32, i32, Span<NaiveTime>)> for MediumRecordingEntity {
+    fn from((ticket_id, position, span): (i32, i32, Span<NaiveTime>)) -> Self {
+        Self {
+            ticket_id,
+            position,
+            time: span.start.format("%H:%M:%S").to_string(),
+            end_time: span.end.map(|end| end.format("%H:%M:%S").to_string()),
+        }
+    }
 }
 
-impl TryFrom<MediumRecordingEntity> for Medium {
+impl TryFrom<MediumRecordingEntity> for Span<NaiveTime> {
     type Error = anyhow::Error;
 
     fn try_from(value: MediumRecordingEntity) -> Result<Self, Self::Error> {
-        Ok(Self::Recording {
-            ticket_id: value.ticket_id,
-            time: NaiveTime::parse_from_str(&value.time, "%H:%M:%S")?,
+        Ok(Self {
+            start: NaiveTime::parse_from_str(&value.time, "%H:%M:%S")?,
+            end: value
+                .end_time
+                .map(|end| NaiveTime::parse_from_str(&end, "%H:%M:%S"))
+                .transpose()?,
         })
     }
 }
 
 /// A full interactive medium entity equivalent to the`medium_interactives` table, also representing
-/// a new entry that can be added to the system.
+/// a new entry that can be added to the system. Each entry is a single location of a ticket's
+/// medium.
 #[derive(Queryable, Insertable, Deserialize)]
 #[table_name = "medium_interactives"]
 pub struct MediumInteractiveEntity {
     pub ticket_id: i32,
+    #[serde(default)]
+    pub position: i32,
     pub url: String,
 }
 
-impl TryFrom<MediumInteractiveEntity> for Medium {
-    type Error = anyhow::Error;
-
-    fn try_from(value: MediumInteractiveEntity) -> Result<Self, Self::Error> {
-        Ok(Self::Interactive {
-            ticket_id: value.ticket_id,
-            url: value.url.parse()?,
-        })
-    }
-}
-
 /// A full questionaire medium entity equivalent to the`medium_questionaires` table, also
-/// representing a new entry that can be added to the system.
+/// representing a new entry that can be added to the system. Each entry is a single location of a
+/// ticket's medium.
 #[derive(Queryable, Insertable, Deserialize)]
 #[table_name = "medium_questionaires"]
 pub struct MediumQuestionaireEntity {
     pub ticket_id: i32,
+    #[serde(default)]
+    pub position: i32,
     pub question: i32,
+    pub end_question: Option<i32>,
     pub answer: String,
 }
 
-impl TryFrom<MediumQuestionaireEntity> for Medium {
+impl From<(i32, i32, QuestionLocation)> for MediumQuestionaireEntity {
+    fn from((ticket_id, position, location): (i32, i32, QuestionLocation)) -> Self {
+        Self {
+            ticket_id,
+            position,
+            question: location.questions.start.into(),
+            end_question: location.questions.end.map(Into::into),
+            answer: location.answer,
+        }
+    }
+}
+
+impl TryFrom<MediumQuestionaireEntity> for QuestionLocation {
     type Error = anyhow::Error;
 
     fn try_from(value: MediumQuestionaireEntity) -> Result<Self, Self::Error> {
-        Ok(Self::Questionaire {
-            ticket_id: value.ticket_id,
-            question: value.question.try_into()?,
+        Ok(Self {
+            questions: Span {
+                start: value.question.try_into()?,
+                end: value.end_question.map(TryInto::try_into).transpose()?,
+            },
             answer: value.answer,
         })
     }
@@ -297,6 +454,10 @@ pub struct CommentEntity {
     pub creator_id: i32,
     pub timestamp: String,
     pub message: String,
+    pub source_ticket_id: Option<i32>,
+    pub internal: bool,
+    pub edited_at: Option<String>,
+    pub deleted: bool,
 }
 
 impl TryFrom<CommentEntity> for Comment {
@@ -309,6 +470,13 @@ impl TryFrom<CommentEntity> for Comment {
             creator_id: value.creator_id,
             timestamp: DateTime::parse_from_rfc3339(&value.timestamp)?.into(),
             message: value.message,
+            source_ticket_id: value.source_ticket_id,
+            internal: value.internal,
+            edited_at: value
+                .edited_at
+                .map(|ts| DateTime::parse_from_rfc3339(&ts).map(Into::into))
+                .transpose()?,
+            deleted: value.deleted,
         })
     }
 }
@@ -321,6 +489,8 @@ pub struct NewCommentEntity {
     pub creator_id: i32,
     pub timestamp: String,
     pub message: String,
+    pub source_ticket_id: Option<i32>,
+    pub internal: bool,
 }
 
 impl From<NewComment> for NewCommentEntity {
@@ -330,6 +500,751 @@ impl From<NewComment> for NewCommentEntity {
             creator_id: value.creator_id,
             timestamp: value.timestamp.to_rfc3339(),
             message: value.message,
+            source_ticket_id: value.source_ticket_id,
+            internal: value.internal,
+        }
+    }
+}
+
+/// A full comment revision entity equivalent to the `comment_revisions` table.
+#[derive(Queryable)]
+pub struct CommentRevisionEntity {
+    pub id: i32,
+    pub comment_id: i32,
+    pub editor_id: i32,
+    pub timestamp: String,
+    pub message: String,
+}
+
+impl TryFrom<CommentRevisionEntity> for CommentRevision {
+    type Error = anyhow::Error;
+
+    fn try_from(value: CommentRevisionEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            comment_id: value.comment_id,
+            editor_id: value.editor_id,
+            timestamp: DateTime::parse_from_rfc3339(&value.timestamp)?.into(),
+            message: value.message,
+        })
+    }
+}
+
+/// A new comment revision to be added to the database.
+#[derive(Insertable)]
+#[table_name = "comment_revisions"]
+pub struct NewCommentRevisionEntity {
+    pub comment_id: i32,
+    pub editor_id: i32,
+    pub timestamp: String,
+    pub message: String,
+}
+
+impl From<NewCommentRevision> for NewCommentRevisionEntity {
+    fn from(value: NewCommentRevision) -> Self {
+        Self {
+            comment_id: value.comment_id,
+            editor_id: value.editor_id,
+            timestamp: value.timestamp.to_rfc3339(),
+            message: value.message,
+        }
+    }
+}
+
+/// A full ticket event entity equivalent to the `ticket_events` table.
+#[derive(Queryable)]
+pub struct TicketEventEntity {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub actor_id: i32,
+    pub timestamp: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+impl TryFrom<TicketEventEntity> for TicketEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TicketEventEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            ticket_id: value.ticket_id,
+            actor_id: value.actor_id,
+            timestamp: DateTime::parse_from_rfc3339(&value.timestamp)?.into(),
+            field: value.field.parse()?,
+            old_value: value.old_value,
+            new_value: value.new_value,
+        })
+    }
+}
+
+/// A new ticket event to be added to the database.
+#[derive(Insertable)]
+#[table_name = "ticket_events"]
+pub struct NewTicketEventEntity {
+    pub ticket_id: i32,
+    pub actor_id: i32,
+    pub timestamp: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+impl From<NewTicketEvent> for NewTicketEventEntity {
+    fn from(value: NewTicketEvent) -> Self {
+        Self {
+            ticket_id: value.ticket_id,
+            actor_id: value.actor_id,
+            timestamp: value.timestamp.to_rfc3339(),
+            field: value.field.to_string(),
+            old_value: value.old_value,
+            new_value: value.new_value,
+        }
+    }
+}
+
+/// A full ticket revision entity equivalent to the `ticket_revisions` table.
+#[derive(Queryable)]
+pub struct TicketRevisionEntity {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub editor_id: i32,
+    pub timestamp: String,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+}
+
+impl TryFrom<TicketRevisionEntity> for TicketRevision {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TicketRevisionEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            ticket_id: value.ticket_id,
+            editor_id: value.editor_id,
+            timestamp: DateTime::parse_from_rfc3339(&value.timestamp)?.into(),
+            title: value.title,
+            description: value.description,
+            location: value.location,
+        })
+    }
+}
+
+/// A new ticket revision to be added to the database.
+#[derive(Insertable)]
+#[table_name = "ticket_revisions"]
+pub struct NewTicketRevisionEntity {
+    pub ticket_id: i32,
+    pub editor_id: i32,
+    pub timestamp: String,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+}
+
+impl From<NewTicketRevision> for NewTicketRevisionEntity {
+    fn from(value: NewTicketRevision) -> Self {
+        Self {
+            ticket_id: value.ticket_id,
+            editor_id: value.editor_id,
+            timestamp: value.timestamp.to_rfc3339(),
+            title: value.title,
+            description: value.description,
+            location: value.location,
+        }
+    }
+}
+
+/// A full attachment entity equivalent to the `attachments` table.
+#[derive(Queryable)]
+pub struct AttachmentEntity {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub comment_id: Option<i32>,
+    pub creator_id: i32,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub timestamp: String,
+}
+
+impl TryFrom<AttachmentEntity> for Attachment {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AttachmentEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            ticket_id: value.ticket_id,
+            comment_id: value.comment_id,
+            creator_id: value.creator_id,
+            file_name: value.file_name,
+            mime_type: value.mime_type,
+            size: value.size.try_into()?,
+            timestamp: DateTime::parse_from_rfc3339(&value.timestamp)?.into(),
+        })
+    }
+}
+
+/// A new attachment to be added to the database.
+#[derive(Insertable)]
+#[table_name = "attachments"]
+pub struct NewAttachmentEntity {
+    pub ticket_id: i32,
+    pub comment_id: Option<i32>,
+    pub creator_id: i32,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub timestamp: String,
+}
+
+impl TryFrom<NewAttachment> for NewAttachmentEntity {
+    type Error = anyhow::Error;
+
+    fn try_from(value: NewAttachment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            ticket_id: value.ticket_id,
+            comment_id: value.comment_id,
+            creator_id: value.creator_id,
+            file_name: value.file_name,
+            mime_type: value.mime_type,
+            size: value.size.try_into()?,
+            timestamp: value.timestamp.to_rfc3339(),
+        })
+    }
+}
+
+/// A full ticket link entity equivalent to the `ticket_links` table.
+#[derive(Queryable)]
+pub struct TicketLinkEntity {
+    pub id: i32,
+    pub source_id: i32,
+    pub target_id: i32,
+    pub kind: String,
+}
+
+impl TryFrom<TicketLinkEntity> for TicketLink {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TicketLinkEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            source_id: value.source_id,
+            target_id: value.target_id,
+            kind: value.kind.parse()?,
+        })
+    }
+}
+
+/// A new ticket link to be added to the database.
+#[derive(Insertable)]
+#[table_name = "ticket_links"]
+pub struct NewTicketLinkEntity {
+    pub source_id: i32,
+    pub target_id: i32,
+    pub kind: String,
+}
+
+impl From<NewTicketLink> for NewTicketLinkEntity {
+    /// Convert into an entity, storing non-canonical link types as their inverse.
+    fn from(value: NewTicketLink) -> Self {
+        if value.kind.is_canonical() {
+            Self {
+                source_id: value.source_id,
+                target_id: value.target_id,
+                kind: value.kind.to_string(),
+            }
+        } else {
+            Self {
+                source_id: value.target_id,
+                target_id: value.source_id,
+                kind: value.kind.inverse().to_string(),
+            }
+        }
+    }
+}
+
+/// A new workflow status to be added to the database.
+#[derive(Insertable)]
+#[table_name = "workflow_statuses"]
+pub struct NewWorkflowStatusEntity {
+    pub key: String,
+    pub name: String,
+    pub color: String,
+    pub icon: String,
+    pub terminal: bool,
+    pub position: i32,
+}
+
+impl From<(WorkflowStatus, i32)> for NewWorkflowStatusEntity {
+    fn from(value: (WorkflowStatus, i32)) -> Self {
+        Self {
+            key: value.0.key.to_string(),
+            name: value.0.name,
+            color: value.0.color.to_string(),
+            icon: value.0.icon,
+            terminal: value.0.terminal,
+            position: value.1,
+        }
+    }
+}
+
+/// A full workflow status entity equivalent to the `workflow_statuses` table.
+#[derive(Queryable)]
+pub struct WorkflowStatusEntity {
+    pub key: String,
+    pub name: String,
+    pub color: String,
+    pub icon: String,
+    pub terminal: bool,
+    pub position: i32,
+}
+
+impl TryFrom<WorkflowStatusEntity> for WorkflowStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(value: WorkflowStatusEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: value.key.parse()?,
+            name: value.name,
+            color: value.color.parse()?,
+            icon: value.icon,
+            terminal: value.terminal,
+        })
+    }
+}
+
+/// A workflow transition entity equivalent to the `workflow_transitions` table.
+#[derive(Insertable, Queryable)]
+#[table_name = "workflow_transitions"]
+pub struct TransitionEntity {
+    pub from_status: String,
+    pub to_status: String,
+    pub role: String,
+}
+
+impl From<Transition> for TransitionEntity {
+    fn from(value: Transition) -> Self {
+        Self {
+            from_status: value.from.to_string(),
+            to_status: value.to.to_string(),
+            role: value.role.to_string(),
+        }
+    }
+}
+
+impl TryFrom<TransitionEntity> for Transition {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TransitionEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            from: value.from_status.parse()?,
+            to: value.to_status.parse()?,
+            role: value.role.parse()?,
+        })
+    }
+}
+
+/// A full label entity equivalent to the `labels` table.
+#[derive(Queryable)]
+pub struct LabelEntity {
+    pub id: i32,
+    pub name: String,
+    pub color: String,
+    pub course_id: Option<i32>,
+}
+
+impl TryFrom<LabelEntity> for Label {
+    type Error = anyhow::Error;
+
+    fn try_from(value: LabelEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            name: value.name,
+            color: value.color.parse()?,
+            course_id: value.course_id,
+        })
+    }
+}
+
+/// A new label to be added to the database.
+#[derive(Insertable)]
+#[table_name = "labels"]
+pub struct NewLabelEntity {
+    pub name: String,
+    pub color: String,
+    pub course_id: Option<i32>,
+}
+
+impl From<NewLabel> for NewLabelEntity {
+    fn from(value: NewLabel) -> Self {
+        Self {
+            name: value.name,
+            color: value.color.to_string(),
+            course_id: value.course_id,
+        }
+    }
+}
+
+/// A full rule entity equivalent to the `ticket_rules` table.
+#[derive(Queryable)]
+pub struct RuleEntity {
+    pub id: i32,
+    pub name: String,
+    pub course_id: Option<i32>,
+    pub type_: Option<String>,
+    pub category: Option<String>,
+    pub medium: Option<String>,
+    pub keywords: String,
+    pub priority: Option<String>,
+    pub forward: bool,
+    pub assignee_id: Option<i32>,
+}
+
+impl TryFrom<RuleEntity> for Rule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RuleEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            name: value.name,
+            course_id: value.course_id,
+            type_: value.type_.map(|v| v.parse()).transpose()?,
+            category: value.category.map(|v| v.parse()).transpose()?,
+            medium: value.medium.map(|v| v.parse()).transpose()?,
+            keywords: value
+                .keywords
+                .split(',')
+                .filter(|k| !k.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+            priority: value.priority.map(|v| v.parse()).transpose()?,
+            forward: value.forward,
+            assignee_id: value.assignee_id,
+            labels: Vec::new(),
+        })
+    }
+}
+
+/// A new rule to be added to the database.
+#[derive(Insertable)]
+#[table_name = "ticket_rules"]
+pub struct NewRuleEntity {
+    pub name: String,
+    pub course_id: Option<i32>,
+    pub type_: Option<String>,
+    pub category: Option<String>,
+    pub medium: Option<String>,
+    pub keywords: String,
+    pub priority: Option<String>,
+    pub forward: bool,
+    pub assignee_id: Option<i32>,
+}
+
+impl From<NewRule> for NewRuleEntity {
+    fn from(value: NewRule) -> Self {
+        Self {
+            name: value.name,
+            course_id: value.course_id,
+            type_: value.type_.as_ref().map(ToString::to_string),
+            category: value.category.as_ref().map(ToString::to_string),
+            medium: value.medium.as_ref().map(ToString::to_string),
+            keywords: value.keywords.join(","),
+            priority: value.priority.as_ref().map(ToString::to_string),
+            forward: value.forward,
+            assignee_id: value.assignee_id,
+        }
+    }
+}
+
+/// A full intake template entity equivalent to the `ticket_templates` table. The fields are stored
+/// as a single text with one label per line.
+#[derive(Queryable, Insertable)]
+#[table_name = "ticket_templates"]
+pub struct TicketTemplateEntity {
+    pub type_: String,
+    pub description: String,
+    pub help: String,
+    pub fields: String,
+}
+
+impl TryFrom<TicketTemplateEntity> for TicketTemplate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TicketTemplateEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            type_: value.type_.parse()?,
+            description: value.description,
+            help: value.help,
+            fields: value
+                .fields
+                .lines()
+                .filter(|f| !f.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+        })
+    }
+}
+
+impl From<TicketTemplate> for TicketTemplateEntity {
+    fn from(value: TicketTemplate) -> Self {
+        Self {
+            type_: value.type_.to_string(),
+            description: value.description,
+            help: value.help,
+            fields: value.fields.join("\n"),
+        }
+    }
+}
+
+/// A full ticket field entity equivalent to the `ticket_fields` table.
+#[derive(Queryable, Insertable)]
+#[table_name = "ticket_fields"]
+pub struct TicketFieldEntity {
+    pub ticket_id: i32,
+    pub position: i32,
+    pub label: String,
+    pub value: String,
+}
+
+impl From<TicketFieldEntity> for TicketField {
+    fn from(value: TicketFieldEntity) -> Self {
+        Self {
+            label: value.label,
+            value: value.value,
+        }
+    }
+}
+
+/// A new public report to be added to the database.
+#[derive(Insertable)]
+#[table_name = "public_reports"]
+pub struct NewPublicReportEntity {
+    pub course_id: i32,
+    pub type_: String,
+    pub category: String,
+    pub title: String,
+    pub description: String,
+    pub contact_email: Option<String>,
+    pub ip_address: String,
+    pub created_at: String,
+}
+
+/// A full public report entity equivalent to the `public_reports` table.
+#[derive(Queryable)]
+pub struct PublicReportEntity {
+    pub id: i32,
+    pub course_id: i32,
+    pub type_: String,
+    pub category: String,
+    pub title: String,
+    pub description: String,
+    pub contact_email: Option<String>,
+    pub ip_address: String,
+    pub created_at: String,
+    pub ticket_id: Option<i32>,
+    pub rejected: bool,
+}
+
+impl
+    TryFrom<(
+        PublicReportEntity,
+        Vec<PublicReportLocationEntity>,
+        Vec<PublicReportFieldEntity>,
+    )> for PublicReport
+{
+    type Error = anyhow::Error;
+
+    fn try_from(
+        (report, locations, fields): (
+            PublicReportEntity,
+            Vec<PublicReportLocationEntity>,
+            Vec<PublicReportFieldEntity>,
+        ),
+    ) -> Result<Self, Self::Error> {
+        let type_ = report.type_.parse::<TicketType>()?;
+
+        Ok(Self {
+            id: report.id,
+            course_id: report.course_id,
+            type_,
+            category: report.category.parse()?,
+            title: report.title,
+            description: report.description,
+            medium: PublicReportLocationEntity::into_medium(type_.medium(), locations)?,
+            fields: fields.into_iter().map(Into::into).collect(),
+            contact_email: report.contact_email,
+            created_at: DateTime::parse_from_rfc3339(&report.created_at)?.into(),
+        })
+    }
+}
+
+/// A full public report location entity equivalent to the `public_report_locations` table, also
+/// representing a new entry that can be added to the system. Only the columns that belong to the
+/// report type's medium are set.
+#[derive(Queryable, Insertable)]
+#[table_name = "public_report_locations"]
+pub struct PublicReportLocationEntity {
+    pub report_id: i32,
+    pub position: i32,
+    pub page: Option<i32>,
+    pub line: Option<i32>,
+    pub end_page: Option<i32>,
+    pub end_line: Option<i32>,
+    pub time: Option<String>,
+    pub end_time: Option<String>,
+    pub url: Option<String>,
+    pub question: Option<i32>,
+    pub end_question: Option<i32>,
+    pub answer: Option<String>,
+}
+
+impl PublicReportLocationEntity {
+    /// An empty location at the given position, without any medium columns set.
+    const fn empty(report_id: i32, position: i32) -> Self {
+        Self {
+            report_id,
+            position,
+            page: None,
+            line: None,
+            end_page: None,
+            end_line: None,
+            time: None,
+            end_time: None,
+            url: None,
+            question: None,
+            end_question: None,
+            answer: None,
+        }
+    }
+
+    /// Split a medium into its single locations.
+    pub fn from_medium(report_id: i32, medium: NewMedium) -> Vec<Self> {
+        match medium {
+            NewMedium::Text { locations } => locations
+                .into_iter()
+                .zip(0..)
+                .map(|(span, position)| Self {
+                    page: Some(span.start.page.into()),
+                    line: Some(span.start.line.into()),
+                    end_page: span.end.map(|end| end.page.into()),
+                    end_line: span.end.map(|end| end.line.into()),
+                    ..Self::empty(report_id, position)
+                })
+                .collect(),
+            NewMedium::Recording { locations } => locations
+                .into_iter()
+                .zip(0..)
+                .map(|(span, position)| Self {
+                    time: Some(span.start.format("%H:%M:%S").to_string()),
+                    end_time: span.end.map(|end| end.format("%H:%M:%S").to_string()),
+                    ..Self::empty(report_id, position)
+                })
+                .collect(),
+            NewMedium::Interactive { urls } => urls
+                .into_iter()
+                .zip(0..)
+                .map(|(url, position)| Self {
+                    url: Some(url.into()),
+                    ..Self::empty(report_id, position)
+                })
+                .collect(),
+            NewMedium::Questionaire { locations } => locations
+                .into_iter()
+                .zip(0..)
+                .map(|(location, position)| Self {
+                    question: Some(location.questions.start.into()),
+                    end_question: location.questions.end.map(Into::into),
+                    answer: Some(location.answer),
+                    ..Self::empty(report_id, position)
+                })
+                .collect(),
+        }
+    }
+
+    /// Join single locations back into a medium of the given type, expecting them to be ordered by
+    /// their position.
+    pub fn into_medium(medium: MediumType, locations: Vec<Self>) -> anyhow::Result<NewMedium> {
+        fn column<T>(value: Option<T>, name: &str) -> anyhow::Result<T> {
+            value.ok_or_else(|| anyhow::anyhow!("public report location is missing its {}", name))
+        }
+
+        let locations = locations.into_iter();
+
+        Ok(match medium {
+            MediumType::Text => NewMedium::Text {
+                locations: locations
+                    .map(|l| {
+                        let end = match (l.end_page, l.end_line) {
+                            (Some(page), Some(line)) => Some(TextPosition {
+                                page: page.try_into()?,
+                                line: line.try_into()?,
+                            }),
+                            _ => None,
+                        };
+
+                        Ok(Span {
+                            start: TextPosition {
+                                page: column(l.page, "page")?.try_into()?,
+                                line: column(l.line, "line")?.try_into()?,
+                            },
+                            end,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+            MediumType::Recording => NewMedium::Recording {
+                locations: locations
+                    .map(|l| {
+                        Ok(Span {
+                            start: NaiveTime::parse_from_str(&column(l.time, "time")?, "%H:%M:%S")?,
+                            end: l
+                                .end_time
+                                .map(|end| NaiveTime::parse_from_str(&end, "%H:%M:%S"))
+                                .transpose()?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+            MediumType::Interactive => NewMedium::Interactive {
+                urls: locations
+                    .map(|l| Ok(column(l.url, "url")?.parse()?))
+                    .collect::<anyhow::Result<_>>()?,
+            },
+            MediumType::Questionaire => NewMedium::Questionaire {
+                locations: locations
+                    .map(|l| {
+                        Ok(QuestionLocation {
+                            questions: Span {
+                                start: column(l.question, "question")?.try_into()?,
+                                end: l.end_question.map(TryInto::try_into).transpose()?,
+                            },
+                            answer: column(l.answer, "answer")?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+        })
+    }
+}
+
+/// A full public report field entity equivalent to the `public_report_fields` table.
+#[derive(Queryable, Insertable)]
+#[table_name = "public_report_fields"]
+pub struct PublicReportFieldEntity {
+    pub report_id: i32,
+    pub position: i32,
+    pub label: String,
+    pub value: String,
+}
+
+impl From<PublicReportFieldEntity> for TicketField {
+    fn from(value: PublicReportFieldEntity) -> Self {
+        Self {
+            label: value.label,
+            value: value.value,
         }
     }
 }
@@ -1,23 +1,41 @@
 //! Abstractions over the database for easy access to the data.
 
-use std::convert::TryInto;
-use std::iter::FromIterator;
+use std::cmp::Ordering;
+use std::convert::{TryFrom, TryInto};
+use std::iter::{self, FromIterator};
+use std::mem;
 
 use anyhow::{ensure, Context, Result};
+use chrono::{DateTime, NaiveTime, Utc};
 use diesel::prelude::*;
 use diesel::result::Error as DieselError;
 use fnv::{FnvHashMap, FnvHashSet};
+use url::Url;
 
 use super::models::{
-    CommentEntity, CourseEntity, MediumInteractiveEntity, MediumQuestionaireEntity,
-    MediumRecordingEntity, MediumTextEntity, NewCommentEntity, NewCourseEntity, NewTicketEntity,
-    NewUserEntity, TicketEntity, UserEntity,
+    AttachmentEntity, CommentEntity, CommentRevisionEntity, CourseEntity, CourseProgramEntity,
+    FacultyEntity, LabelEntity, MediumInteractiveEntity, MediumQuestionaireEntity,
+    MediumRecordingEntity, MediumTextEntity, NewAttachmentEntity, NewCommentEntity,
+    NewCommentRevisionEntity, NewCourseEntity, NewFacultyEntity, NewLabelEntity, NewProgramEntity,
+    NewPublicReportEntity, NewRuleEntity, NewTicketEntity, NewTicketEventEntity,
+    NewTicketLinkEntity, NewTicketRevisionEntity, NewUserEntity, NewWorkflowStatusEntity,
+    ProgramEntity, ProgramManagerEntity, PublicReportEntity, PublicReportFieldEntity,
+    PublicReportLocationEntity, RuleEntity, TicketEntity, TicketEventEntity, TicketFieldEntity,
+    TicketLinkEntity, TicketRevisionEntity, TicketTemplateEntity, TransitionEntity, UserEntity,
+    WorkflowStatusEntity,
 };
 use super::QueryExt;
+use crate::language::Translate;
 use crate::models::{
-    Comment, CommentWithNames, Course, CourseWithNames, EditCourse, EditTicket, EditUser,
-    MediumType, NewComment, NewCourse, NewMedium, NewTicket, NewUser, Priority, Role, Status,
-    Ticket, TicketSearch, TicketWithNames, TicketWithRels, User,
+    Attachment, Comment, CommentRevisionWithNames, CommentWithNames, Course, CourseWithNames,
+    EditCourse, EditProgram, EditTicket, EditTicketContent, EditUser, EventField, Faculty, Label,
+    LabelWithNames, LinkedTicket, Medium, MediumType, MoveTicket, NewAttachment, NewComment,
+    NewCommentRevision, NewCourse, NewLabel, NewMedium, NewProgram, NewPublicReport, NewRule,
+    NewTicket, NewTicketEvent, NewTicketLink, NewTicketRevision, NewUser, Priority, Program,
+    ProgramWithRels, PublicReport, PublicReportWithNames, QuestionLocation, Role, Rule,
+    RuleWithNames, Span, Status, TextPosition, Ticket, TicketEvent, TicketEventWithNames,
+    TicketLink, TicketOrder, TicketRevisionWithNames, TicketSearch, TicketTemplate, TicketType,
+    TicketWithNames, TicketWithRels, TimelineEntry, Transition, User, Workflow, WorkflowStatus,
 };
 
 /// User related functionality.
@@ -26,12 +44,19 @@ pub trait UserRepository {
     fn find(&self, id: i32) -> Result<User>;
     /// Find a single user by its username.
     fn find_by_username(&self, username: &str) -> Result<User>;
-    /// Find the user who created a ticket.
-    fn find_ticket_creator(&self, ticket_id: i32) -> Result<User>;
+    /// List all users that watch a ticket.
+    fn list_ticket_watchers(&self, ticket_id: i32) -> Result<Vec<User>>;
     /// List all users.
     fn list(&self) -> Result<Vec<User>>;
     /// List all users' ID and name filtered by role.
     fn list_names_by_role(&self, role: Role) -> Result<Vec<(i32, String)>>;
+    /// List all users' ID and name that have at least the [`Role::Tutor`] role.
+    fn list_staff_names(&self) -> Result<Vec<(i32, String)>>;
+    /// Find all active users with any of the given usernames.
+    fn list_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>>;
+    /// Search active users by a part of their username or name, limited to the given roles.
+    /// Returns the username and name of the first few matching users.
+    fn search_names(&self, term: &str, roles: &[Role]) -> Result<Vec<(String, String)>>;
     /// Create a new user.
     fn create(&self, user: NewUser) -> Result<()>;
     /// Activate a previously created user.
@@ -71,17 +96,17 @@ impl<'a> UserRepository for UserRepositoryImpl<'a> {
             .and_then(TryInto::try_into)
     }
 
-    fn find_ticket_creator(&self, ticket_id: i32) -> Result<User> {
-        use super::schema::{tickets, users};
+    fn list_ticket_watchers(&self, ticket_id: i32) -> Result<Vec<User>> {
+        use super::schema::{ticket_watchers, users};
 
-        tickets::table
-            .find(ticket_id)
+        ticket_watchers::table
+            .filter(ticket_watchers::ticket_id.eq(ticket_id))
             .inner_join(users::table)
             .select(users::all_columns)
             .log_query()
-            .get_result::<UserEntity>(self.conn)
+            .load::<UserEntity>(self.conn)
             .map_err(Into::into)
-            .and_then(TryInto::try_into)
+            .and_then(|users| users.into_iter().map(TryInto::try_into).collect())
     }
 
     fn list(&self) -> Result<Vec<User>> {
@@ -104,6 +129,57 @@ impl<'a> UserRepository for UserRepositoryImpl<'a> {
             .map_err(Into::into)
     }
 
+    fn list_staff_names(&self) -> Result<Vec<(i32, String)>> {
+        use super::schema::users;
+
+        users::table
+            .select((users::id, users::name))
+            .filter(users::active.eq(true))
+            .filter(users::role.eq_any(&[
+                Role::Admin.as_ref(),
+                Role::Author.as_ref(),
+                Role::Tutor.as_ref(),
+            ]))
+            .order_by(users::name)
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map_err(Into::into)
+    }
+
+    fn list_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>> {
+        use super::schema::users;
+
+        users::table
+            .filter(users::active.eq(true))
+            .filter(users::username.eq_any(usernames))
+            .log_query()
+            .load::<UserEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|users| users.into_iter().map(TryInto::try_into).collect())
+    }
+
+    fn search_names(&self, term: &str, roles: &[Role]) -> Result<Vec<(String, String)>> {
+        use super::schema::users;
+
+        let pattern = format!("%{}%", term);
+        let roles = roles.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+
+        users::table
+            .select((users::username, users::name))
+            .filter(users::active.eq(true))
+            .filter(users::role.eq_any(roles))
+            .filter(
+                users::username
+                    .like(&pattern)
+                    .or(users::name.like(&pattern)),
+            )
+            .order_by(users::username)
+            .limit(10)
+            .log_query()
+            .load::<(String, String)>(self.conn)
+            .map_err(Into::into)
+    }
+
     fn create(&self, user: NewUser) -> Result<()> {
         use super::schema::users;
 
@@ -293,6 +369,7 @@ impl<'a> CourseRepository for CourseRepositoryImpl<'a> {
                 courses::title.eq(course.title),
                 courses::author_id.eq(course.author_id),
                 courses::tutor_id.eq(course.tutor_id),
+                courses::public_reports.eq(course.public_reports),
             ))
             .log_query()
             .execute(self.conn)?;
@@ -307,389 +384,2293 @@ pub fn course_repo(conn: &SqliteConnection) -> impl CourseRepository + '_ {
     CourseRepositoryImpl { conn }
 }
 
-/// Ticket related functionality.
-pub trait TicketRepository {
-    /// List all tickets together with their course and creator names.
-    fn list_with_names(&self) -> Result<Vec<TicketWithNames>>;
-    /// List all tickets by their creator ID.
-    fn list_by_creator_id(&self, creator_id: i32) -> Result<Vec<TicketWithNames>>;
-    /// List all tickets by their assignee ID. The assignee ID is the tutor or author ID of the
-    /// course that a ticket belongs to.
-    fn list_by_assignee_id(&self, assignee_id: i32) -> Result<Vec<TicketWithNames>>;
-    /// Get a single ticket by ID.
-    fn get(&self, id: i32) -> Result<Ticket>;
-    /// Get a single ticket with course and creator names.
-    fn get_with_names(&self, id: i32) -> Result<TicketWithNames>;
-    /// Get a single ticket with all related data.
-    fn get_with_rels(&self, id: i32) -> Result<TicketWithRels>;
-    /// Create a new ticket.
-    fn create(&self, ticket: NewTicket, priority: Priority, medium: NewMedium) -> Result<i32>;
-    /// Add a new comment to an existing ticket.
-    fn add_comment(&self, comment: NewComment) -> Result<()>;
-    /// Update an existing ticket.
-    fn update(&self, ticket: EditTicket) -> Result<()>;
-    /// Forward a ticket to its course's author.
-    fn forward(&self, id: i32) -> Result<()>;
-    /// Get the current status of a ticket.
-    fn get_status(&self, id: i32) -> Result<Status>;
-    /// Set the new status of a ticket.
-    fn set_status(&self, id: i32, status: Status) -> Result<()>;
-    /// Search for tickets with different criteria.
-    fn search(&self, search: &TicketSearch) -> Result<Vec<TicketWithNames>>;
-    /// Activate a new ticket, changing it to [`Status::InProgress`] if it's still in
-    /// [`Status::Open`] and accessed by a tutor or author user.
-    fn activate_ticket(&self, id: i32, user_id: i32) -> Result<bool>;
-    /// Check whether the provided user is the creator of a ticket.
-    fn is_creator(&self, id: i32, user_id: i32) -> Result<bool>;
+/// Faculty and program related functionality.
+pub trait ProgramRepository {
+    /// List all faculties.
+    fn list_faculties(&self) -> Result<Vec<Faculty>>;
+    /// List all programs.
+    fn list(&self) -> Result<Vec<Program>>;
+    /// List the names (with IDs) of all programs.
+    fn list_names(&self) -> Result<Vec<(i32, String)>>;
+    /// List all course assignments as `(course_id, program_id)` pairs.
+    fn list_course_assignments(&self) -> Result<Vec<(i32, i32)>>;
+    /// List all manager assignments as `(program_id, user_name)` pairs.
+    fn list_manager_names(&self) -> Result<Vec<(i32, String)>>;
+    /// List the IDs of all programs that the given user manages.
+    fn list_managed_ids(&self, user_id: i32) -> Result<Vec<i32>>;
+    /// Get a single program by ID, together with its course and manager IDs.
+    fn get_with_rels(&self, id: i32) -> Result<ProgramWithRels>;
+    /// Create a new faculty.
+    fn create_faculty(&self, name: String) -> Result<()>;
+    /// Create a new program.
+    fn create(&self, program: NewProgram) -> Result<()>;
+    /// Update an existing program.
+    fn update(&self, program: EditProgram) -> Result<()>;
 }
 
-/// Main implementation of [`TicketRepository`].
-struct TicketRepositoryImpl<'a> {
+/// Main implementation of [`ProgramRepository`].
+struct ProgramRepositoryImpl<'a> {
     conn: &'a SqliteConnection,
 }
 
-impl<'a> TicketRepositoryImpl<'a> {
-    /// List all tickets.
-    fn list(&self) -> Result<Vec<Ticket>> {
-        use super::schema::tickets;
-
-        tickets::table
-            .load::<TicketEntity>(self.conn)
-            .map_err(Into::into)
-            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
-    }
-
-    /// Load user and course names and attach them to the given list of tickets.
-    fn load_names(&self, tickets: Vec<Ticket>) -> Result<Vec<TicketWithNames>> {
-        use super::schema::{courses, users};
-
-        let mut user_ids = FnvHashSet::default();
-        let mut course_ids = FnvHashSet::default();
+impl<'a> ProgramRepositoryImpl<'a> {
+    /// Replace all course and manager assignments of a program.
+    fn set_assignments(&self, id: i32, course_ids: &[i32], manager_ids: &[i32]) -> Result<()> {
+        use super::schema::{course_programs, program_managers};
 
-        for ticket in &tickets {
-            user_ids.insert(ticket.creator_id);
-            course_ids.insert(ticket.course_id);
-        }
+        diesel::delete(course_programs::table.filter(course_programs::program_id.eq(id)))
+            .log_query()
+            .execute(self.conn)?;
 
-        let courses = courses::table
-            .select((
-                courses::id,
-                courses::code,
-                courses::author_id,
-                courses::tutor_id,
-            ))
-            .filter(courses::id.eq_any(&course_ids))
+        diesel::delete(program_managers::table.filter(program_managers::program_id.eq(id)))
             .log_query()
-            .load::<(i32, String, i32, i32)>(self.conn)
-            .map(|data| {
-                data.into_iter()
-                    .map(|row| (row.0, (row.1, row.2, row.3)))
-                    .collect::<FnvHashMap<_, _>>()
-            })?;
+            .execute(self.conn)?;
 
-        for (_, author_id, tutor_id) in courses.values() {
-            user_ids.insert(*author_id);
-            user_ids.insert(*tutor_id);
-        }
+        let courses = course_ids
+            .iter()
+            .map(|&course_id| CourseProgramEntity {
+                course_id,
+                program_id: id,
+            })
+            .collect::<Vec<_>>();
 
-        let users = users::table
-            .select((users::id, users::name))
-            .filter(users::id.eq_any(&user_ids))
-            .log_query()
-            .load::<(i32, String)>(self.conn)
-            .map(FnvHashMap::from_iter)?;
+        diesel::insert_into(course_programs::table)
+            .values(courses)
+            .execute(self.conn)?;
 
-        tickets
-            .into_iter()
-            .map(|ticket| {
-                let (course_name, course_author, course_tutor) = courses
-                    .get(&ticket.course_id)
-                    .cloned()
-                    .context("Entry missing for tickets's course ID")?;
-                let creator_name = users
-                    .get(&ticket.creator_id)
-                    .cloned()
-                    .context("Entry missing for tickets's creator ID")?;
-                let editor_name = users
-                    .get(&if ticket.forwarded {
-                        course_author
-                    } else {
-                        course_tutor
-                    })
-                    .cloned()
-                    .context("Entry missing for ticket's editor ID")?;
-                Ok(TicketWithNames {
-                    ticket,
-                    course_name,
-                    creator_name,
-                    editor_name,
-                })
+        let managers = manager_ids
+            .iter()
+            .map(|&user_id| ProgramManagerEntity {
+                program_id: id,
+                user_id,
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(program_managers::table)
+            .values(managers)
+            .execute(self.conn)?;
+
+        Ok(())
     }
 }
 
-impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
-    fn list_with_names(&self) -> Result<Vec<TicketWithNames>> {
-        let tickets = self.list()?;
+impl<'a> ProgramRepository for ProgramRepositoryImpl<'a> {
+    fn list_faculties(&self) -> Result<Vec<Faculty>> {
+        use super::schema::faculties;
 
-        self.load_names(tickets)
+        faculties::table
+            .order_by(faculties::name)
+            .log_query()
+            .load::<FacultyEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
     }
 
-    fn list_by_creator_id(&self, creator_id: i32) -> Result<Vec<TicketWithNames>> {
-        use super::schema::tickets;
+    fn list(&self) -> Result<Vec<Program>> {
+        use super::schema::programs;
 
-        let tickets = tickets::table
-            .filter(tickets::creator_id.eq(creator_id))
+        programs::table
+            .order_by(programs::code)
             .log_query()
-            .load::<TicketEntity>(self.conn)
+            .load::<ProgramEntity>(self.conn)
             .map_err(Into::into)
-            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
-
-        self.load_names(tickets)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
     }
 
-    fn list_by_assignee_id(&self, assignee_id: i32) -> Result<Vec<TicketWithNames>> {
-        use super::schema::{courses, tickets};
+    fn list_names(&self) -> Result<Vec<(i32, String)>> {
+        use super::schema::programs;
 
-        let tickets = tickets::table
-            .inner_join(courses::table)
-            .filter(
-                courses::author_id
-                    .eq(assignee_id)
-                    .and(tickets::forwarded.eq(true))
-                    .or(courses::tutor_id
-                        .eq(assignee_id)
-                        .and(tickets::forwarded.eq(false))),
-            )
-            .select(tickets::all_columns)
+        programs::table
+            .select((programs::id, programs::code))
+            .order_by(programs::code)
             .log_query()
-            .load::<TicketEntity>(self.conn)
+            .load::<(i32, String)>(self.conn)
             .map_err(Into::into)
-            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+    }
 
-        self.load_names(tickets)
+    fn list_course_assignments(&self) -> Result<Vec<(i32, i32)>> {
+        use super::schema::course_programs;
+
+        course_programs::table
+            .select((course_programs::course_id, course_programs::program_id))
+            .log_query()
+            .load::<(i32, i32)>(self.conn)
+            .map_err(Into::into)
     }
 
-    fn get(&self, id: i32) -> Result<Ticket> {
-        use super::schema::tickets;
+    fn list_manager_names(&self) -> Result<Vec<(i32, String)>> {
+        use super::schema::{program_managers, users};
 
-        tickets::table
-            .find(id)
+        program_managers::table
+            .inner_join(users::table)
+            .select((program_managers::program_id, users::name))
+            .order_by(users::name)
             .log_query()
-            .get_result::<TicketEntity>(self.conn)
+            .load::<(i32, String)>(self.conn)
             .map_err(Into::into)
-            .and_then(TryInto::try_into)
     }
 
-    fn get_with_names(&self, id: i32) -> Result<TicketWithNames> {
-        use super::schema::{courses, users};
+    fn list_managed_ids(&self, user_id: i32) -> Result<Vec<i32>> {
+        use super::schema::program_managers;
 
-        let ticket = self.get(id)?;
+        program_managers::table
+            .select(program_managers::program_id)
+            .filter(program_managers::user_id.eq(user_id))
+            .log_query()
+            .load::<i32>(self.conn)
+            .map_err(Into::into)
+    }
 
-        let (course_name, author_id, tutor_id) = courses::table
-            .find(ticket.course_id)
-            .select((courses::code, courses::author_id, courses::tutor_id))
+    fn get_with_rels(&self, id: i32) -> Result<ProgramWithRels> {
+        use super::schema::{course_programs, program_managers, programs};
+
+        let program = programs::table
+            .find(id)
             .log_query()
-            .get_result::<(String, i32, i32)>(self.conn)?;
+            .get_result::<ProgramEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(TryInto::try_into)?;
 
-        let creator_name = users::table
-            .find(ticket.creator_id)
-            .select(users::name)
+        let course_ids = course_programs::table
+            .select(course_programs::course_id)
+            .filter(course_programs::program_id.eq(id))
             .log_query()
-            .get_result(self.conn)?;
+            .load::<i32>(self.conn)?;
 
-        let editor_name = users::table
-            .find(if ticket.forwarded {
-                author_id
-            } else {
-                tutor_id
-            })
-            .select(users::name)
+        let manager_ids = program_managers::table
+            .select(program_managers::user_id)
+            .filter(program_managers::program_id.eq(id))
             .log_query()
-            .get_result(self.conn)?;
+            .load::<i32>(self.conn)?;
 
-        Ok(TicketWithNames {
-            ticket,
-            course_name,
-            creator_name,
-            editor_name,
+        Ok(ProgramWithRels {
+            program,
+            course_ids,
+            manager_ids,
         })
     }
 
-    fn get_with_rels(&self, id: i32) -> Result<TicketWithRels> {
-        use super::schema::{
-            comments, medium_interactives, medium_questionaires, medium_recordings, medium_texts,
-            users,
-        };
-
-        let ticket = self.get_with_names(id)?;
-
-        let medium = match ticket.ticket.type_.medium() {
-            MediumType::Text => medium_texts::table
-                .find(id)
-                .log_query()
-                .get_result::<MediumTextEntity>(self.conn)
-                .map_err(Into::into)
-                .and_then(TryInto::try_into),
-            MediumType::Recording => medium_recordings::table
-                .find(id)
-                .log_query()
-                .get_result::<MediumRecordingEntity>(self.conn)
-                .map_err(Into::into)
-                .and_then(TryInto::try_into),
-            MediumType::Interactive => medium_interactives::table
-                .find(id)
-                .log_query()
-                .get_result::<MediumInteractiveEntity>(self.conn)
-                .map_err(Into::into)
-                .and_then(TryInto::try_into),
-            MediumType::Questionaire => medium_questionaires::table
-                .find(id)
-                .log_query()
-                .get_result::<MediumQuestionaireEntity>(self.conn)
-                .map_err(Into::into)
-                .and_then(TryInto::try_into),
-        }?;
+    fn create_faculty(&self, name: String) -> Result<()> {
+        use super::schema::faculties;
 
-        let comments = comments::table
-            .filter(comments::ticket_id.eq(id))
+        let res = diesel::insert_into(faculties::table)
+            .values(NewFacultyEntity { name })
             .log_query()
-            .load::<CommentEntity>(self.conn)
-            .map_err(Into::into)
-            .and_then(|comments| {
-                comments
-                    .into_iter()
-                    .map(TryInto::try_into)
-                    .collect::<Result<Vec<Comment>>>()
-            })?;
-
-        let creator_ids = comments
-            .iter()
-            .map(|c| c.creator_id)
-            .collect::<FnvHashSet<_>>();
+            .execute(self.conn)?;
 
-        let users = users::table
-            .select((users::id, users::name))
-            .filter(users::id.eq_any(creator_ids))
-            .log_query()
-            .load::<(i32, String)>(self.conn)
-            .map(FnvHashMap::from_iter)?;
+        ensure!(res == 1, "Failed inserting faculty");
+        Ok(())
+    }
 
-        let comments = comments
-            .into_iter()
-            .map(|comment| {
-                let creator_name = users
-                    .get(&comment.creator_id)
-                    .cloned()
-                    .context("Entry missing for comment's user ID")?;
+    fn create(&self, program: NewProgram) -> Result<()> {
+        use super::schema::programs;
 
-                Ok(CommentWithNames {
-                    comment,
-                    creator_name,
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(programs::table)
+                .values(NewProgramEntity {
+                    faculty_id: program.faculty_id,
+                    code: program.code,
+                    title: program.title,
                 })
-            })
-            .collect::<Result<Vec<_>>>()?;
+                .log_query()
+                .execute(self.conn)?;
 
-        Ok(TicketWithRels {
-            ticket: ticket.ticket,
-            course_name: ticket.course_name,
-            creator_name: ticket.creator_name,
-            editor_name: ticket.editor_name,
-            medium,
-            comments,
+            ensure!(res == 1, "Failed inserting program");
+
+            let program_id = programs::table
+                .select(programs::id)
+                .order_by(programs::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            self.set_assignments(program_id, &program.course_ids, &program.manager_ids)
         })
     }
 
-    fn create(&self, ticket: NewTicket, priority: Priority, medium: NewMedium) -> Result<i32> {
-        use super::schema::{
-            medium_interactives, medium_questionaires, medium_recordings, medium_texts, tickets,
-        };
+    fn update(&self, program: EditProgram) -> Result<()> {
+        use super::schema::programs;
 
         self.conn.transaction(|| {
-            let res = diesel::insert_into(tickets::table)
-                .values(NewTicketEntity::from((ticket, priority)))
+            let res = diesel::update(programs::table.find(program.id))
+                .set((
+                    programs::faculty_id.eq(program.faculty_id),
+                    programs::title.eq(program.title),
+                ))
                 .log_query()
                 .execute(self.conn)?;
 
-            ensure!(res == 1, "Failed inserting ticket");
+            ensure!(res == 1, "Program with ID {} not found", program.id);
 
-            let ticket_id = tickets::table
-                .select(tickets::id)
-                .order_by(tickets::id.desc())
-                .limit(1)
-                .log_query()
-                .get_result::<i32>(self.conn)?;
+            self.set_assignments(program.id, &program.course_ids, &program.manager_ids)
+        })
+    }
+}
 
-            let res_medium = match medium {
-                NewMedium::Text { page, line } => diesel::insert_into(medium_texts::table)
-                    .values(MediumTextEntity {
-                        ticket_id,
-                        page: page.into(),
-                        line: line.into(),
-                    })
-                    .log_query()
-                    .execute(self.conn),
-                NewMedium::Recording { time } => diesel::insert_into(medium_recordings::table)
-                    .values(MediumRecordingEntity {
-                        ticket_id,
-                        time: time.format("%H:%M:%S").to_string(),
+/// Create a new program repository.
+pub fn program_repo(conn: &SqliteConnection) -> impl ProgramRepository + '_ {
+    ProgramRepositoryImpl { conn }
+}
+
+/// Workflow related functionality.
+pub trait WorkflowRepository {
+    /// Load the full workflow with all statuses and transitions.
+    fn get(&self) -> Result<Workflow>;
+    /// Create a new status, placed after all existing ones.
+    fn create_status(&self, status: WorkflowStatus) -> Result<()>;
+    /// Update the details of an existing status.
+    fn update_status(&self, status: WorkflowStatus) -> Result<()>;
+    /// Add a new transition between two statuses.
+    fn add_transition(&self, transition: Transition) -> Result<()>;
+    /// Remove an existing transition.
+    fn remove_transition(&self, transition: Transition) -> Result<()>;
+}
+
+/// Main implementation of [`WorkflowRepository`].
+struct WorkflowRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> WorkflowRepository for WorkflowRepositoryImpl<'a> {
+    fn get(&self) -> Result<Workflow> {
+        use super::schema::{workflow_statuses, workflow_transitions};
+
+        let statuses = workflow_statuses::table
+            .order_by(workflow_statuses::position)
+            .log_query()
+            .load::<WorkflowStatusEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+
+        let transitions = workflow_transitions::table
+            .order_by((
+                workflow_transitions::from_status,
+                workflow_transitions::to_status,
+            ))
+            .log_query()
+            .load::<TransitionEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+
+        Ok(Workflow {
+            statuses,
+            transitions,
+        })
+    }
+
+    fn create_status(&self, status: WorkflowStatus) -> Result<()> {
+        use diesel::dsl::max;
+
+        use super::schema::workflow_statuses;
+
+        self.conn.transaction(|| {
+            let position = workflow_statuses::table
+                .select(max(workflow_statuses::position))
+                .log_query()
+                .get_result::<Option<i32>>(self.conn)?
+                .unwrap_or_default()
+                + 1;
+
+            let res = diesel::insert_into(workflow_statuses::table)
+                .values(NewWorkflowStatusEntity::from((status, position)))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting status");
+            Ok(())
+        })
+    }
+
+    fn update_status(&self, status: WorkflowStatus) -> Result<()> {
+        use super::schema::workflow_statuses;
+
+        let res = diesel::update(workflow_statuses::table.find(status.key.as_ref()))
+            .set((
+                workflow_statuses::name.eq(status.name),
+                workflow_statuses::color.eq(status.color.as_ref()),
+                workflow_statuses::icon.eq(status.icon),
+                workflow_statuses::terminal.eq(status.terminal),
+            ))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Status {} not found", status.key);
+        Ok(())
+    }
+
+    fn add_transition(&self, transition: Transition) -> Result<()> {
+        use super::schema::workflow_transitions;
+
+        let res = diesel::insert_into(workflow_transitions::table)
+            .values(TransitionEntity::from(transition))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Failed inserting transition");
+        Ok(())
+    }
+
+    fn remove_transition(&self, transition: Transition) -> Result<()> {
+        use super::schema::workflow_transitions;
+
+        let res = diesel::delete(
+            workflow_transitions::table
+                .filter(workflow_transitions::from_status.eq(transition.from.as_ref()))
+                .filter(workflow_transitions::to_status.eq(transition.to.as_ref()))
+                .filter(workflow_transitions::role.eq(transition.role.as_ref())),
+        )
+        .log_query()
+        .execute(self.conn)?;
+
+        ensure!(res == 1, "Transition not found");
+        Ok(())
+    }
+}
+
+/// Create a new workflow repository.
+pub fn workflow_repo(conn: &SqliteConnection) -> impl WorkflowRepository + '_ {
+    WorkflowRepositoryImpl { conn }
+}
+
+/// Label related functionality.
+pub trait LabelRepository {
+    /// List all labels, global ones first and then grouped by course.
+    fn list(&self) -> Result<Vec<Label>>;
+    /// List all labels together with the names of their courses.
+    fn list_with_names(&self) -> Result<Vec<LabelWithNames>>;
+    /// List all labels that can be assigned to the tickets of a course, which are the global
+    /// labels and the ones of the course itself.
+    fn list_for_course(&self, course_id: i32) -> Result<Vec<Label>>;
+    /// Create a new label.
+    fn create(&self, label: NewLabel) -> Result<()>;
+    /// Delete an existing label, removing it from all tickets and rules as well.
+    fn delete(&self, id: i32) -> Result<()>;
+}
+
+/// Main implementation of [`LabelRepository`].
+struct LabelRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> LabelRepository for LabelRepositoryImpl<'a> {
+    fn list(&self) -> Result<Vec<Label>> {
+        use super::schema::labels;
+
+        labels::table
+            .order_by((labels::course_id, labels::name))
+            .log_query()
+            .load::<LabelEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
+    }
+
+    fn list_with_names(&self) -> Result<Vec<LabelWithNames>> {
+        use super::schema::courses;
+
+        let labels = self.list()?;
+
+        let courses = courses::table
+            .select((courses::id, courses::code))
+            .filter(courses::id.eq_any(labels.iter().filter_map(|l| l.course_id)))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        Ok(labels
+            .into_iter()
+            .map(|label| LabelWithNames {
+                course_name: label.course_id.and_then(|id| courses.get(&id).cloned()),
+                label,
+            })
+            .collect())
+    }
+
+    fn list_for_course(&self, course_id: i32) -> Result<Vec<Label>> {
+        use super::schema::labels;
+
+        labels::table
+            .filter(
+                labels::course_id
+                    .is_null()
+                    .or(labels::course_id.eq(course_id)),
+            )
+            .order_by(labels::name)
+            .log_query()
+            .load::<LabelEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
+    }
+
+    fn create(&self, label: NewLabel) -> Result<()> {
+        use super::schema::labels;
+
+        let res = diesel::insert_into(labels::table)
+            .values(NewLabelEntity::from(label))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Failed inserting label");
+        Ok(())
+    }
+
+    fn delete(&self, id: i32) -> Result<()> {
+        use super::schema::{labels, rule_labels, ticket_labels};
+
+        self.conn.transaction(|| {
+            diesel::delete(ticket_labels::table.filter(ticket_labels::label_id.eq(id)))
+                .log_query()
+                .execute(self.conn)?;
+
+            diesel::delete(rule_labels::table.filter(rule_labels::label_id.eq(id)))
+                .log_query()
+                .execute(self.conn)?;
+
+            let res = diesel::delete(labels::table.find(id))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Label with ID {} not found", id);
+            Ok(())
+        })
+    }
+}
+
+/// Create a new label repository.
+pub fn label_repo(conn: &SqliteConnection) -> impl LabelRepository + '_ {
+    LabelRepositoryImpl { conn }
+}
+
+/// Rule related functionality.
+pub trait RuleRepository {
+    /// List all rules, in the order they were created.
+    fn list(&self) -> Result<Vec<Rule>>;
+    /// List all rules together with their course and assignee names.
+    fn list_with_names(&self) -> Result<Vec<RuleWithNames>>;
+    /// Create a new rule.
+    fn create(&self, rule: NewRule) -> Result<()>;
+    /// Delete an existing rule.
+    fn delete(&self, id: i32) -> Result<()>;
+}
+
+/// Main implementation of [`RuleRepository`].
+struct RuleRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> RuleRepository for RuleRepositoryImpl<'a> {
+    fn list(&self) -> Result<Vec<Rule>> {
+        use super::schema::{labels, rule_labels, ticket_rules};
+
+        let mut by_rule = FnvHashMap::<i32, Vec<Label>>::default();
+
+        for (rule_id, label) in rule_labels::table
+            .inner_join(labels::table)
+            .select((rule_labels::rule_id, labels::all_columns))
+            .order_by(labels::name)
+            .log_query()
+            .load::<(i32, LabelEntity)>(self.conn)?
+        {
+            by_rule.entry(rule_id).or_default().push(label.try_into()?);
+        }
+
+        ticket_rules::table
+            .order_by(ticket_rules::id)
+            .log_query()
+            .load::<RuleEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| {
+                entities
+                    .into_iter()
+                    .map(|entity| {
+                        let mut rule = Rule::try_from(entity)?;
+                        rule.labels = by_rule.remove(&rule.id).unwrap_or_default();
+                        Ok(rule)
+                    })
+                    .collect()
+            })
+    }
+
+    fn list_with_names(&self) -> Result<Vec<RuleWithNames>> {
+        use super::schema::{courses, users};
+
+        let rules = self.list()?;
+
+        let courses = courses::table
+            .select((courses::id, courses::code))
+            .filter(courses::id.eq_any(rules.iter().filter_map(|r| r.course_id)))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        let users = users::table
+            .select((users::id, users::name))
+            .filter(users::id.eq_any(rules.iter().filter_map(|r| r.assignee_id)))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        Ok(rules
+            .into_iter()
+            .map(|rule| RuleWithNames {
+                course_name: rule.course_id.and_then(|id| courses.get(&id).cloned()),
+                assignee_name: rule.assignee_id.and_then(|id| users.get(&id).cloned()),
+                rule,
+            })
+            .collect())
+    }
+
+    fn create(&self, mut rule: NewRule) -> Result<()> {
+        use super::schema::{rule_labels, ticket_rules};
+
+        let label_ids = std::mem::take(&mut rule.label_ids);
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(ticket_rules::table)
+                .values(NewRuleEntity::from(rule))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting rule");
+
+            let rule_id = ticket_rules::table
+                .select(ticket_rules::id)
+                .order_by(ticket_rules::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let labels = label_ids
+                .into_iter()
+                .map(|label_id| {
+                    (
+                        rule_labels::rule_id.eq(rule_id),
+                        rule_labels::label_id.eq(label_id),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(rule_labels::table)
+                .values(labels)
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+
+    fn delete(&self, id: i32) -> Result<()> {
+        use super::schema::{rule_labels, ticket_rules};
+
+        self.conn.transaction(|| {
+            diesel::delete(rule_labels::table.filter(rule_labels::rule_id.eq(id)))
+                .log_query()
+                .execute(self.conn)?;
+
+            let res = diesel::delete(ticket_rules::table.find(id))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Rule with ID {} not found", id);
+            Ok(())
+        })
+    }
+}
+
+/// Create a new rule repository.
+pub fn rule_repo(conn: &SqliteConnection) -> impl RuleRepository + '_ {
+    RuleRepositoryImpl { conn }
+}
+
+/// Intake template related functionality.
+pub trait TemplateRepository {
+    /// List all stored templates. Ticket types without a template are missing from the list.
+    fn list(&self) -> Result<Vec<TicketTemplate>>;
+    /// Find the template of a ticket type, if there is one.
+    fn find(&self, ty: TicketType) -> Result<Option<TicketTemplate>>;
+    /// Create or replace the template of a ticket type.
+    fn save(&self, template: TicketTemplate) -> Result<()>;
+}
+
+/// Main implementation of [`TemplateRepository`].
+struct TemplateRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> TemplateRepository for TemplateRepositoryImpl<'a> {
+    fn list(&self) -> Result<Vec<TicketTemplate>> {
+        use super::schema::ticket_templates;
+
+        ticket_templates::table
+            .order_by(ticket_templates::type_)
+            .log_query()
+            .load::<TicketTemplateEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
+    }
+
+    fn find(&self, ty: TicketType) -> Result<Option<TicketTemplate>> {
+        use super::schema::ticket_templates;
+
+        ticket_templates::table
+            .find(ty.as_ref())
+            .log_query()
+            .get_result::<TicketTemplateEntity>(self.conn)
+            .optional()?
+            .map(TryInto::try_into)
+            .transpose()
+    }
+
+    fn save(&self, template: TicketTemplate) -> Result<()> {
+        use super::schema::ticket_templates;
+
+        let res = diesel::replace_into(ticket_templates::table)
+            .values(TicketTemplateEntity::from(template))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Failed saving template");
+        Ok(())
+    }
+}
+
+/// Create a new template repository.
+pub fn template_repo(conn: &SqliteConnection) -> impl TemplateRepository + '_ {
+    TemplateRepositoryImpl { conn }
+}
+
+/// Public report related functionality.
+pub trait PublicReportRepository {
+    /// List all reports that are neither approved nor rejected yet, together with their course
+    /// names.
+    fn list_pending(&self) -> Result<Vec<PublicReportWithNames>>;
+    /// Get a single report by its ID, if it's still waiting for moderation.
+    fn get_pending(&self, id: i32) -> Result<PublicReport>;
+    /// Create a new report in the system.
+    fn create(&self, report: NewPublicReport, created_at: DateTime<Utc>) -> Result<()>;
+    /// Count the reports that were sent from an IP address since the given point in time.
+    fn count_since(&self, ip_address: &str, since: DateTime<Utc>) -> Result<i64>;
+    /// Mark a pending report as approved, linking it to the ticket that was created from it.
+    fn approve(&self, id: i32, ticket_id: i32) -> Result<()>;
+    /// Mark a report as rejected.
+    fn reject(&self, id: i32) -> Result<()>;
+    /// Run several changes in a single transaction, which is rolled back if any of them fail.
+    fn transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()>;
+}
+
+/// Main implementation of [`PublicReportRepository`].
+struct PublicReportRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> PublicReportRepositoryImpl<'a> {
+    /// Load the medium locations of all given reports in order, mapped by the report ID.
+    fn load_locations(
+        &self,
+        report_ids: &[i32],
+    ) -> Result<FnvHashMap<i32, Vec<PublicReportLocationEntity>>> {
+        use super::schema::public_report_locations;
+
+        let mut by_report = FnvHashMap::<i32, Vec<_>>::default();
+
+        for location in public_report_locations::table
+            .filter(public_report_locations::report_id.eq_any(report_ids))
+            .order_by((
+                public_report_locations::report_id,
+                public_report_locations::position,
+            ))
+            .log_query()
+            .load::<PublicReportLocationEntity>(self.conn)?
+        {
+            by_report
+                .entry(location.report_id)
+                .or_default()
+                .push(location);
+        }
+
+        Ok(by_report)
+    }
+
+    /// Load the template fields of all given reports in order, mapped by the report ID.
+    fn load_fields(
+        &self,
+        report_ids: &[i32],
+    ) -> Result<FnvHashMap<i32, Vec<PublicReportFieldEntity>>> {
+        use super::schema::public_report_fields;
+
+        let mut by_report = FnvHashMap::<i32, Vec<_>>::default();
+
+        for field in public_report_fields::table
+            .filter(public_report_fields::report_id.eq_any(report_ids))
+            .order_by((
+                public_report_fields::report_id,
+                public_report_fields::position,
+            ))
+            .log_query()
+            .load::<PublicReportFieldEntity>(self.conn)?
+        {
+            by_report.entry(field.report_id).or_default().push(field);
+        }
+
+        Ok(by_report)
+    }
+}
+
+impl<'a> PublicReportRepository for PublicReportRepositoryImpl<'a> {
+    fn list_pending(&self) -> Result<Vec<PublicReportWithNames>> {
+        use super::schema::{courses, public_reports};
+
+        let reports = public_reports::table
+            .inner_join(courses::table)
+            .filter(public_reports::ticket_id.is_null())
+            .filter(public_reports::rejected.eq(false))
+            .select((public_reports::all_columns, courses::code))
+            .order_by(public_reports::id)
+            .log_query()
+            .load::<(PublicReportEntity, String)>(self.conn)?;
+
+        let report_ids = reports.iter().map(|(r, _)| r.id).collect::<Vec<_>>();
+        let mut locations = self.load_locations(&report_ids)?;
+        let mut fields = self.load_fields(&report_ids)?;
+
+        reports
+            .into_iter()
+            .map(|(report, course_name)| {
+                let locations = locations.remove(&report.id).unwrap_or_default();
+                let fields = fields.remove(&report.id).unwrap_or_default();
+                Ok(PublicReportWithNames {
+                    report: (report, locations, fields).try_into()?,
+                    course_name,
+                })
+            })
+            .collect()
+    }
+
+    fn get_pending(&self, id: i32) -> Result<PublicReport> {
+        use super::schema::public_reports;
+
+        let report = public_reports::table
+            .find(id)
+            .filter(public_reports::ticket_id.is_null())
+            .filter(public_reports::rejected.eq(false))
+            .log_query()
+            .get_result::<PublicReportEntity>(self.conn)?;
+        let locations = self.load_locations(&[id])?.remove(&id).unwrap_or_default();
+        let fields = self.load_fields(&[id])?.remove(&id).unwrap_or_default();
+
+        (report, locations, fields).try_into()
+    }
+
+    fn create(&self, report: NewPublicReport, created_at: DateTime<Utc>) -> Result<()> {
+        use super::schema::{public_report_fields, public_report_locations, public_reports};
+
+        let NewPublicReport {
+            course_id,
+            type_,
+            category,
+            title,
+            description,
+            medium,
+            fields,
+            contact_email,
+            ip_address,
+        } = report;
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(public_reports::table)
+                .values(NewPublicReportEntity {
+                    course_id,
+                    type_: type_.to_string(),
+                    category: category.to_string(),
+                    title,
+                    description,
+                    contact_email,
+                    ip_address,
+                    created_at: created_at.to_rfc3339(),
+                })
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting public report");
+
+            let report_id = public_reports::table
+                .select(public_reports::id)
+                .order_by(public_reports::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let locations = PublicReportLocationEntity::from_medium(report_id, medium);
+            let expected = locations.len();
+
+            let res = diesel::insert_into(public_report_locations::table)
+                .values(locations)
+                .execute(self.conn)?;
+
+            ensure!(
+                expected > 0 && res == expected,
+                "Failed inserting public report locations"
+            );
+
+            let fields = fields
+                .into_iter()
+                .enumerate()
+                .map(|(position, field)| {
+                    Ok(PublicReportFieldEntity {
+                        report_id,
+                        position: i32::try_from(position)?,
+                        label: field.label,
+                        value: field.value,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            diesel::insert_into(public_report_fields::table)
+                .values(fields)
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+
+    fn count_since(&self, ip_address: &str, since: DateTime<Utc>) -> Result<i64> {
+        use super::schema::public_reports;
+
+        // Timestamps are stored as RFC 3339 strings in UTC, so they compare chronologically.
+        public_reports::table
+            .filter(public_reports::ip_address.eq(ip_address))
+            .filter(public_reports::created_at.ge(since.to_rfc3339()))
+            .count()
+            .log_query()
+            .get_result(self.conn)
+            .map_err(Into::into)
+    }
+
+    fn approve(&self, id: i32, ticket_id: i32) -> Result<()> {
+        use super::schema::public_reports;
+
+        let res = diesel::update(
+            public_reports::table
+                .find(id)
+                .filter(public_reports::ticket_id.is_null())
+                .filter(public_reports::rejected.eq(false)),
+        )
+        .set(public_reports::ticket_id.eq(ticket_id))
+        .log_query()
+        .execute(self.conn)?;
+
+        ensure!(res == 1, "Pending public report with ID {} not found", id);
+        Ok(())
+    }
+
+    fn reject(&self, id: i32) -> Result<()> {
+        use super::schema::public_reports;
+
+        let res = diesel::update(
+            public_reports::table
+                .find(id)
+                .filter(public_reports::ticket_id.is_null()),
+        )
+        .set(public_reports::rejected.eq(true))
+        .log_query()
+        .execute(self.conn)?;
+
+        ensure!(res == 1, "Pending public report with ID {} not found", id);
+        Ok(())
+    }
+
+    fn transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        self.conn.transaction(f)
+    }
+}
+
+/// Create a new public report repository.
+pub fn public_report_repo(conn: &SqliteConnection) -> impl PublicReportRepository + '_ {
+    PublicReportRepositoryImpl { conn }
+}
+
+/// Ticket related functionality.
+pub trait TicketRepository {
+    /// List all tickets together with their course, creator and assignee names.
+    fn list_with_names(&self) -> Result<Vec<TicketWithNames>>;
+    /// List all tickets by their creator ID.
+    fn list_by_creator_id(&self, creator_id: i32) -> Result<Vec<TicketWithNames>>;
+    /// List all tickets by their assignee ID.
+    fn list_by_assignee_id(&self, assignee_id: i32) -> Result<Vec<TicketWithNames>>;
+    /// List all tickets that are not assigned to anyone yet and are not finished, optionally
+    /// limited to a single course.
+    fn list_unassigned(&self, course_id: Option<i32>) -> Result<Vec<TicketWithNames>>;
+    /// List all open tickets of a course and type together with their medium, that are not
+    /// marked as duplicate.
+    fn list_open_with_media(
+        &self,
+        course_id: i32,
+        type_: TicketType,
+    ) -> Result<Vec<(TicketWithNames, Medium)>>;
+    /// Get a single ticket by ID.
+    fn get(&self, id: i32) -> Result<Ticket>;
+    /// Get a single ticket with course and creator names.
+    fn get_with_names(&self, id: i32) -> Result<TicketWithNames>;
+    /// Get a single ticket with all related data.
+    fn get_with_rels(&self, id: i32) -> Result<TicketWithRels>;
+    /// Create a new ticket.
+    fn create(
+        &self,
+        ticket: NewTicket,
+        priority: Priority,
+        created_at: DateTime<Utc>,
+        medium: NewMedium,
+    ) -> Result<i32>;
+    /// Add a new comment to an existing ticket, returning the new comment's ID.
+    fn add_comment(&self, comment: NewComment) -> Result<i32>;
+    /// Get a single comment by its ID.
+    fn get_comment(&self, comment_id: i32) -> Result<Comment>;
+    /// Replace the message of a comment, keeping the previous message as a revision.
+    fn edit_comment(
+        &self,
+        comment_id: i32,
+        message: String,
+        revision: NewCommentRevision,
+    ) -> Result<()>;
+    /// Delete a comment by removing its message, keeping the previous message as a revision.
+    fn delete_comment(&self, comment_id: i32, revision: NewCommentRevision) -> Result<()>;
+    /// List the previous versions of all comments of a ticket, oldest first.
+    fn list_comment_revisions(&self, id: i32) -> Result<Vec<CommentRevisionWithNames>>;
+    /// Update an existing ticket.
+    fn update(&self, ticket: EditTicket) -> Result<()>;
+    /// Update the title, description and medium of a ticket, keeping the previous version as a
+    /// revision.
+    fn update_content(&self, ticket: EditTicketContent, revision: NewTicketRevision) -> Result<()>;
+    /// Move a ticket into another course or ticket type. A forwarded ticket is no longer forwarded
+    /// in a new course and labels that only belong to the old course are removed. If the ticket
+    /// gets a new medium, the old one of type `old_medium` is replaced and the revision recorded.
+    fn move_ticket(
+        &self,
+        ticket: MoveTicket,
+        old_medium: MediumType,
+        revision: Option<NewTicketRevision>,
+    ) -> Result<()>;
+    /// List all previous versions of a ticket, oldest first.
+    fn list_revisions(&self, id: i32) -> Result<Vec<TicketRevisionWithNames>>;
+    /// Forward a ticket to its course's author, making the author the new assignee. Returns the
+    /// author's ID.
+    fn forward(&self, id: i32) -> Result<i32>;
+    /// Return a forwarded ticket to its course's tutor, making the tutor the new assignee. Returns
+    /// the tutor's ID.
+    fn return_to_tutor(&self, id: i32) -> Result<i32>;
+    /// Assign a ticket to the given user.
+    fn assign(&self, id: i32, assignee_id: i32) -> Result<()>;
+    /// Remove the assignee of a ticket, putting it back into its course's unassigned queue.
+    fn unassign(&self, id: i32) -> Result<()>;
+    /// Replace the labels of a ticket. All labels must be available in the ticket's course.
+    fn set_labels(&self, id: i32, label_ids: &[i32]) -> Result<()>;
+    /// Get the current status of a ticket.
+    fn get_status(&self, id: i32) -> Result<Status>;
+    /// Set the new status of a ticket, together with the time it was closed if the status is a
+    /// terminal one.
+    fn set_status(&self, id: i32, status: Status, closed_at: Option<DateTime<Utc>>) -> Result<()>;
+    /// Reopen a closed ticket, putting it back into [`Status::OPEN`] and increasing its reopen
+    /// counter.
+    fn reopen(&self, id: i32) -> Result<()>;
+    /// Record that the given user asked for a closed ticket to be reopened. Only a single request
+    /// can be pending for each ticket.
+    fn add_reopen_request(&self, id: i32, user_id: i32, at: DateTime<Utc>) -> Result<()>;
+    /// Get the ID of the user that asked for the ticket to be reopened, if a request is pending.
+    fn reopen_requester(&self, id: i32) -> Result<Option<i32>>;
+    /// Remove the pending reopen request of a ticket, if there is any.
+    fn remove_reopen_request(&self, id: i32) -> Result<()>;
+    /// Record a new change in the history of a ticket.
+    fn add_event(&self, event: NewTicketEvent) -> Result<()>;
+    /// Set the time of the first response to a ticket, unless it was already answered before.
+    fn set_first_response(&self, id: i32, at: DateTime<Utc>) -> Result<()>;
+    /// Set the escalation level of a ticket, together with the time of the escalation.
+    fn set_escalation(&self, id: i32, level: u32, at: DateTime<Utc>) -> Result<()>;
+    /// Close a ticket as duplicate of the primary ticket. Its creator and watchers become watchers
+    /// of the primary ticket, its comments are copied over and any tickets that were previously
+    /// merged into it are moved to the primary ticket as well.
+    fn mark_duplicate(&self, id: i32, primary_id: i32, closed_at: DateTime<Utc>) -> Result<()>;
+    /// Search for tickets with different criteria.
+    fn search(&self, search: &TicketSearch) -> Result<Vec<TicketWithNames>>;
+    /// Load the media of all given tickets, mapped by the ticket ID.
+    fn list_media(&self, tickets: &[TicketWithNames]) -> Result<FnvHashMap<i32, Medium>>;
+    /// Activate a new ticket, changing it to [`Status::IN_PROGRESS`] if it's still in
+    /// [`Status::OPEN`] and accessed by its assignee.
+    fn activate_ticket(&self, id: i32, user_id: i32) -> Result<bool>;
+    /// Link a ticket to another one.
+    fn add_link(&self, link: NewTicketLink) -> Result<()>;
+    /// Remove a link from a ticket, which can be either its source or target.
+    fn remove_link(&self, id: i32, link_id: i32) -> Result<()>;
+    /// Add a vote of a user to a ticket. Returns the new amount of votes, or [`None`] if the user
+    /// already voted for the ticket before.
+    fn add_vote(&self, id: i32, user_id: i32) -> Result<Option<u32>>;
+    /// Check whether the provided user voted for a ticket.
+    fn has_voted(&self, id: i32, user_id: i32) -> Result<bool>;
+    /// Add a user as watcher of a ticket, if not watching it already.
+    fn add_watcher(&self, id: i32, user_id: i32) -> Result<()>;
+    /// Remove a user from the watchers of a ticket, if watching it.
+    fn remove_watcher(&self, id: i32, user_id: i32) -> Result<()>;
+    /// Check whether the provided user watches a ticket.
+    fn is_watcher(&self, id: i32, user_id: i32) -> Result<bool>;
+    /// Check whether the provided user is the creator of a ticket.
+    fn is_creator(&self, id: i32, user_id: i32) -> Result<bool>;
+    /// Check whether the provided user manages a program that contains the ticket's course.
+    fn is_program_manager(&self, id: i32, user_id: i32) -> Result<bool>;
+    /// Run several changes in a single transaction, which is rolled back if any of them fail.
+    fn transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()>;
+}
+
+/// Main implementation of [`TicketRepository`].
+struct TicketRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> TicketRepositoryImpl<'a> {
+    /// List all tickets.
+    fn list(&self) -> Result<Vec<Ticket>> {
+        use super::schema::tickets;
+
+        tickets::table
+            .load::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())
+    }
+
+    /// Load the comments and recorded changes of a ticket as a single timeline, ordered by their
+    /// time of creation.
+    fn load_timeline(&self, id: i32) -> Result<Vec<TimelineEntry>> {
+        use super::schema::{comments, ticket_events, users};
+
+        let comments = comments::table
+            .filter(comments::ticket_id.eq(id))
+            .log_query()
+            .load::<CommentEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|comments| {
+                comments
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<Comment>>>()
+            })?;
+
+        let events = ticket_events::table
+            .filter(ticket_events::ticket_id.eq(id))
+            .log_query()
+            .load::<TicketEventEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|events| {
+                events
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<TicketEvent>>>()
+            })?;
+
+        let user_ids = comments
+            .iter()
+            .map(|c| c.creator_id)
+            .chain(events.iter().map(|e| e.actor_id))
+            .chain(
+                events
+                    .iter()
+                    .filter(|e| {
+                        matches!(
+                            e.field,
+                            EventField::Forwarded | EventField::Returned | EventField::Assignee
+                        )
+                    })
+                    .flat_map(|e| e.old_value.iter().chain(e.new_value.iter()))
+                    .filter_map(|v| v.parse::<i32>().ok()),
+            )
+            .collect::<FnvHashSet<i32>>();
+
+        let users = users::table
+            .select((users::id, users::name))
+            .filter(users::id.eq_any(user_ids))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        let courses = self.load_course_codes(&events)?;
+        let statuses = self.load_statuses()?;
+
+        let comments = comments.into_iter().map(|comment| {
+            let creator_name = users
+                .get(&comment.creator_id)
+                .cloned()
+                .context("Entry missing for comment's user ID")?;
+
+            Ok(TimelineEntry::Comment(CommentWithNames {
+                comment,
+                creator_name,
+            }))
+        });
+
+        let events = events.into_iter().map(|event| {
+            let actor_name = users
+                .get(&event.actor_id)
+                .cloned()
+                .context("Entry missing for event's user ID")?;
+
+            let resolve = |value: &Option<String>| -> Result<Option<String>> {
+                let value = match value {
+                    Some(value) => value,
+                    None => return Ok(None),
+                };
+
+                Ok(Some(match event.field {
+                    EventField::Status | EventField::Reopened => statuses
+                        .get(&value.parse::<Status>()?)
+                        .map_or_else(|| value.clone(), |s| s.name.clone()),
+                    EventField::Priority => value.parse::<Priority>()?.german().to_owned(),
+                    EventField::Forwarded | EventField::Returned | EventField::Assignee => users
+                        .get(&value.parse::<i32>()?)
+                        .cloned()
+                        .context("Entry missing for event's value user ID")?,
+                    EventField::Course => courses
+                        .get(&value.parse::<i32>()?)
+                        .cloned()
+                        .context("Entry missing for event's value course ID")?,
+                    EventField::Type => value.parse::<TicketType>()?.german().to_owned(),
+                    EventField::ReopenRequested
+                    | EventField::ReopenRefused
+                    | EventField::Edited
+                    | EventField::Rule => value.clone(),
+                }))
+            };
+
+            let old_value = resolve(&event.old_value)?;
+            let new_value = resolve(&event.new_value)?;
+
+            Ok(TimelineEntry::Event(TicketEventWithNames {
+                event,
+                actor_name,
+                old_value,
+                new_value,
+            }))
+        });
+
+        let mut timeline = comments.chain(events).collect::<Result<Vec<_>>>()?;
+        timeline.sort_by(|a, b| a.timestamp().cmp(b.timestamp()));
+
+        Ok(timeline)
+    }
+
+    /// Load the codes of all courses that the given events moved tickets from or to, mapped by
+    /// the course ID.
+    fn load_course_codes(&self, events: &[TicketEvent]) -> Result<FnvHashMap<i32, String>> {
+        use super::schema::courses;
+
+        let course_ids = events
+            .iter()
+            .filter(|e| e.field == EventField::Course)
+            .flat_map(|e| e.old_value.iter().chain(e.new_value.iter()))
+            .filter_map(|v| v.parse::<i32>().ok())
+            .collect::<FnvHashSet<i32>>();
+
+        courses::table
+            .select((courses::id, courses::code))
+            .filter(courses::id.eq_any(course_ids))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)
+            .map_err(Into::into)
+    }
+
+    /// Load all links of a ticket, in both directions, together with the linked tickets.
+    fn load_links(&self, id: i32) -> Result<Vec<LinkedTicket>> {
+        use super::schema::{ticket_links, tickets};
+
+        let links = ticket_links::table
+            .filter(
+                ticket_links::source_id
+                    .eq(id)
+                    .or(ticket_links::target_id.eq(id)),
+            )
+            .order_by(ticket_links::id)
+            .log_query()
+            .load::<TicketLinkEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|links| {
+                links
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<TicketLink>>>()
+            })?;
+
+        let linked_ids = links
+            .iter()
+            .map(|l| {
+                if l.source_id == id {
+                    l.target_id
+                } else {
+                    l.source_id
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let linked_tickets = tickets::table
+            .filter(tickets::id.eq_any(linked_ids))
+            .select((tickets::id, tickets::title, tickets::status))
+            .log_query()
+            .load::<(i32, String, String)>(self.conn)?
+            .into_iter()
+            .map(|(ticket_id, title, status)| (ticket_id, (title, status)))
+            .collect::<FnvHashMap<_, _>>();
+
+        let statuses = self.load_statuses()?;
+
+        links
+            .into_iter()
+            .map(|link| {
+                let (ticket_id, kind) = if link.source_id == id {
+                    (link.target_id, link.kind)
+                } else {
+                    (link.source_id, link.kind.inverse())
+                };
+                let (title, status) = linked_tickets
+                    .get(&ticket_id)
+                    .context("Entry missing for linked ticket")?;
+                let status = statuses
+                    .get(&status.parse::<Status>()?)
+                    .cloned()
+                    .context("Entry missing for linked ticket's status")?;
+
+                Ok(LinkedTicket {
+                    link_id: link.id,
+                    kind,
+                    ticket_id,
+                    title: title.clone(),
+                    status,
+                })
+            })
+            .collect()
+    }
+
+    /// Load all statuses of the workflow, mapped by their key.
+    fn load_statuses(&self) -> Result<FnvHashMap<Status, WorkflowStatus>> {
+        use super::schema::workflow_statuses;
+
+        workflow_statuses::table
+            .order_by(workflow_statuses::position)
+            .log_query()
+            .load::<WorkflowStatusEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| {
+                entities
+                    .into_iter()
+                    .map(|entity| {
+                        let status = WorkflowStatus::try_from(entity)?;
+                        Ok((status.key.clone(), status))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Load the labels of all given tickets, mapped by the ticket ID.
+    fn load_labels(&self, ticket_ids: &[i32]) -> Result<FnvHashMap<i32, Vec<Label>>> {
+        use super::schema::{labels, ticket_labels};
+
+        let mut by_ticket = FnvHashMap::<i32, Vec<Label>>::default();
+
+        for (ticket_id, label) in ticket_labels::table
+            .inner_join(labels::table)
+            .select((ticket_labels::ticket_id, labels::all_columns))
+            .filter(ticket_labels::ticket_id.eq_any(ticket_ids))
+            .order_by(labels::name)
+            .log_query()
+            .load::<(i32, LabelEntity)>(self.conn)?
+        {
+            by_ticket
+                .entry(ticket_id)
+                .or_default()
+                .push(label.try_into()?);
+        }
+
+        Ok(by_ticket)
+    }
+
+    /// Load the media of all given tickets, identified by their ID and medium type, with their
+    /// locations in order and mapped by the ticket ID.
+    #[allow(clippy::too_many_lines)]
+    fn load_media(&self, tickets: &[(i32, MediumType)]) -> Result<FnvHashMap<i32, Medium>> {
+        use super::schema::{
+            medium_interactives, medium_questionaires, medium_recordings, medium_texts,
+        };
+
+        let mut types = tickets
+            .iter()
+            .map(|&(_, medium)| medium)
+            .collect::<Vec<_>>();
+        types.sort();
+        types.dedup();
+
+        let mut media = FnvHashMap::default();
+
+        for medium in types {
+            let ids = tickets
+                .iter()
+                .filter(|&&(_, m)| m == medium)
+                .map(|&(id, _)| id)
+                .collect::<Vec<_>>();
+
+            match medium {
+                MediumType::Text => {
+                    let mut by_ticket = FnvHashMap::<i32, Vec<Span<TextPosition>>>::default();
+
+                    for entity in medium_texts::table
+                        .filter(medium_texts::ticket_id.eq_any(ids))
+                        .order_by((medium_texts::ticket_id, medium_texts::position))
+                        .log_query()
+                        .load::<MediumTextEntity>(self.conn)?
+                    {
+                        by_ticket
+                            .entry(entity.ticket_id)
+                            .or_default()
+                            .push(entity.try_into()?);
+                    }
+
+                    media.extend(by_ticket.into_iter().map(|(ticket_id, locations)| {
+                        (
+                            ticket_id,
+                            Medium::Text {
+                                ticket_id,
+                                locations,
+                            },
+                        )
+                    }));
+                }
+                MediumType::Recording => {
+                    let mut by_ticket = FnvHashMap::<i32, Vec<Span<NaiveTime>>>::default();
+
+                    for entity in medium_recordings::table
+                        .filter(medium_recordings::ticket_id.eq_any(ids))
+                        .order_by((medium_recordings::ticket_id, medium_recordings::position))
+                        .log_query()
+                        .load::<MediumRecordingEntity>(self.conn)?
+                    {
+                        by_ticket
+                            .entry(entity.ticket_id)
+                            .or_default()
+                            .push(entity.try_into()?);
+                    }
+
+                    media.extend(by_ticket.into_iter().map(|(ticket_id, locations)| {
+                        (
+                            ticket_id,
+                            Medium::Recording {
+                                ticket_id,
+                                locations,
+                            },
+                        )
+                    }));
+                }
+                MediumType::Interactive => {
+                    let mut by_ticket = FnvHashMap::<i32, Vec<Url>>::default();
+
+                    for entity in medium_interactives::table
+                        .filter(medium_interactives::ticket_id.eq_any(ids))
+                        .order_by((
+                            medium_interactives::ticket_id,
+                            medium_interactives::position,
+                        ))
+                        .log_query()
+                        .load::<MediumInteractiveEntity>(self.conn)?
+                    {
+                        by_ticket
+                            .entry(entity.ticket_id)
+                            .or_default()
+                            .push(entity.url.parse()?);
+                    }
+
+                    media.extend(by_ticket.into_iter().map(|(ticket_id, urls)| {
+                        (ticket_id, Medium::Interactive { ticket_id, urls })
+                    }));
+                }
+                MediumType::Questionaire => {
+                    let mut by_ticket = FnvHashMap::<i32, Vec<QuestionLocation>>::default();
+
+                    for entity in medium_questionaires::table
+                        .filter(medium_questionaires::ticket_id.eq_any(ids))
+                        .order_by((
+                            medium_questionaires::ticket_id,
+                            medium_questionaires::position,
+                        ))
+                        .log_query()
+                        .load::<MediumQuestionaireEntity>(self.conn)?
+                    {
+                        by_ticket
+                            .entry(entity.ticket_id)
+                            .or_default()
+                            .push(entity.try_into()?);
+                    }
+
+                    media.extend(by_ticket.into_iter().map(|(ticket_id, locations)| {
+                        (
+                            ticket_id,
+                            Medium::Questionaire {
+                                ticket_id,
+                                locations,
+                            },
+                        )
+                    }));
+                }
+            }
+        }
+
+        Ok(media)
+    }
+
+    /// Insert all locations of a ticket's medium, keeping their order.
+    fn insert_medium(&self, ticket_id: i32, medium: NewMedium) -> Result<()> {
+        use super::schema::{
+            medium_interactives, medium_questionaires, medium_recordings, medium_texts,
+        };
+
+        fn positions<T>(locations: Vec<T>) -> Result<Vec<(i32, T)>> {
+            locations
+                .into_iter()
+                .enumerate()
+                .map(|(position, location)| Ok((i32::try_from(position)?, location)))
+                .collect()
+        }
+
+        let (res, expected) = match medium {
+            NewMedium::Text { locations } => {
+                let entities = positions(locations)?
+                    .into_iter()
+                    .map(|(position, span)| MediumTextEntity::from((ticket_id, position, span)))
+                    .collect::<Vec<_>>();
+                let expected = entities.len();
+
+                let res = diesel::insert_into(medium_texts::table)
+                    .values(entities)
+                    .execute(self.conn)?;
+                (res, expected)
+            }
+            NewMedium::Recording { locations } => {
+                let entities = positions(locations)?
+                    .into_iter()
+                    .map(|(position, span)| {
+                        MediumRecordingEntity::from((ticket_id, position, span))
+                    })
+                    .collect::<Vec<_>>();
+                let expected = entities.len();
+
+                let res = diesel::insert_into(medium_recordings::table)
+                    .values(entities)
+                    .execute(self.conn)?;
+                (res, expected)
+            }
+            NewMedium::Interactive { urls } => {
+                let entities = positions(urls)?
+                    .into_iter()
+                    .map(|(position, url)| MediumInteractiveEntity {
+                        ticket_id,
+                        position,
+                        url: url.into(),
+                    })
+                    .collect::<Vec<_>>();
+                let expected = entities.len();
+
+                let res = diesel::insert_into(medium_interactives::table)
+                    .values(entities)
+                    .execute(self.conn)?;
+                (res, expected)
+            }
+            NewMedium::Questionaire { locations } => {
+                let entities = positions(locations)?
+                    .into_iter()
+                    .map(|(position, location)| {
+                        MediumQuestionaireEntity::from((ticket_id, position, location))
+                    })
+                    .collect::<Vec<_>>();
+                let expected = entities.len();
+
+                let res = diesel::insert_into(medium_questionaires::table)
+                    .values(entities)
+                    .execute(self.conn)?;
+                (res, expected)
+            }
+        };
+
+        ensure!(
+            expected > 0 && res == expected,
+            "Failed inserting medium locations"
+        );
+        Ok(())
+    }
+
+    /// Delete all locations of a ticket's medium.
+    fn delete_medium(&self, ticket_id: i32, medium: MediumType) -> Result<()> {
+        use super::schema::{
+            medium_interactives, medium_questionaires, medium_recordings, medium_texts,
+        };
+
+        let res = match medium {
+            MediumType::Text => {
+                diesel::delete(medium_texts::table.filter(medium_texts::ticket_id.eq(ticket_id)))
+                    .log_query()
+                    .execute(self.conn)
+            }
+            MediumType::Recording => diesel::delete(
+                medium_recordings::table.filter(medium_recordings::ticket_id.eq(ticket_id)),
+            )
+            .log_query()
+            .execute(self.conn),
+            MediumType::Interactive => diesel::delete(
+                medium_interactives::table.filter(medium_interactives::ticket_id.eq(ticket_id)),
+            )
+            .log_query()
+            .execute(self.conn),
+            MediumType::Questionaire => diesel::delete(
+                medium_questionaires::table.filter(medium_questionaires::ticket_id.eq(ticket_id)),
+            )
+            .log_query()
+            .execute(self.conn),
+        }?;
+
+        ensure!(res > 0, "Medium for ticket {} not found", ticket_id);
+        Ok(())
+    }
+
+    /// Load user and course names as well as status details and attach them to the given list of
+    /// tickets.
+    fn load_names(&self, tickets: Vec<Ticket>) -> Result<Vec<TicketWithNames>> {
+        use super::schema::{courses, users};
+
+        let mut user_ids = FnvHashSet::default();
+        let mut course_ids = FnvHashSet::default();
+
+        for ticket in &tickets {
+            user_ids.insert(ticket.creator_id);
+            user_ids.extend(ticket.assignee_id);
+            course_ids.insert(ticket.course_id);
+        }
+
+        let courses = courses::table
+            .select((courses::id, courses::code))
+            .filter(courses::id.eq_any(&course_ids))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        let users = users::table
+            .select((users::id, users::name))
+            .filter(users::id.eq_any(&user_ids))
+            .log_query()
+            .load::<(i32, String)>(self.conn)
+            .map(FnvHashMap::from_iter)?;
+
+        let statuses = self.load_statuses()?;
+        let mut labels = self.load_labels(&tickets.iter().map(|t| t.id).collect::<Vec<_>>())?;
+
+        tickets
+            .into_iter()
+            .map(|ticket| {
+                let status = statuses
+                    .get(&ticket.status)
+                    .cloned()
+                    .context("Entry missing for ticket's status")?;
+                let course_name = courses
+                    .get(&ticket.course_id)
+                    .cloned()
+                    .context("Entry missing for tickets's course ID")?;
+                let creator_name = users
+                    .get(&ticket.creator_id)
+                    .cloned()
+                    .context("Entry missing for tickets's creator ID")?;
+                let assignee_name = ticket
+                    .assignee_id
+                    .map(|id| {
+                        users
+                            .get(&id)
+                            .cloned()
+                            .context("Entry missing for ticket's assignee ID")
                     })
+                    .transpose()?;
+                let labels = labels.remove(&ticket.id).unwrap_or_default();
+                Ok(TicketWithNames {
+                    ticket,
+                    status,
+                    course_name,
+                    creator_name,
+                    assignee_name,
+                    labels,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
+    fn list_with_names(&self) -> Result<Vec<TicketWithNames>> {
+        let tickets = self.list()?;
+
+        self.load_names(tickets)
+    }
+
+    fn list_by_creator_id(&self, creator_id: i32) -> Result<Vec<TicketWithNames>> {
+        use super::schema::tickets;
+
+        let tickets = tickets::table
+            .filter(tickets::creator_id.eq(creator_id))
+            .log_query()
+            .load::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+
+        self.load_names(tickets)
+    }
+
+    fn list_by_assignee_id(&self, assignee_id: i32) -> Result<Vec<TicketWithNames>> {
+        use super::schema::tickets;
+
+        let tickets = tickets::table
+            .filter(tickets::assignee_id.eq(assignee_id))
+            .log_query()
+            .load::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+
+        self.load_names(tickets)
+    }
+
+    fn list_unassigned(&self, course_id: Option<i32>) -> Result<Vec<TicketWithNames>> {
+        use super::schema::{tickets, workflow_statuses};
+
+        let open_statuses = workflow_statuses::table
+            .select(workflow_statuses::key)
+            .filter(workflow_statuses::terminal.eq(false))
+            .log_query()
+            .load::<String>(self.conn)?;
+
+        let mut query = tickets::table
+            .filter(tickets::assignee_id.is_null())
+            .filter(tickets::status.eq_any(open_statuses))
+            .into_boxed();
+
+        if let Some(course_id) = course_id {
+            query = query.filter(tickets::course_id.eq(course_id));
+        }
+
+        let tickets = query
+            .order_by(tickets::id)
+            .log_query()
+            .load::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+
+        self.load_names(tickets)
+    }
+
+    fn list_open_with_media(
+        &self,
+        course_id: i32,
+        type_: TicketType,
+    ) -> Result<Vec<(TicketWithNames, Medium)>> {
+        use super::schema::{tickets, workflow_statuses};
+
+        let open_statuses = workflow_statuses::table
+            .select(workflow_statuses::key)
+            .filter(workflow_statuses::terminal.eq(false))
+            .log_query()
+            .load::<String>(self.conn)?;
+
+        let tickets = tickets::table
+            .filter(tickets::course_id.eq(course_id))
+            .filter(tickets::type_.eq(type_.as_ref()))
+            .filter(tickets::status.eq_any(open_statuses))
+            .filter(tickets::duplicate_of.is_null())
+            .order_by(tickets::id)
+            .log_query()
+            .load::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|entities| {
+                entities
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<Ticket>>>()
+            })?;
+
+        let mut media = self.load_media(
+            &tickets
+                .iter()
+                .map(|t| (t.id, type_.medium()))
+                .collect::<Vec<_>>(),
+        )?;
+
+        self.load_names(tickets)?
+            .into_iter()
+            .map(|ticket| {
+                let medium = media
+                    .remove(&ticket.ticket.id)
+                    .context("Entry missing for ticket's medium")?;
+                Ok((ticket, medium))
+            })
+            .collect()
+    }
+
+    fn get(&self, id: i32) -> Result<Ticket> {
+        use super::schema::tickets;
+
+        tickets::table
+            .find(id)
+            .log_query()
+            .get_result::<TicketEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(TryInto::try_into)
+    }
+
+    fn get_with_names(&self, id: i32) -> Result<TicketWithNames> {
+        use super::schema::{courses, users, workflow_statuses};
+
+        let ticket = self.get(id)?;
+
+        let status = workflow_statuses::table
+            .find(ticket.status.as_ref())
+            .log_query()
+            .get_result::<WorkflowStatusEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(TryInto::try_into)?;
+
+        let course_name = courses::table
+            .find(ticket.course_id)
+            .select(courses::code)
+            .log_query()
+            .get_result(self.conn)?;
+
+        let creator_name = users::table
+            .find(ticket.creator_id)
+            .select(users::name)
+            .log_query()
+            .get_result(self.conn)?;
+
+        let assignee_name = ticket
+            .assignee_id
+            .map(|assignee_id| {
+                users::table
+                    .find(assignee_id)
+                    .select(users::name)
                     .log_query()
-                    .execute(self.conn),
-                NewMedium::Interactive { url } => diesel::insert_into(medium_interactives::table)
-                    .values(MediumInteractiveEntity {
+                    .get_result(self.conn)
+            })
+            .transpose()?;
+
+        let labels = self.load_labels(&[id])?.remove(&id).unwrap_or_default();
+
+        Ok(TicketWithNames {
+            ticket,
+            status,
+            course_name,
+            creator_name,
+            assignee_name,
+            labels,
+        })
+    }
+
+    fn get_with_rels(&self, id: i32) -> Result<TicketWithRels> {
+        use super::schema::{attachments, ticket_fields, tickets};
+
+        let ticket = self.get_with_names(id)?;
+
+        let medium = self
+            .load_media(&[(id, ticket.ticket.type_.medium())])?
+            .remove(&id)
+            .context("Entry missing for ticket's medium")?;
+
+        let attachments = attachments::table
+            .filter(attachments::ticket_id.eq(id))
+            .order_by(attachments::id)
+            .log_query()
+            .load::<AttachmentEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(|attachments| {
+                attachments
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<Attachment>>>()
+            })?;
+
+        let duplicates = tickets::table
+            .filter(tickets::duplicate_of.eq(id))
+            .select(tickets::id)
+            .order_by(tickets::id)
+            .log_query()
+            .load::<i32>(self.conn)?;
+
+        let fields = ticket_fields::table
+            .filter(ticket_fields::ticket_id.eq(id))
+            .order_by(ticket_fields::position)
+            .log_query()
+            .load::<TicketFieldEntity>(self.conn)?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let timeline = self.load_timeline(id)?;
+        let links = self.load_links(id)?;
+
+        Ok(TicketWithRels {
+            ticket: ticket.ticket,
+            status: ticket.status,
+            course_name: ticket.course_name,
+            creator_name: ticket.creator_name,
+            assignee_name: ticket.assignee_name,
+            labels: ticket.labels,
+            medium,
+            timeline,
+            attachments,
+            duplicates,
+            links,
+            fields,
+        })
+    }
+
+    fn create(
+        &self,
+        mut ticket: NewTicket,
+        priority: Priority,
+        created_at: DateTime<Utc>,
+        medium: NewMedium,
+    ) -> Result<i32> {
+        use super::schema::{ticket_fields, tickets};
+
+        let fields = mem::take(&mut ticket.fields);
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(tickets::table)
+                .values(NewTicketEntity::from((ticket, priority, created_at)))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting ticket");
+
+            let ticket_id = tickets::table
+                .select(tickets::id)
+                .order_by(tickets::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            self.insert_medium(ticket_id, medium)?;
+
+            let fields = fields
+                .into_iter()
+                .enumerate()
+                .map(|(position, field)| {
+                    Ok(TicketFieldEntity {
                         ticket_id,
-                        url: url.into(),
+                        position: i32::try_from(position)?,
+                        label: field.label,
+                        value: field.value,
                     })
-                    .log_query()
-                    .execute(self.conn),
-                NewMedium::Questionaire { question, answer } => {
-                    diesel::insert_into(medium_questionaires::table)
-                        .values(MediumQuestionaireEntity {
-                            ticket_id,
-                            question: question.into(),
-                            answer,
-                        })
-                        .log_query()
-                        .execute(self.conn)
-                }
-            }?;
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            diesel::insert_into(ticket_fields::table)
+                .values(fields)
+                .execute(self.conn)?;
 
-            ensure!(res_medium == 1, "Failed inserting medium");
             Ok(ticket_id)
         })
     }
 
-    fn add_comment(&self, comment: NewComment) -> Result<()> {
-        use super::schema::comments;
+    fn add_comment(&self, comment: NewComment) -> Result<i32> {
+        use super::schema::comments;
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(comments::table)
+                .values(NewCommentEntity::from(comment))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting comment");
+
+            comments::table
+                .select(comments::id)
+                .order_by(comments::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)
+                .map_err(Into::into)
+        })
+    }
+
+    fn get_comment(&self, comment_id: i32) -> Result<Comment> {
+        use super::schema::comments;
+
+        comments::table
+            .find(comment_id)
+            .log_query()
+            .get_result::<CommentEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(TryInto::try_into)
+    }
+
+    fn edit_comment(
+        &self,
+        comment_id: i32,
+        message: String,
+        revision: NewCommentRevision,
+    ) -> Result<()> {
+        use super::schema::{comment_revisions, comments};
+
+        self.conn.transaction(|| {
+            let edited_at = revision.timestamp.to_rfc3339();
+
+            let res = diesel::insert_into(comment_revisions::table)
+                .values(NewCommentRevisionEntity::from(revision))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting comment revision");
+
+            let res = diesel::update(comments::table.find(comment_id))
+                .set((
+                    comments::message.eq(message),
+                    comments::edited_at.eq(edited_at),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Comment with ID {} not found", comment_id);
+            Ok(())
+        })
+    }
+
+    fn delete_comment(&self, comment_id: i32, revision: NewCommentRevision) -> Result<()> {
+        use super::schema::{comment_revisions, comments};
+
+        self.conn.transaction(|| {
+            let edited_at = revision.timestamp.to_rfc3339();
+
+            let res = diesel::insert_into(comment_revisions::table)
+                .values(NewCommentRevisionEntity::from(revision))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting comment revision");
+
+            let res = diesel::update(comments::table.find(comment_id))
+                .set((
+                    comments::message.eq(""),
+                    comments::edited_at.eq(edited_at),
+                    comments::deleted.eq(true),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Comment with ID {} not found", comment_id);
+            Ok(())
+        })
+    }
+
+    fn list_comment_revisions(&self, id: i32) -> Result<Vec<CommentRevisionWithNames>> {
+        use super::schema::{comment_revisions, comments, users};
+
+        let comment_ids = comments::table
+            .filter(comments::ticket_id.eq(id))
+            .select(comments::id)
+            .log_query()
+            .load::<i32>(self.conn)?;
+
+        comment_revisions::table
+            .filter(comment_revisions::comment_id.eq_any(comment_ids))
+            .inner_join(users::table)
+            .select((comment_revisions::all_columns, users::name))
+            .order_by(comment_revisions::id)
+            .log_query()
+            .load::<(CommentRevisionEntity, String)>(self.conn)
+            .map_err(Into::into)
+            .and_then(|revisions| {
+                revisions
+                    .into_iter()
+                    .map(|(revision, editor_name)| {
+                        Ok(CommentRevisionWithNames {
+                            revision: revision.try_into()?,
+                            editor_name,
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    fn update(&self, ticket: EditTicket) -> Result<()> {
+        use super::schema::tickets;
+
+        let res = diesel::update(tickets::table.find(ticket.id))
+            .set(tickets::priority.eq(ticket.priority.as_ref()))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Ticket with ID {} not found", ticket.id);
+        Ok(())
+    }
+
+    fn update_content(&self, ticket: EditTicketContent, revision: NewTicketRevision) -> Result<()> {
+        use super::schema::{ticket_revisions, tickets};
+
+        let id = ticket.id;
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(ticket_revisions::table)
+                .values(NewTicketRevisionEntity::from(revision))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting ticket revision");
+
+            let res = diesel::update(tickets::table.find(id))
+                .set((
+                    tickets::title.eq(ticket.title),
+                    tickets::description.eq(ticket.description),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Ticket with ID {} not found", id);
+
+            self.delete_medium(id, ticket.medium.medium_type())?;
+            self.insert_medium(id, ticket.medium)
+        })
+    }
+
+    fn move_ticket(
+        &self,
+        ticket: MoveTicket,
+        old_medium: MediumType,
+        revision: Option<NewTicketRevision>,
+    ) -> Result<()> {
+        use super::schema::{labels, ticket_labels, ticket_revisions, tickets};
+
+        let id = ticket.id;
+
+        self.conn.transaction(|| {
+            let old_course_id = tickets::table
+                .find(id)
+                .select(tickets::course_id)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let res = diesel::update(tickets::table.find(id))
+                .set((
+                    tickets::course_id.eq(ticket.course_id),
+                    tickets::type_.eq(ticket.type_.as_ref()),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Ticket with ID {} not found", id);
+
+            if old_course_id != ticket.course_id {
+                diesel::update(tickets::table.find(id))
+                    .set(tickets::forwarded.eq(false))
+                    .log_query()
+                    .execute(self.conn)?;
+
+                let course_labels = labels::table
+                    .select(labels::id)
+                    .filter(labels::course_id.eq(old_course_id));
+
+                diesel::delete(
+                    ticket_labels::table
+                        .filter(ticket_labels::ticket_id.eq(id))
+                        .filter(ticket_labels::label_id.eq_any(course_labels)),
+                )
+                .log_query()
+                .execute(self.conn)?;
+            }
+
+            if let Some(revision) = revision {
+                let res = diesel::insert_into(ticket_revisions::table)
+                    .values(NewTicketRevisionEntity::from(revision))
+                    .log_query()
+                    .execute(self.conn)?;
+
+                ensure!(res == 1, "Failed inserting ticket revision");
+            }
+
+            if let Some(medium) = ticket.medium {
+                self.delete_medium(id, old_medium)?;
+                self.insert_medium(id, medium)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn list_revisions(&self, id: i32) -> Result<Vec<TicketRevisionWithNames>> {
+        use super::schema::{ticket_revisions, users};
+
+        ticket_revisions::table
+            .filter(ticket_revisions::ticket_id.eq(id))
+            .inner_join(users::table)
+            .select((ticket_revisions::all_columns, users::name))
+            .order_by(ticket_revisions::id)
+            .log_query()
+            .load::<(TicketRevisionEntity, String)>(self.conn)
+            .map_err(Into::into)
+            .and_then(|revisions| {
+                revisions
+                    .into_iter()
+                    .map(|(revision, editor_name)| {
+                        Ok(TicketRevisionWithNames {
+                            revision: revision.try_into()?,
+                            editor_name,
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    fn forward(&self, id: i32) -> Result<i32> {
+        use super::schema::{courses, tickets};
+
+        let author_id = tickets::table
+            .find(id)
+            .inner_join(courses::table)
+            .select(courses::author_id)
+            .log_query()
+            .get_result::<i32>(self.conn)?;
+
+        let res = diesel::update(tickets::table.find(id))
+            .set((
+                tickets::forwarded.eq(true),
+                tickets::assignee_id.eq(author_id),
+            ))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Ticket with ID {} not found", id);
+        Ok(author_id)
+    }
+
+    fn return_to_tutor(&self, id: i32) -> Result<i32> {
+        use super::schema::{courses, tickets};
+
+        let tutor_id = tickets::table
+            .find(id)
+            .inner_join(courses::table)
+            .select(courses::tutor_id)
+            .log_query()
+            .get_result::<i32>(self.conn)?;
+
+        let res = diesel::update(tickets::table.find(id))
+            .set((
+                tickets::forwarded.eq(false),
+                tickets::assignee_id.eq(tutor_id),
+            ))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Ticket with ID {} not found", id);
+        Ok(tutor_id)
+    }
+
+    fn assign(&self, id: i32, assignee_id: i32) -> Result<()> {
+        use super::schema::tickets;
+
+        let res = diesel::update(tickets::table.find(id))
+            .set(tickets::assignee_id.eq(assignee_id))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Ticket with ID {} not found", id);
+        Ok(())
+    }
+
+    fn unassign(&self, id: i32) -> Result<()> {
+        use super::schema::tickets;
 
-        let res = diesel::insert_into(comments::table)
-            .values(NewCommentEntity::from(comment))
+        let res = diesel::update(tickets::table.find(id))
+            .set(tickets::assignee_id.eq(None::<i32>))
             .log_query()
             .execute(self.conn)?;
 
-        ensure!(res == 1, "Failed inserting comment");
+        ensure!(res == 1, "Ticket with ID {} not found", id);
         Ok(())
     }
 
-    fn update(&self, ticket: EditTicket) -> Result<()> {
+    fn set_labels(&self, id: i32, label_ids: &[i32]) -> Result<()> {
+        use super::schema::{labels, ticket_labels, tickets};
+
+        self.conn.transaction(|| {
+            let course_id = tickets::table
+                .find(id)
+                .select(tickets::course_id)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let available = labels::table
+                .select(labels::id)
+                .filter(labels::id.eq_any(label_ids))
+                .filter(
+                    labels::course_id
+                        .is_null()
+                        .or(labels::course_id.eq(course_id)),
+                )
+                .log_query()
+                .load::<i32>(self.conn)?;
+
+            ensure!(
+                available.len() == label_ids.len(),
+                "Labels not available for the ticket's course"
+            );
+
+            diesel::delete(ticket_labels::table.filter(ticket_labels::ticket_id.eq(id)))
+                .log_query()
+                .execute(self.conn)?;
+
+            let labels = available
+                .into_iter()
+                .map(|label_id| {
+                    (
+                        ticket_labels::ticket_id.eq(id),
+                        ticket_labels::label_id.eq(label_id),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(ticket_labels::table)
+                .values(labels)
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+
+    fn get_status(&self, id: i32) -> Result<Status> {
         use super::schema::tickets;
 
-        let res = diesel::update(tickets::table.find(ticket.id))
-            .set(tickets::priority.eq(ticket.priority.as_ref()))
+        tickets::table
+            .find(id)
+            .select(tickets::status)
+            .log_query()
+            .get_result::<String>(self.conn)
+            .map_err(Into::into)
+            .and_then(|v| v.parse().map_err(Into::into))
+    }
+
+    fn set_status(&self, id: i32, status: Status, closed_at: Option<DateTime<Utc>>) -> Result<()> {
+        use super::schema::tickets;
+
+        let res = diesel::update(tickets::table.find(id))
+            .set((
+                tickets::status.eq(status.as_ref()),
+                tickets::closed_at.eq(closed_at.map(|ts| ts.to_rfc3339())),
+            ))
             .log_query()
             .execute(self.conn)?;
 
-        ensure!(res == 1, "Ticket with ID {} not found", ticket.id);
+        ensure!(res == 1, "Ticket with ID {} not found", id);
         Ok(())
     }
 
-    fn forward(&self, id: i32) -> Result<()> {
+    fn reopen(&self, id: i32) -> Result<()> {
         use super::schema::tickets;
 
         let res = diesel::update(tickets::table.find(id))
-            .set(tickets::forwarded.eq(true))
+            .set((
+                tickets::status.eq(Status::OPEN.as_ref()),
+                tickets::closed_at.eq(None::<String>),
+                tickets::reopen_count.eq(tickets::reopen_count + 1),
+            ))
             .log_query()
             .execute(self.conn)?;
 
@@ -697,23 +2678,71 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
         Ok(())
     }
 
-    fn get_status(&self, id: i32) -> Result<Status> {
-        use super::schema::tickets;
+    fn add_reopen_request(&self, id: i32, user_id: i32, at: DateTime<Utc>) -> Result<()> {
+        use super::schema::reopen_requests;
 
-        tickets::table
+        let res = diesel::insert_or_ignore_into(reopen_requests::table)
+            .values((
+                reopen_requests::ticket_id.eq(id),
+                reopen_requests::requester_id.eq(user_id),
+                reopen_requests::requested_at.eq(at.to_rfc3339()),
+            ))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(
+            res == 1,
+            "Ticket with ID {} already has a reopen request",
+            id
+        );
+        Ok(())
+    }
+
+    fn reopen_requester(&self, id: i32) -> Result<Option<i32>> {
+        use super::schema::reopen_requests;
+
+        reopen_requests::table
             .find(id)
-            .select(tickets::status)
+            .select(reopen_requests::requester_id)
             .log_query()
-            .get_result::<String>(self.conn)
+            .get_result(self.conn)
+            .optional()
             .map_err(Into::into)
-            .and_then(|v| v.parse().map_err(Into::into))
     }
 
-    fn set_status(&self, id: i32, status: Status) -> Result<()> {
+    fn remove_reopen_request(&self, id: i32) -> Result<()> {
+        use super::schema::reopen_requests;
+
+        diesel::delete(reopen_requests::table.find(id))
+            .log_query()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    fn set_first_response(&self, id: i32, at: DateTime<Utc>) -> Result<()> {
+        use super::schema::tickets;
+
+        diesel::update(
+            tickets::table
+                .find(id)
+                .filter(tickets::first_response_at.is_null()),
+        )
+        .set(tickets::first_response_at.eq(at.to_rfc3339()))
+        .log_query()
+        .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    fn set_escalation(&self, id: i32, level: u32, at: DateTime<Utc>) -> Result<()> {
         use super::schema::tickets;
 
         let res = diesel::update(tickets::table.find(id))
-            .set(tickets::status.eq(status.as_ref()))
+            .set((
+                tickets::escalation_level.eq(i32::try_from(level)?),
+                tickets::escalated_at.eq(at.to_rfc3339()),
+            ))
             .log_query()
             .execute(self.conn)?;
 
@@ -721,8 +2750,101 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
         Ok(())
     }
 
+    fn add_event(&self, event: NewTicketEvent) -> Result<()> {
+        use super::schema::ticket_events;
+
+        let res = diesel::insert_into(ticket_events::table)
+            .values(NewTicketEventEntity::from(event))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Failed inserting ticket event");
+        Ok(())
+    }
+
+    fn mark_duplicate(&self, id: i32, primary_id: i32, closed_at: DateTime<Utc>) -> Result<()> {
+        use super::schema::{comments, ticket_watchers, tickets};
+
+        self.conn.transaction(|| {
+            let res = diesel::update(tickets::table.find(id))
+                .set((
+                    tickets::duplicate_of.eq(primary_id),
+                    tickets::status.eq(Status::DUPLICATE.as_ref()),
+                    tickets::closed_at.eq(closed_at.to_rfc3339()),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Ticket with ID {} not found", id);
+
+            diesel::update(tickets::table.filter(tickets::duplicate_of.eq(id)))
+                .set(tickets::duplicate_of.eq(primary_id))
+                .log_query()
+                .execute(self.conn)?;
+
+            let primary_creator_id = tickets::table
+                .find(primary_id)
+                .select(tickets::creator_id)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let creator_id = tickets::table
+                .find(id)
+                .select(tickets::creator_id)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            let watcher_ids = ticket_watchers::table
+                .filter(ticket_watchers::ticket_id.eq(id))
+                .select(ticket_watchers::user_id)
+                .log_query()
+                .load::<i32>(self.conn)?;
+
+            let watchers = iter::once(creator_id)
+                .chain(watcher_ids)
+                .filter(|&user_id| user_id != primary_creator_id)
+                .map(|user_id| {
+                    (
+                        ticket_watchers::ticket_id.eq(primary_id),
+                        ticket_watchers::user_id.eq(user_id),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_or_ignore_into(ticket_watchers::table)
+                .values(&watchers)
+                .execute(self.conn)?;
+
+            diesel::delete(ticket_watchers::table.filter(ticket_watchers::ticket_id.eq(id)))
+                .log_query()
+                .execute(self.conn)?;
+
+            let copies = comments::table
+                .filter(comments::ticket_id.eq(id))
+                .filter(comments::deleted.eq(false))
+                .log_query()
+                .load::<CommentEntity>(self.conn)?
+                .into_iter()
+                .map(|comment| NewCommentEntity {
+                    ticket_id: primary_id,
+                    creator_id: comment.creator_id,
+                    timestamp: comment.timestamp,
+                    message: comment.message,
+                    source_ticket_id: comment.source_ticket_id.or(Some(id)),
+                    internal: comment.internal,
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(comments::table)
+                .values(&copies)
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+
     fn search(&self, search: &TicketSearch) -> Result<Vec<TicketWithNames>> {
-        use super::schema::tickets;
+        use super::schema::{course_programs, courses, ticket_labels, tickets};
 
         let mut query = tickets::table.into_boxed();
 
@@ -730,10 +2852,30 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
             query = query.filter(tickets::title.like(format!("%{}%", title)));
         }
 
+        if let Some(program_id) = search.program_id {
+            let course_ids = course_programs::table
+                .select(course_programs::course_id)
+                .filter(course_programs::program_id.eq(program_id))
+                .log_query()
+                .load::<i32>(self.conn)?;
+
+            query = query.filter(tickets::course_id.eq_any(course_ids));
+        }
+
         if let Some(course_id) = search.course_id {
             query = query.filter(tickets::course_id.eq(course_id));
         }
 
+        if search.active_only {
+            let course_ids = courses::table
+                .select(courses::id)
+                .filter(courses::active.eq(true))
+                .log_query()
+                .load::<i32>(self.conn)?;
+
+            query = query.filter(tickets::course_id.eq_any(course_ids));
+        }
+
         if let Some(category) = search.category {
             query = query.filter(tickets::category.eq(category.to_string()));
         }
@@ -742,35 +2884,89 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
             query = query.filter(tickets::priority.eq(priority.to_string()));
         }
 
-        if let Some(status) = search.status {
+        if let Some(status) = &search.status {
             query = query.filter(tickets::status.eq(status.to_string()));
         }
 
-        let tickets = query
+        if !search.labels.is_empty() {
+            let mut counts = FnvHashMap::<i32, usize>::default();
+
+            for ticket_id in ticket_labels::table
+                .select(ticket_labels::ticket_id)
+                .filter(ticket_labels::label_id.eq_any(&search.labels))
+                .log_query()
+                .load::<i32>(self.conn)?
+            {
+                *counts.entry(ticket_id).or_default() += 1;
+            }
+
+            let ticket_ids = counts
+                .into_iter()
+                .filter(|&(_, count)| count == search.labels.len())
+                .map(|(ticket_id, _)| ticket_id)
+                .collect::<Vec<_>>();
+
+            query = query.filter(tickets::id.eq_any(ticket_ids));
+        }
+
+        query = match search.order {
+            Some(TicketOrder::Newest) => query.order_by(tickets::id.desc()),
+            Some(TicketOrder::Votes) => query.order_by((tickets::votes.desc(), tickets::id)),
+            Some(TicketOrder::Location) | None => query.order_by(tickets::id),
+        };
+
+        let mut tickets = query
             .log_query()
             .load::<TicketEntity>(self.conn)
             .map_err(Into::into)
-            .and_then(|entities| entities.into_iter().map(TryInto::try_into).collect())?;
+            .and_then(|entities| {
+                entities
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<Ticket>>>()
+            })?;
+
+        if search.order == Some(TicketOrder::Location) {
+            // Locations are only comparable within the same material, so tickets are grouped by
+            // course and medium first. The sort is stable, keeping ties ordered by ID.
+            let media = self.load_media(
+                &tickets
+                    .iter()
+                    .map(|t| (t.id, t.type_.medium()))
+                    .collect::<Vec<_>>(),
+            )?;
+
+            tickets.sort_by(|a, b| {
+                a.course_id
+                    .cmp(&b.course_id)
+                    .then_with(|| a.type_.medium().cmp(&b.type_.medium()))
+                    .then_with(|| match (media.get(&a.id), media.get(&b.id)) {
+                        (Some(a), Some(b)) => a.cmp_location(b),
+                        _ => Ordering::Equal,
+                    })
+            });
+        }
 
         self.load_names(tickets)
     }
 
+    fn list_media(&self, tickets: &[TicketWithNames]) -> Result<FnvHashMap<i32, Medium>> {
+        self.load_media(
+            &tickets
+                .iter()
+                .map(|t| (t.ticket.id, t.ticket.type_.medium()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
     fn activate_ticket(&self, id: i32, user_id: i32) -> Result<bool> {
-        use super::schema::{courses, tickets};
+        use super::schema::tickets;
 
         let res = tickets::table
             .find(id)
             .select(tickets::id)
-            .inner_join(courses::table)
-            .filter(tickets::status.eq(Status::Open.as_ref()))
-            .filter(
-                courses::tutor_id
-                    .eq(user_id)
-                    .and(tickets::forwarded.eq(false))
-                    .or(courses::author_id
-                        .eq(user_id)
-                        .and(tickets::forwarded.eq(true))),
-            )
+            .filter(tickets::status.eq(Status::OPEN.as_ref()))
+            .filter(tickets::assignee_id.eq(user_id))
             .log_query()
             .get_result::<i32>(self.conn);
 
@@ -782,7 +2978,7 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
 
         if found {
             let res = diesel::update(tickets::table.find(id))
-                .set(tickets::status.eq(Status::InProgress.as_ref()))
+                .set(tickets::status.eq(Status::IN_PROGRESS.as_ref()))
                 .log_query()
                 .execute(self.conn)?;
 
@@ -793,6 +2989,125 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
         Ok(false)
     }
 
+    fn add_link(&self, link: NewTicketLink) -> Result<()> {
+        use super::schema::ticket_links;
+
+        let res = diesel::insert_into(ticket_links::table)
+            .values(NewTicketLinkEntity::from(link))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Failed inserting ticket link");
+        Ok(())
+    }
+
+    fn remove_link(&self, id: i32, link_id: i32) -> Result<()> {
+        use super::schema::ticket_links;
+
+        let res = diesel::delete(
+            ticket_links::table.find(link_id).filter(
+                ticket_links::source_id
+                    .eq(id)
+                    .or(ticket_links::target_id.eq(id)),
+            ),
+        )
+        .log_query()
+        .execute(self.conn)?;
+
+        ensure!(
+            res == 1,
+            "Link with ID {} not found for ticket {}",
+            link_id,
+            id
+        );
+        Ok(())
+    }
+
+    fn add_vote(&self, id: i32, user_id: i32) -> Result<Option<u32>> {
+        use super::schema::{ticket_votes, tickets};
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_or_ignore_into(ticket_votes::table)
+                .values((
+                    ticket_votes::ticket_id.eq(id),
+                    ticket_votes::user_id.eq(user_id),
+                ))
+                .log_query()
+                .execute(self.conn)?;
+
+            if res == 0 {
+                return Ok(None);
+            }
+
+            let res = diesel::update(tickets::table.find(id))
+                .set(tickets::votes.eq(tickets::votes + 1))
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Ticket with ID {} not found", id);
+
+            let votes = tickets::table
+                .find(id)
+                .select(tickets::votes)
+                .log_query()
+                .get_result::<i32>(self.conn)?;
+
+            Ok(Some(votes.try_into()?))
+        })
+    }
+
+    fn has_voted(&self, id: i32, user_id: i32) -> Result<bool> {
+        use diesel::dsl::count;
+
+        use super::schema::ticket_votes;
+
+        let res = ticket_votes::table
+            .find((id, user_id))
+            .select(count(ticket_votes::user_id))
+            .log_query()
+            .get_result::<i64>(self.conn)?;
+
+        Ok(res == 1)
+    }
+
+    fn add_watcher(&self, id: i32, user_id: i32) -> Result<()> {
+        use super::schema::ticket_watchers;
+
+        diesel::insert_or_ignore_into(ticket_watchers::table)
+            .values((
+                ticket_watchers::ticket_id.eq(id),
+                ticket_watchers::user_id.eq(user_id),
+            ))
+            .log_query()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    fn remove_watcher(&self, id: i32, user_id: i32) -> Result<()> {
+        use super::schema::ticket_watchers;
+
+        diesel::delete(ticket_watchers::table.find((id, user_id)))
+            .log_query()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    fn is_watcher(&self, id: i32, user_id: i32) -> Result<bool> {
+        use diesel::dsl::count;
+
+        use super::schema::ticket_watchers;
+
+        let res = ticket_watchers::table
+            .find((id, user_id))
+            .select(count(ticket_watchers::user_id))
+            .log_query()
+            .get_result::<i64>(self.conn)?;
+
+        Ok(res == 1)
+    }
+
     fn is_creator(&self, id: i32, user_id: i32) -> Result<bool> {
         use diesel::dsl::count;
 
@@ -807,9 +3122,107 @@ impl<'a> TicketRepository for TicketRepositoryImpl<'a> {
 
         Ok(res == 1)
     }
+
+    fn is_program_manager(&self, id: i32, user_id: i32) -> Result<bool> {
+        use diesel::dsl::count_star;
+
+        use super::schema::{course_programs, program_managers, tickets};
+
+        let course_id = tickets::table
+            .find(id)
+            .select(tickets::course_id)
+            .log_query()
+            .get_result::<i32>(self.conn)?;
+
+        let program_ids = program_managers::table
+            .select(program_managers::program_id)
+            .filter(program_managers::user_id.eq(user_id))
+            .log_query()
+            .load::<i32>(self.conn)?;
+
+        let res = course_programs::table
+            .select(count_star())
+            .filter(course_programs::course_id.eq(course_id))
+            .filter(course_programs::program_id.eq_any(program_ids))
+            .log_query()
+            .get_result::<i64>(self.conn)?;
+
+        Ok(res > 0)
+    }
+
+    fn transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        self.conn.transaction(f)
+    }
 }
 
 /// Create a new ticket repository.
 pub fn ticket_repo(conn: &SqliteConnection) -> impl TicketRepository + '_ {
     TicketRepositoryImpl { conn }
 }
+
+/// The attachment repository manages files attached to tickets and comments. Only the metadata
+/// is kept in the database, the content itself is handled by a
+/// [`FileStore`](crate::storage::FileStore).
+pub trait AttachmentRepository {
+    /// Get a single attachment by ID.
+    fn get(&self, id: i32) -> Result<Attachment>;
+    /// Create a new attachment, returning its ID.
+    fn create(&self, attachment: NewAttachment) -> Result<i32>;
+    /// Delete an attachment.
+    fn delete(&self, id: i32) -> Result<()>;
+}
+
+/// Main implementation of [`AttachmentRepository`].
+struct AttachmentRepositoryImpl<'a> {
+    conn: &'a SqliteConnection,
+}
+
+impl<'a> AttachmentRepository for AttachmentRepositoryImpl<'a> {
+    fn get(&self, id: i32) -> Result<Attachment> {
+        use super::schema::attachments;
+
+        attachments::table
+            .find(id)
+            .log_query()
+            .get_result::<AttachmentEntity>(self.conn)
+            .map_err(Into::into)
+            .and_then(TryInto::try_into)
+    }
+
+    fn create(&self, attachment: NewAttachment) -> Result<i32> {
+        use super::schema::attachments;
+
+        self.conn.transaction(|| {
+            let res = diesel::insert_into(attachments::table)
+                .values(NewAttachmentEntity::try_from(attachment)?)
+                .log_query()
+                .execute(self.conn)?;
+
+            ensure!(res == 1, "Failed inserting attachment");
+
+            attachments::table
+                .select(attachments::id)
+                .order_by(attachments::id.desc())
+                .limit(1)
+                .log_query()
+                .get_result::<i32>(self.conn)
+                .map_err(Into::into)
+        })
+    }
+
+    fn delete(&self, id: i32) -> Result<()> {
+        use super::schema::attachments;
+
+        let res = diesel::delete(attachments::table.find(id))
+            .log_query()
+            .execute(self.conn)?;
+
+        ensure!(res == 1, "Attachment with ID {} not found", id);
+        Ok(())
+    }
+}
+
+/// Create a new attachment repository.
+pub fn attachment_repo(conn: &SqliteConnection) -> impl AttachmentRepository + '_ {
+    AttachmentRepositoryImpl { conn }
+}
@@ -1,3 +1,26 @@
+table! {
+    attachments (id) {
+        id -> Integer,
+        ticket_id -> Integer,
+        comment_id -> Nullable<Integer>,
+        creator_id -> Integer,
+        file_name -> Text,
+        mime_type -> Text,
+        size -> BigInt,
+        timestamp -> Text,
+    }
+}
+
+table! {
+    comment_revisions (id) {
+        id -> Integer,
+        comment_id -> Integer,
+        editor_id -> Integer,
+        timestamp -> Text,
+        message -> Text,
+    }
+}
+
 table! {
     comments (id) {
         id -> Integer,
@@ -5,6 +28,17 @@ table! {
         creator_id -> Integer,
         timestamp -> Text,
         message -> Text,
+        source_ticket_id -> Nullable<Integer>,
+        internal -> Bool,
+        edited_at -> Nullable<Text>,
+        deleted -> Bool,
+    }
+}
+
+table! {
+    course_programs (course_id, program_id) {
+        course_id -> Integer,
+        program_id -> Integer,
     }
 }
 
@@ -16,36 +50,135 @@ table! {
         author_id -> Integer,
         tutor_id -> Integer,
         active -> Bool,
+        public_reports -> Bool,
+    }
+}
+
+table! {
+    faculties (id) {
+        id -> Integer,
+        name -> Text,
     }
 }
 
 table! {
-    medium_interactives (ticket_id) {
+    labels (id) {
+        id -> Integer,
+        name -> Text,
+        color -> Text,
+        course_id -> Nullable<Integer>,
+    }
+}
+
+table! {
+    medium_interactives (ticket_id, position) {
         ticket_id -> Integer,
+        position -> Integer,
         url -> Text,
     }
 }
 
 table! {
-    medium_questionaires (ticket_id) {
+    medium_questionaires (ticket_id, position) {
         ticket_id -> Integer,
+        position -> Integer,
         question -> Integer,
+        end_question -> Nullable<Integer>,
         answer -> Text,
     }
 }
 
 table! {
-    medium_recordings (ticket_id) {
+    medium_recordings (ticket_id, position) {
         ticket_id -> Integer,
+        position -> Integer,
         time -> Text,
+        end_time -> Nullable<Text>,
     }
 }
 
 table! {
-    medium_texts (ticket_id) {
+    medium_texts (ticket_id, position) {
         ticket_id -> Integer,
+        position -> Integer,
         page -> Integer,
         line -> Integer,
+        end_page -> Nullable<Integer>,
+        end_line -> Nullable<Integer>,
+    }
+}
+
+table! {
+    program_managers (program_id, user_id) {
+        program_id -> Integer,
+        user_id -> Integer,
+    }
+}
+
+table! {
+    programs (id) {
+        id -> Integer,
+        faculty_id -> Integer,
+        code -> Text,
+        title -> Text,
+    }
+}
+
+table! {
+    public_report_fields (report_id, position) {
+        report_id -> Integer,
+        position -> Integer,
+        label -> Text,
+        value -> Text,
+    }
+}
+
+table! {
+    public_report_locations (report_id, position) {
+        report_id -> Integer,
+        position -> Integer,
+        page -> Nullable<Integer>,
+        line -> Nullable<Integer>,
+        end_page -> Nullable<Integer>,
+        end_line -> Nullable<Integer>,
+        time -> Nullable<Text>,
+        end_time -> Nullable<Text>,
+        url -> Nullable<Text>,
+        question -> Nullable<Integer>,
+        end_question -> Nullable<Integer>,
+        answer -> Nullable<Text>,
+    }
+}
+
+table! {
+    public_reports (id) {
+        id -> Integer,
+        course_id -> Integer,
+        #[sql_name = "type"]
+        type_ -> Text,
+        category -> Text,
+        title -> Text,
+        description -> Text,
+        contact_email -> Nullable<Text>,
+        ip_address -> Text,
+        created_at -> Text,
+        ticket_id -> Nullable<Integer>,
+        rejected -> Bool,
+    }
+}
+
+table! {
+    reopen_requests (ticket_id) {
+        ticket_id -> Integer,
+        requester_id -> Integer,
+        requested_at -> Text,
+    }
+}
+
+table! {
+    rule_labels (rule_id, label_id) {
+        rule_id -> Integer,
+        label_id -> Integer,
     }
 }
 
@@ -56,6 +189,18 @@ table! {
     }
 }
 
+table! {
+    ticket_events (id) {
+        id -> Integer,
+        ticket_id -> Integer,
+        actor_id -> Integer,
+        timestamp -> Text,
+        field -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+    }
+}
+
 table! {
     tickets (id) {
         id -> Integer,
@@ -69,6 +214,92 @@ table! {
         forwarded -> Bool,
         course_id -> Integer,
         creator_id -> Integer,
+        assignee_id -> Nullable<Integer>,
+        closed_at -> Nullable<Text>,
+        reopen_count -> Integer,
+        duplicate_of -> Nullable<Integer>,
+        votes -> Integer,
+        created_at -> Text,
+        first_response_at -> Nullable<Text>,
+        escalation_level -> Integer,
+        escalated_at -> Nullable<Text>,
+    }
+}
+
+table! {
+    ticket_fields (ticket_id, position) {
+        ticket_id -> Integer,
+        position -> Integer,
+        label -> Text,
+        value -> Text,
+    }
+}
+
+table! {
+    ticket_labels (ticket_id, label_id) {
+        ticket_id -> Integer,
+        label_id -> Integer,
+    }
+}
+
+table! {
+    ticket_links (id) {
+        id -> Integer,
+        source_id -> Integer,
+        target_id -> Integer,
+        kind -> Text,
+    }
+}
+
+table! {
+    ticket_revisions (id) {
+        id -> Integer,
+        ticket_id -> Integer,
+        editor_id -> Integer,
+        timestamp -> Text,
+        title -> Text,
+        description -> Text,
+        location -> Text,
+    }
+}
+
+table! {
+    ticket_rules (id) {
+        id -> Integer,
+        name -> Text,
+        course_id -> Nullable<Integer>,
+        #[sql_name = "type"]
+        type_ -> Nullable<Text>,
+        category -> Nullable<Text>,
+        medium -> Nullable<Text>,
+        keywords -> Text,
+        priority -> Nullable<Text>,
+        forward -> Bool,
+        assignee_id -> Nullable<Integer>,
+    }
+}
+
+table! {
+    ticket_templates (type_) {
+        #[sql_name = "type"]
+        type_ -> Text,
+        description -> Text,
+        help -> Text,
+        fields -> Text,
+    }
+}
+
+table! {
+    ticket_votes (ticket_id, user_id) {
+        ticket_id -> Integer,
+        user_id -> Integer,
+    }
+}
+
+table! {
+    ticket_watchers (ticket_id, user_id) {
+        ticket_id -> Integer,
+        user_id -> Integer,
     }
 }
 
@@ -84,23 +315,97 @@ table! {
     }
 }
 
+table! {
+    workflow_statuses (key) {
+        key -> Text,
+        name -> Text,
+        color -> Text,
+        icon -> Text,
+        terminal -> Bool,
+        position -> Integer,
+    }
+}
+
+table! {
+    workflow_transitions (from_status, to_status, role) {
+        from_status -> Text,
+        to_status -> Text,
+        role -> Text,
+    }
+}
+
+joinable!(attachments -> comments (comment_id));
+joinable!(attachments -> tickets (ticket_id));
+joinable!(attachments -> users (creator_id));
+joinable!(comment_revisions -> comments (comment_id));
+joinable!(comment_revisions -> users (editor_id));
 joinable!(comments -> tickets (ticket_id));
 joinable!(comments -> users (creator_id));
+joinable!(course_programs -> courses (course_id));
+joinable!(course_programs -> programs (program_id));
+joinable!(labels -> courses (course_id));
 joinable!(medium_interactives -> tickets (ticket_id));
 joinable!(medium_questionaires -> tickets (ticket_id));
 joinable!(medium_recordings -> tickets (ticket_id));
 joinable!(medium_texts -> tickets (ticket_id));
+joinable!(program_managers -> programs (program_id));
+joinable!(program_managers -> users (user_id));
+joinable!(programs -> faculties (faculty_id));
+joinable!(public_report_fields -> public_reports (report_id));
+joinable!(public_report_locations -> public_reports (report_id));
+joinable!(public_reports -> courses (course_id));
+joinable!(public_reports -> tickets (ticket_id));
+joinable!(reopen_requests -> tickets (ticket_id));
+joinable!(reopen_requests -> users (requester_id));
+joinable!(rule_labels -> labels (label_id));
+joinable!(rule_labels -> ticket_rules (rule_id));
+joinable!(ticket_events -> tickets (ticket_id));
+joinable!(ticket_events -> users (actor_id));
+joinable!(ticket_fields -> tickets (ticket_id));
+joinable!(ticket_labels -> labels (label_id));
+joinable!(ticket_labels -> tickets (ticket_id));
+joinable!(ticket_revisions -> tickets (ticket_id));
+joinable!(ticket_revisions -> users (editor_id));
+joinable!(ticket_rules -> courses (course_id));
+joinable!(ticket_rules -> users (assignee_id));
+joinable!(ticket_votes -> tickets (ticket_id));
+joinable!(ticket_votes -> users (user_id));
+joinable!(ticket_watchers -> tickets (ticket_id));
+joinable!(ticket_watchers -> users (user_id));
 joinable!(tickets -> courses (course_id));
 joinable!(tickets -> users (creator_id));
 
 allow_tables_to_appear_in_same_query!(
+    attachments,
+    comment_revisions,
     comments,
+    course_programs,
     courses,
+    faculties,
+    labels,
     medium_interactives,
     medium_questionaires,
     medium_recordings,
     medium_texts,
+    program_managers,
+    programs,
+    public_report_fields,
+    public_report_locations,
+    public_reports,
+    reopen_requests,
+    rule_labels,
     samples,
+    ticket_events,
+    ticket_fields,
+    ticket_labels,
+    ticket_links,
+    ticket_revisions,
+    ticket_rules,
+    ticket_templates,
+    ticket_votes,
+    ticket_watchers,
     tickets,
     users,
+    workflow_statuses,
+    workflow_transitions,
 );
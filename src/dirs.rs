@@ -7,6 +7,7 @@ pub static DIRS: Lazy<Dirs> = Lazy::new(|| Dirs::new().unwrap());
 pub struct Dirs {
     config_file: Utf8PathBuf,
     db_file: Utf8PathBuf,
+    attachment_dir: Utf8PathBuf,
     dirs: UnifiedDirs,
 }
 
@@ -19,6 +20,7 @@ impl Dirs {
         Ok(Self {
             config_file: dirs.config_dir().join("config.toml"),
             db_file: dirs.data_dir().join("data.db"),
+            attachment_dir: dirs.data_dir().join("attachments"),
             dirs,
         })
     }
@@ -35,4 +37,8 @@ impl Dirs {
     pub fn db_dir(&self) -> &Utf8Path {
         self.dirs.data_dir()
     }
+
+    pub fn attachment_dir(&self) -> &Utf8Path {
+        &self.attachment_dir
+    }
 }
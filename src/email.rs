@@ -8,8 +8,8 @@ use lettre::{Message, SmtpTransport, Transport};
 use log::error;
 
 use crate::config::SmtpConfig;
-use crate::language::Translate;
-use crate::models::{Id, Status};
+use crate::markdown;
+use crate::models::Id;
 
 /// A mail sender allows to send emails.
 pub trait MailSender {
@@ -75,16 +75,32 @@ pub trait MailRenderer {
     fn invitation(&self, name: &str, code: &str) -> (&str, String);
     /// Create the status change email for whenever a ticket status changes.
     fn status_change(&self, name: &str, details: StatusDetails<'_>) -> (&str, String);
-    /// Create the new comment email for whenever someone adds a new comment to a ticket.
+    /// Create the new comment email for whenever someone adds a new comment to a ticket. The
+    /// comment is formatted as Markdown and converted to plain text for the email.
     fn new_comment(&self, name: &str, details: CommentDetails<'_>) -> (&str, String);
+    /// Create the mention email for whenever someone mentions a user in a new comment. The
+    /// comment is converted to plain text the same way as for [`Self::new_comment`].
+    fn mention(&self, name: &str, details: CommentDetails<'_>) -> (&str, String);
+    /// Create the overdue email for whenever a ticket missed its due dates and is escalated.
+    fn overdue(&self, name: &str, details: OverdueDetails<'_>) -> (&str, String);
+    /// Create the assignment email for whenever a ticket is assigned to a user.
+    fn assignment(&self, name: &str, details: AssignmentDetails<'_>) -> (&str, String);
+    /// Create the reopen request email for whenever the creator of a closed ticket asks for it to
+    /// be reopened.
+    fn reopen_request(&self, name: &str, details: CommentDetails<'_>) -> (&str, String);
+    /// Create the refusal email for whenever a staff member refuses to reopen a ticket.
+    fn reopen_refused(&self, name: &str, details: CommentDetails<'_>) -> (&str, String);
+    /// Create the return email for whenever an author sends a forwarded ticket back to the tutor.
+    /// The author's comment is converted to plain text the same way as for [`Self::new_comment`].
+    fn returned(&self, name: &str, details: CommentDetails<'_>) -> (&str, String);
 }
 
 /// Detail information to create the status change email.
 pub struct StatusDetails<'a> {
     pub ticket_title: &'a str,
     pub ticket_id: Id,
-    pub old_status: Status,
-    pub new_status: Status,
+    pub old_status: &'a str,
+    pub new_status: &'a str,
 }
 
 /// Detail information to create the new comment email.
@@ -95,6 +111,20 @@ pub struct CommentDetails<'a> {
     pub writer_name: &'a str,
 }
 
+/// Detail information to create the assignment email.
+pub struct AssignmentDetails<'a> {
+    pub ticket_title: &'a str,
+    pub ticket_id: Id,
+    pub assigner_name: &'a str,
+}
+
+/// Detail information to create the overdue email.
+pub struct OverdueDetails<'a> {
+    pub ticket_title: &'a str,
+    pub ticket_id: Id,
+    pub assignee_name: Option<&'a str>,
+}
+
 /// Main implementation of [`MailRenderer`].
 struct MailRendererImpl<'a> {
     host: &'a str,
@@ -135,8 +165,8 @@ impl<'a> MailRenderer for MailRendererImpl<'a> {
                 Dein Amelio-Team",
                 name = name,
                 title = details.ticket_title,
-                old = details.old_status.german(),
-                new = details.new_status.german(),
+                old = details.old_status,
+                new = details.new_status,
                 host = self.host,
                 id = details.ticket_id
             ),
@@ -162,15 +192,195 @@ impl<'a> MailRenderer for MailRendererImpl<'a> {
                 name = name,
                 title = details.ticket_title,
                 writer = details.writer_name,
+                comment = markdown::to_plain_text(details.comment),
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
+
+    fn mention(&self, name: &str, details: CommentDetails<'_>) -> (&str, String) {
+        (
+            "Du wurdest in einem Ticket erw\u{00e4}hnt",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                {writer} hat Dich soeben in einem Kommentar zum Ticket \"{title}\" \
+                erw\u{00e4}hnt:\n\
+                \n\
+                {comment}\n\
+                \n\
+                Du kannst das Ticket jederzeit unter folgendem Link einsehen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                writer = details.writer_name,
+                comment = markdown::to_plain_text(details.comment),
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
+
+    fn overdue(&self, name: &str, details: OverdueDetails<'_>) -> (&str, String) {
+        (
+            "\u{00dc}berf\u{00e4}lliges Ticket",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                Das Ticket \"{title}\" hat die vereinbarten Fristen \u{00fc}berschritten und \
+                ben\u{00f6}tigt dringend Aufmerksamkeit. {assignee}\n\
+                \n\
+                Du kannst das Ticket jederzeit unter folgendem Link einsehen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                assignee = details.assignee_name.map_or_else(
+                    || "Es ist noch niemandem zugewiesen.".to_owned(),
+                    |assignee| format!("Es ist aktuell {} zugewiesen.", assignee)
+                ),
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
+
+    fn assignment(&self, name: &str, details: AssignmentDetails<'_>) -> (&str, String) {
+        (
+            "Neues Ticket f\u{00fc}r Dich",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                Dir wurde soeben das Ticket \"{title}\" von {assigner} zugewiesen.\n\
+                \n\
+                Du kannst das Ticket jederzeit unter folgendem Link einsehen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                assigner = details.assigner_name,
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
+
+    fn reopen_request(&self, name: &str, details: CommentDetails<'_>) -> (&str, String) {
+        (
+            "Wiederer\u{00f6}ffnung eines Tickets beantragt",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                {writer} hat soeben die Wiederer\u{00f6}ffnung des Tickets \"{title}\" mit \
+                folgender Begr\u{00fc}ndung beantragt:\n\
+                \n\
+                {comment}\n\
+                \n\
+                Du kannst den Antrag unter folgendem Link annehmen oder ablehnen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                writer = details.writer_name,
+                comment = details.comment,
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
+
+    fn reopen_refused(&self, name: &str, details: CommentDetails<'_>) -> (&str, String) {
+        (
+            "Wiederer\u{00f6}ffnung Deines Tickets abgelehnt",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                {writer} hat soeben die Wiederer\u{00f6}ffnung Deines Tickets \"{title}\" mit \
+                folgender Begr\u{00fc}ndung abgelehnt:\n\
+                \n\
+                {comment}\n\
+                \n\
+                Du kannst dein Ticket jederzeit unter folgendem Link einsehen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                writer = details.writer_name,
                 comment = details.comment,
                 host = self.host,
                 id = details.ticket_id,
             ),
         )
     }
+
+    fn returned(&self, name: &str, details: CommentDetails<'_>) -> (&str, String) {
+        (
+            "Ticket an Dich zur\u{00fc}ckgegeben",
+            format!(
+                "Hallo {name},\n\
+                \n\
+                {writer} hat Dir soeben das Ticket \"{title}\" mit folgendem Kommentar \
+                zur\u{00fc}ckgegeben:\n\
+                \n\
+                {comment}\n\
+                \n\
+                Du kannst das Ticket jederzeit unter folgendem Link einsehen:\n\
+                {host}/tickets/{id}\n\
+                \n\
+                Viele Gr\u{00fc}\u{00df}e,\n\
+                Dein Amelio-Team",
+                name = name,
+                title = details.ticket_title,
+                writer = details.writer_name,
+                comment = markdown::to_plain_text(details.comment),
+                host = self.host,
+                id = details.ticket_id,
+            ),
+        )
+    }
 }
 
 /// Create a new mail renderer.
 pub fn new_mail_renderer(host: &str) -> impl MailRenderer + '_ {
     MailRendererImpl { host }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+
+    use super::{Mail, MailSender};
+
+    /// Mail sender that keeps the recipients of all mails instead of sending them.
+    #[derive(Clone, Default)]
+    pub struct RecordingSender(Rc<RefCell<Vec<String>>>);
+
+    impl RecordingSender {
+        /// Addresses of all recipients, in the order the mails were sent.
+        pub fn recipients(&self) -> Vec<String> {
+            self.0.borrow().clone()
+        }
+    }
+
+    impl MailSender for RecordingSender {
+        fn send(&self, mail: Mail<'_>) -> Result<()> {
+            self.0.borrow_mut().push(mail.to.0.to_owned());
+            Ok(())
+        }
+    }
+}
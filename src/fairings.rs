@@ -2,12 +2,21 @@
 
 #![cfg_attr(doc, allow(unused_braces))]
 
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::Method;
-use rocket::{uri, Data, Request, Response};
+use rocket::{uri, Data, Request, Response, Rocket};
 
+use crate::config::{Config, SlaConfig, SmtpConfig};
+use crate::db::connection::DbPool;
+use crate::db::repositories;
 use crate::roles::{AdminUser, AuthUser, StudentUser};
-use crate::routes;
+use crate::services::{self, TicketService};
+use crate::{email, routes};
 
 const CSP_HEADER_NAME: &str = "Content-Security-Policy";
 
@@ -32,6 +41,7 @@ impl Fairing for Csp {
             CSP_HEADER_NAME,
             "\
             default-src 'none'; \
+            connect-src 'self'; \
             img-src 'self'; \
             script-src 'self' https://cdn.jsdelivr.net; \
             style-src 'unsafe-inline' https://cdn.jsdelivr.net; \
@@ -45,8 +55,8 @@ impl Fairing for Csp {
     }
 }
 
-const ADMIN_AUTH_PATHS: &[&str] = &["users", "courses"];
-const STUDENT_AUTH_PATHS: &[&str] = &["tickets"];
+const ADMIN_AUTH_PATHS: &[&str] = &["users", "courses", "workflow", "rules", "labels", "intake"];
+const STUDENT_AUTH_PATHS: &[&str] = &["tickets", "programs", "reports"];
 
 macro_rules! check_rules {
     ($name:ident, $t:ty, $path:ident) => {
@@ -107,6 +117,60 @@ impl Fairing for Auth {
     }
 }
 
+/// A fairing that starts a background thread on launch, which regularly escalates all tickets
+/// that missed their due dates. This way no external scheduler is needed.
+pub struct Escalation;
+
+impl Escalation {
+    /// Run a single check for overdue tickets.
+    fn run(pool: &DbPool, smtp: &SmtpConfig, host: &str, sla: &SlaConfig) -> Result<()> {
+        let conn = pool.get()?;
+        let service = services::ticket_service(
+            repositories::ticket_repo(&conn),
+            repositories::workflow_repo(&conn),
+            repositories::course_repo(&conn),
+            repositories::user_repo(&conn),
+            email::new_smtp_sender(smtp),
+            email::new_mail_renderer(host),
+        );
+
+        service.escalate_overdue(sla)
+    }
+}
+
+impl Fairing for Escalation {
+    fn info(&self) -> Info {
+        Info {
+            name: "Ticket Escalation",
+            kind: Kind::Launch,
+        }
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        let (pool, config) = if let (Some(pool), Some(config)) =
+            (DbPool::from_rocket(rocket), rocket.state::<Config>())
+        {
+            (pool, config)
+        } else {
+            error!("database pool or configuration missing, ticket escalation disabled");
+            return;
+        };
+
+        let smtp = config.smtp.clone();
+        let host = config.host.clone();
+        let sla = config.tickets.sla.clone();
+        let interval = Duration::from_secs(sla.check_interval.max(1) * 60);
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            if let Err(e) = Self::run(&pool, &smtp, &host, &sla) {
+                error!("error during ticket escalation: {:?}", e);
+            }
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -1,6 +1,8 @@
 //! Language (mostly German) specific functionality.
 
-use crate::models::{Category, Priority, Role, Status, TicketType};
+use crate::models::{
+    BulkError, Category, LinkType, MediumType, Priority, Role, StatusColor, TicketType,
+};
 
 /// The translate trait allows for any implementing object to translate itself or its value into
 /// different languages.
@@ -37,6 +39,17 @@ impl Translate for TicketType {
     }
 }
 
+impl Translate for MediumType {
+    fn german(&self) -> &'static str {
+        match self {
+            Self::Text => "Text",
+            Self::Recording => "Aufzeichnung",
+            Self::Interactive => "Interaktiv",
+            Self::Questionaire => "Fragebogen",
+        }
+    }
+}
+
 impl Translate for Category {
     fn german(&self) -> &'static str {
         match self {
@@ -59,14 +72,45 @@ impl Translate for Priority {
     }
 }
 
-impl Translate for Status {
+impl Translate for LinkType {
+    fn german(&self) -> &'static str {
+        match self {
+            Self::RelatesTo => "Steht in Beziehung zu",
+            Self::Duplicates => "Dupliziert",
+            Self::DuplicatedBy => "Wird dupliziert von",
+            Self::Blocks => "Blockiert",
+            Self::BlockedBy => "Wird blockiert von",
+            Self::FollowsUp => "Folgt auf",
+            Self::FollowedUpBy => "Wird fortgesetzt durch",
+        }
+    }
+}
+
+impl Translate for StatusColor {
+    fn german(&self) -> &'static str {
+        match self {
+            Self::Primary => "T\u{00fc}rkis",
+            Self::Link => "Blau",
+            Self::Info => "Hellblau",
+            Self::Success => "Gr\u{00fc}n",
+            Self::Warning => "Gelb",
+            Self::Danger => "Rot",
+            Self::Light => "Hellgrau",
+            Self::Dark => "Dunkelgrau",
+        }
+    }
+}
+
+impl Translate for BulkError {
     fn german(&self) -> &'static str {
         match self {
-            Self::Open => "Offen",
-            Self::InProgress => "In Bearbeitung",
-            Self::Accepted => "Akzeptiert",
-            Self::Refused => "Abgelehnt",
-            Self::Completed => "Abgeschlossen",
+            Self::NotFound => "Ticket existiert nicht",
+            Self::Closed => "Ticket ist bereits abgeschlossen",
+            Self::Transition => "Status\u{00e4}nderung ist nicht erlaubt",
+            Self::Forwarded => "Ticket wurde bereits weitergeleitet",
+            Self::Assignee => "Benutzer kann keine Tickets bearbeiten",
+            Self::Label => "Label ist im Kurs nicht verf\u{00fc}gbar",
+            Self::Internal => "Unerwarteter Fehler",
         }
     }
 }
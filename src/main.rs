@@ -35,13 +35,16 @@ mod email;
 mod fairings;
 mod hashing;
 mod language;
+mod markdown;
 mod models;
 mod roles;
 mod routes;
 mod services;
+mod storage;
 mod templates;
 
 /// Create a new pre-configured [`Rocket`] instance.
+#[allow(clippy::too_many_lines)]
 fn rocket() -> Result<Rocket> {
     let (rocket_config, config) = config::load()?;
 
@@ -50,6 +53,7 @@ fn rocket() -> Result<Rocket> {
         .attach(DbMigrations::fairing())
         .attach(fairings::Csp)
         .attach(fairings::Auth)
+        .attach(fairings::Escalation)
         .manage(config)
         .mount(
             "/",
@@ -91,6 +95,66 @@ fn rocket() -> Result<Rocket> {
                 routes::courses::post_edit,
             ],
         )
+        .mount(
+            "/programs",
+            routes![
+                routes::programs::list,
+                routes::programs::list_managed,
+                routes::programs::list_forbidden,
+                routes::programs::post_new_faculty,
+                routes::programs::new,
+                routes::programs::post_new,
+                routes::programs::edit,
+                routes::programs::post_edit,
+            ],
+        )
+        .mount(
+            "/workflow",
+            routes![
+                routes::workflow::list,
+                routes::workflow::post_new_status,
+                routes::workflow::post_edit_status,
+                routes::workflow::post_new_transition,
+                routes::workflow::post_delete_transition,
+            ],
+        )
+        .mount(
+            "/rules",
+            routes![
+                routes::rules::list,
+                routes::rules::post_new,
+                routes::rules::post_delete,
+                routes::rules::test,
+            ],
+        )
+        .mount(
+            "/labels",
+            routes![
+                routes::labels::list,
+                routes::labels::post_new,
+                routes::labels::post_delete,
+            ],
+        )
+        .mount(
+            "/intake",
+            routes![routes::intake::list, routes::intake::post_update],
+        )
+        .mount(
+            "/report",
+            routes![
+                routes::reports::index,
+                routes::reports::new,
+                routes::reports::post_new,
+            ],
+        )
+        .mount(
+            "/reports",
+            routes![
+                routes::reports::list,
+                routes::reports::post_approve,
+                routes::reports::post_reject,
+            ],
+        )
         .mount(
             "/tickets",
             routes![
@@ -98,9 +162,36 @@ fn rocket() -> Result<Rocket> {
                 routes::tickets::post_new,
                 routes::tickets::edit,
                 routes::tickets::post_edit,
+                routes::tickets::post_edit_content,
                 routes::tickets::post_add_comment,
+                routes::tickets::preview,
+                routes::tickets::post_edit_comment,
+                routes::tickets::post_delete_comment,
+                routes::tickets::attachment,
+                routes::tickets::attachment_thumbnail,
                 routes::tickets::forward,
+                routes::tickets::post_return,
+                routes::tickets::move_ticket,
+                routes::tickets::post_move,
+                routes::tickets::post_assign,
+                routes::tickets::post_labels,
+                routes::tickets::post_bulk,
+                routes::tickets::assign_me,
+                routes::tickets::unassigned,
                 routes::tickets::change_status,
+                routes::tickets::post_reopen,
+                routes::tickets::post_reopen_request,
+                routes::tickets::post_accept_reopen,
+                routes::tickets::post_refuse_reopen,
+                routes::tickets::post_duplicate,
+                routes::tickets::similar,
+                routes::tickets::post_me_too,
+                routes::tickets::post_watch,
+                routes::tickets::post_unwatch,
+                routes::tickets::post_link,
+                routes::tickets::post_delete_link,
+                routes::tickets::lookup,
+                routes::tickets::mentions,
                 routes::tickets::search,
             ],
         )
@@ -132,19 +223,19 @@ mod tests {
 
     pub fn prepare_logged_in_client(username: &str, password: &str) -> Client {
         let client = Client::new(crate::rocket().unwrap()).unwrap();
+        login(&client, username, password);
+        client
+    }
 
-        {
-            let res = client
-                .post(uri!(routes::auth::login).to_string())
-                .body(format!("username={}&password={}", username, password))
-                .header(ContentType::Form)
-                .dispatch();
-
-            assert_eq!(Status::SeeOther, res.status());
-            assert_eq!(Some("/"), res.headers().get_one("Location"));
-        }
+    pub fn login(client: &Client, username: &str, password: &str) {
+        let res = client
+            .post(uri!(routes::auth::login).to_string())
+            .body(format!("username={}&password={}", username, password))
+            .header(ContentType::Form)
+            .dispatch();
 
-        client
+        assert_eq!(Status::SeeOther, res.status());
+        assert_eq!(Some("/"), res.headers().get_one("Location"));
     }
 
     pub fn check_form<'a, B: AsRef<str>>(
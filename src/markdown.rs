@@ -0,0 +1,342 @@
+//! Rendering of user written content, like ticket descriptions and comments, that is formatted
+//! with [`CommonMark`](https://commonmark.org).
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag};
+
+/// Parser options for all rendered content. Only few extensions beyond plain `CommonMark` are
+/// enabled, to keep the output simple.
+fn options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+}
+
+/// Render the input as HTML and sanitize it afterwards, so it can be safely embedded in any page.
+///
+/// Raw HTML within the input is kept as long as the sanitizer allows it, but any scripts, styles
+/// or event handlers are removed. Images from other hosts are still rendered as `<img>` elements
+/// but never loaded, as the [`Csp`](crate::fairings::Csp) only allows images from our own host.
+pub fn to_html(input: &str) -> String {
+    render_html(input, Parser::new_ext(input, options()))
+}
+
+/// Same as [`to_html`], but additionally turns `@username` mentions of the given users into links.
+/// The users are given as pairs of username and full name.
+pub fn to_html_with_mentions(input: &str, users: &[(String, String)]) -> String {
+    let mut events = Vec::new();
+    let mut plain = PlainTracker::default();
+
+    for event in parse_merged(input) {
+        plain.track(&event);
+
+        match event {
+            Event::Text(text) if plain.is_plain() => {
+                let mut last = 0;
+
+                for (start, end) in mention_ranges(&text) {
+                    let username = &text[start + 1..end];
+                    let user = users.iter().find(|(u, _)| u == username);
+
+                    if let Some((username, name)) = user {
+                        if start > last {
+                            events.push(Event::Text(text[last..start].to_owned().into()));
+                        }
+                        let url = CowStr::from(format!("mailto:{}@iubh-fernstudium.de", username));
+                        let title = CowStr::from(name.clone());
+                        events.push(Event::Start(Tag::Link(
+                            LinkType::Inline,
+                            url.clone(),
+                            title.clone(),
+                        )));
+                        events.push(Event::Text(format!("@{}", username).into()));
+                        events.push(Event::End(Tag::Link(LinkType::Inline, url, title)));
+                        last = end;
+                    }
+                }
+
+                if last < text.len() {
+                    events.push(Event::Text(text[last..].to_owned().into()));
+                }
+            }
+            event => events.push(event),
+        }
+    }
+
+    render_html(input, events.into_iter())
+}
+
+/// Find the usernames of all `@username` mentions in the text. Mentions within code or links are
+/// ignored, as well as anything that looks like an email address.
+pub fn mentions(input: &str) -> Vec<String> {
+    let mut usernames = Vec::<String>::new();
+    let mut plain = PlainTracker::default();
+
+    for event in parse_merged(input) {
+        plain.track(&event);
+
+        if let Event::Text(text) = event {
+            if plain.is_plain() {
+                for (start, end) in mention_ranges(&text) {
+                    let username = &text[start + 1..end];
+                    if !usernames.iter().any(|u| u == username) {
+                        usernames.push(username.to_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    usernames
+}
+
+/// Parse the input into events, merging adjacent text events. The parser splits text at any
+/// character that might start some formatting, which could cut a mention in half otherwise.
+fn parse_merged(input: &str) -> Vec<Event<'_>> {
+    let mut events = Vec::new();
+
+    for event in Parser::new_ext(input, options()) {
+        if let (Some(Event::Text(prev)), Event::Text(text)) = (events.last_mut(), &event) {
+            *prev = format!("{}{}", prev, text).into();
+            continue;
+        }
+
+        events.push(event);
+    }
+
+    events
+}
+
+/// Locate all mentions within a piece of text, as byte ranges that include the leading `@`.
+fn mention_ranges(text: &str) -> Vec<(usize, usize)> {
+    let is_username_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let mut ranges = Vec::new();
+    let mut prev = None;
+
+    for (start, c) in text.char_indices() {
+        // An `@` directly after a word belongs to an email address instead.
+        if c == '@' && !prev.map_or(false, is_username_char) {
+            let rest = &text[start + 1..];
+            let len = rest.find(|c| !is_username_char(c)).unwrap_or(rest.len());
+            // Dots and dashes at the end rather belong to the surrounding sentence.
+            let username = rest[..len].trim_end_matches(|c| matches!(c, '.' | '-'));
+
+            if !username.is_empty() {
+                ranges.push((start, start + 1 + username.len()));
+            }
+        }
+
+        prev = Some(c);
+    }
+
+    ranges
+}
+
+/// Keeps track of whether the current position of a Markdown document is plain text, outside of
+/// any code blocks and links.
+#[derive(Default)]
+struct PlainTracker {
+    code: usize,
+    links: usize,
+}
+
+impl PlainTracker {
+    fn track(&mut self, event: &Event<'_>) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => self.code += 1,
+            Event::End(Tag::CodeBlock(_)) => self.code -= 1,
+            Event::Start(Tag::Link(..) | Tag::Image(..)) => self.links += 1,
+            Event::End(Tag::Link(..) | Tag::Image(..)) => self.links -= 1,
+            _ => {}
+        }
+    }
+
+    const fn is_plain(&self) -> bool {
+        self.code == 0 && self.links == 0
+    }
+}
+
+/// Render the parsed Markdown events as sanitized HTML.
+fn render_html<'a>(input: &str, events: impl Iterator<Item = Event<'a>>) -> String {
+    let mut output = String::with_capacity(input.len() * 3 / 2);
+    html::push_html(&mut output, events);
+
+    ammonia::Builder::default()
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(&output)
+        .to_string()
+}
+
+/// Render the input as plain text, for places where no HTML can be shown, like emails.
+///
+/// The formatting is mostly dropped, except for the parts that carry meaning, like list bullets,
+/// quotes and link targets.
+pub fn to_plain_text(input: &str) -> String {
+    let mut writer = PlainTextWriter::default();
+    let mut lists = Vec::new();
+    let mut links = Vec::new();
+
+    for event in Parser::new_ext(input, options()) {
+        match event {
+            Event::Start(Tag::List(start)) => lists.push(start),
+            Event::End(Tag::List(_)) => {
+                lists.pop();
+                writer.end_line(if lists.is_empty() { 2 } else { 1 });
+            }
+            Event::Start(Tag::Item) => {
+                writer.end_line(1);
+                let indent = "  ".repeat(lists.len().saturating_sub(1));
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        writer.write(&format!("{}{}. ", indent, number));
+                        *number += 1;
+                    }
+                    _ => writer.write(&format!("{}- ", indent)),
+                }
+            }
+            Event::Start(Tag::BlockQuote) => {
+                writer.end_line(2);
+                writer.quotes += 1;
+            }
+            Event::End(Tag::BlockQuote) => {
+                writer.quotes -= 1;
+                writer.end_line(2);
+            }
+            Event::Start(Tag::Link(_, url, _) | Tag::Image(_, url, _)) => links.push(url),
+            Event::End(Tag::Link(..) | Tag::Image(..)) => {
+                // Autolinks already show their target as text.
+                if let Some(url) = links.pop() {
+                    if !writer.output.ends_with(&*url) {
+                        writer.write(&format!(" ({})", url));
+                    }
+                }
+            }
+            Event::End(Tag::Paragraph) => writer.end_line(if lists.is_empty() { 2 } else { 1 }),
+            Event::End(Tag::Heading(..) | Tag::CodeBlock(_) | Tag::Table(_)) => writer.end_line(2),
+            Event::End(Tag::TableHead | Tag::TableRow) | Event::SoftBreak | Event::HardBreak => {
+                writer.end_line(1);
+            }
+            Event::End(Tag::TableCell) => writer.write(" | "),
+            Event::Text(text) | Event::Code(text) | Event::Html(text) => writer.text(&text),
+            Event::Rule => {
+                writer.write("---");
+                writer.end_line(2);
+            }
+            _ => {}
+        }
+    }
+
+    writer.output
+}
+
+/// Helper for [`to_plain_text`] that keeps track of line breaks and quote markers.
+#[derive(Default)]
+struct PlainTextWriter {
+    output: String,
+    /// Amount of line breaks to insert before the next written text.
+    pending: usize,
+    /// Current nesting level of block quotes.
+    quotes: usize,
+}
+
+impl PlainTextWriter {
+    /// Request at least `count` line breaks before the next text. Line breaks are only written
+    /// once more text follows, so the output never ends with empty lines.
+    fn end_line(&mut self, count: usize) {
+        self.pending = self.pending.max(count);
+    }
+
+    /// Write a single line of text, prefixed with any pending line breaks and quote markers.
+    fn write(&mut self, text: &str) {
+        if self.output.is_empty() || self.pending > 0 {
+            if !self.output.is_empty() {
+                self.output.push_str(&"\n".repeat(self.pending));
+            }
+            self.output.push_str(&"> ".repeat(self.quotes));
+            self.pending = 0;
+        }
+
+        self.output.push_str(text);
+    }
+
+    /// Write text that may span several lines, keeping empty lines within it.
+    fn text(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.pending += 1;
+            }
+            if !line.is_empty() {
+                self.write(line);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn html_is_sanitized() {
+        let output = to_html(
+            "Hello <script>alert(1)</script> *world*\n\n\
+            <a href=\"javascript:alert(1)\" onclick=\"alert(1)\">link</a>",
+        );
+
+        assert!(output.contains("<em>world</em>"));
+        assert!(!output.contains("script"));
+        assert!(!output.contains("onclick"));
+        assert!(!output.contains("javascript"));
+    }
+
+    #[test]
+    fn code_is_escaped() {
+        let output = to_html("```rust\nlet a = <b>;\n```");
+
+        assert!(output.contains("<pre><code"));
+        assert!(output.contains("let a = &lt;b&gt;;"));
+    }
+
+    #[test]
+    fn mentions_are_found() {
+        assert_eq!(
+            vec!["tutor".to_owned(), "author.one".to_owned()],
+            mentions(
+                "Hey @tutor and @author.one. Mail to mail@example.com, not `@code` or \
+                [@link](https://example.com), but @tutor again."
+            )
+        );
+    }
+
+    #[test]
+    fn mentions_are_linked() {
+        let output = to_html_with_mentions(
+            "Hello @tutor and @unknown",
+            &[("tutor".to_owned(), "Tutor".to_owned())],
+        );
+
+        assert!(output.contains("<a href=\"mailto:tutor@iubh-fernstudium.de\" title=\"Tutor\""));
+        assert!(output.contains(">@tutor</a> and @unknown"));
+    }
+
+    #[test]
+    fn plain_text() {
+        let output = to_plain_text(
+            "# Title\n\n\
+            Some **bold** text with a [link](https://example.com).\n\n\
+            - one\n\
+            - two\n\n\
+            > quoted\n> lines\n\n\
+            `code`",
+        );
+
+        assert_eq!(
+            "Title\n\n\
+            Some bold text with a link (https://example.com).\n\n\
+            - one\n\
+            - two\n\n\
+            > quoted\n> lines\n\n\
+            code",
+            output
+        );
+    }
+}
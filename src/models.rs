@@ -1,7 +1,12 @@
 //! The base models of the system, that [`services`](crate::services) work on.
 
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, NaiveTime, Utc};
-use strum::{AsRefStr, Display, EnumString};
+use strum::{AsRefStr, Display, EnumString, ParseError};
 use url::Url;
 
 /// The identifier type for all models.
@@ -51,6 +56,7 @@ pub struct EditUser {
 }
 
 /// A full course with all available details.
+#[derive(Clone)]
 pub struct Course {
     pub id: Id,
     pub code: String,
@@ -58,6 +64,7 @@ pub struct Course {
     pub author_id: Id,
     pub tutor_id: Id,
     pub active: bool,
+    pub public_reports: bool,
 }
 
 /// A new course to be added to the system.
@@ -74,15 +81,77 @@ pub struct EditCourse {
     pub title: String,
     pub author_id: Id,
     pub tutor_id: Id,
+    pub public_reports: bool,
 }
 
 /// A course with its author and tutor names included.
+#[derive(Clone)]
 pub struct CourseWithNames {
     pub course: Course,
     pub author_name: String,
     pub tutor_name: String,
 }
 
+/// A faculty of the university that groups several study [`Program`]s.
+pub struct Faculty {
+    pub id: Id,
+    pub name: String,
+}
+
+/// A study program that belongs to a [`Faculty`]. Each [`Course`] can be part of one or more
+/// programs.
+pub struct Program {
+    pub id: Id,
+    pub faculty_id: Id,
+    pub code: String,
+    pub title: String,
+}
+
+/// A new program to be added to the system.
+pub struct NewProgram {
+    pub faculty_id: Id,
+    pub code: String,
+    pub title: String,
+    pub course_ids: Vec<Id>,
+    pub manager_ids: Vec<Id>,
+}
+
+/// An existing program to be updated.
+pub struct EditProgram {
+    pub id: Id,
+    pub faculty_id: Id,
+    pub title: String,
+    pub course_ids: Vec<Id>,
+    pub manager_ids: Vec<Id>,
+}
+
+/// A program with its assigned courses and the IDs of its program managers.
+pub struct ProgramWithRels {
+    pub program: Program,
+    pub course_ids: Vec<Id>,
+    pub manager_ids: Vec<Id>,
+}
+
+/// The hierarchy of faculties, programs and courses.
+pub struct CourseTree {
+    pub faculties: Vec<FacultyNode>,
+    /// Courses that don't belong to any program yet.
+    pub unassigned: Vec<CourseWithNames>,
+}
+
+/// A single faculty within the [`CourseTree`].
+pub struct FacultyNode {
+    pub faculty: Faculty,
+    pub programs: Vec<ProgramNode>,
+}
+
+/// A single program within the [`CourseTree`].
+pub struct ProgramNode {
+    pub program: Program,
+    pub manager_names: Vec<String>,
+    pub courses: Vec<CourseWithNames>,
+}
+
 /// Different types of [`Ticket`]s. It also decides what kind of medium is attached to a ticket.
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Display, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -100,6 +169,20 @@ pub enum TicketType {
 }
 
 impl TicketType {
+    /// All available ticket types, in the order they're offered for selection.
+    pub const ALL: [Self; 10] = [
+        Self::CourseBook,
+        Self::ReadingList,
+        Self::InteractiveBook,
+        Self::PracticeExam,
+        Self::PracticeExamSolution,
+        Self::Vodcast,
+        Self::Podcast,
+        Self::Presentation,
+        Self::LiveTutorialRecording,
+        Self::OnlineTest,
+    ];
+
     pub const fn medium(self) -> MediumType {
         match self {
             Self::CourseBook | Self::ReadingList | Self::Presentation => MediumType::Text,
@@ -122,6 +205,16 @@ pub enum MediumType {
     Questionaire,
 }
 
+impl MediumType {
+    /// All available medium types, in the order they're offered for selection.
+    pub const ALL: [Self; 4] = [
+        Self::Text,
+        Self::Recording,
+        Self::Interactive,
+        Self::Questionaire,
+    ];
+}
+
 /// The category allows to group [`Ticket`]s into specific topics.
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Display, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -132,6 +225,16 @@ pub enum Category {
     Addition,
 }
 
+impl Category {
+    /// All available categories, in the order they're offered for selection.
+    pub const ALL: [Self; 4] = [
+        Self::Editorial,
+        Self::Content,
+        Self::Improvement,
+        Self::Addition,
+    ];
+}
+
 /// Different priorities of a [`Ticket`].
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Display, EnumString, AsRefStr)]
 #[strum(serialize_all = "kebab-case")]
@@ -142,37 +245,149 @@ pub enum Priority {
     Low,
 }
 
-/// The current status of a ticket.
-///
-/// - A newly created ticket starts with the [`Status::Open`] state.
-/// - The first time in the [`Status::Open`] state is opened by a tutor (or author), the status
-///   changes to [`Status::InProgress`].
-/// - Once a ticket was reviewed it can either become [`Status::Accepted`] or [`Status::Refused`].
-///   If it was refused, then this is the final status.
-/// - If the ticket is [`Status::Accepted`] it becomes [`Status::Completed`] once the related medium
-///   was is updated. This is the final status.
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Display, EnumString, AsRefStr)]
-#[strum(serialize_all = "kebab-case")]
-pub enum Status {
-    Open,
-    InProgress,
-    Accepted,
-    Refused,
-    Completed,
+impl Priority {
+    /// All available priorities, from the highest to the lowest one.
+    pub const ALL: [Self; 4] = [Self::Critical, Self::High, Self::Medium, Self::Low];
+
+    /// The next higher priority, or the same if it's already the highest one.
+    pub const fn raised(self) -> Self {
+        match self {
+            Self::Critical | Self::High => Self::Critical,
+            Self::Medium => Self::High,
+            Self::Low => Self::Medium,
+        }
+    }
 }
 
+/// The key of a ticket status. All statuses and the allowed transitions between them are defined
+/// by administrators in the [`Workflow`], but a few well-known statuses of the default workflow are
+/// referred to directly.
+///
+/// - A newly created ticket starts with the [`Status::OPEN`] state. This status can't be removed
+///   from the workflow.
+/// - The first time in the [`Status::OPEN`] state is opened by its assignee, the status changes to
+///   [`Status::IN_PROGRESS`], as long as the workflow allows it.
+/// - Tickets marked as duplicate of another ticket are closed with the [`Status::DUPLICATE`] state.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Status(Cow<'static, str>);
+
 impl Status {
-    /// Determine whether the current status can be changed into the `other` status.
-    pub fn can_change(self, other: Self) -> bool {
-        match self {
-            Self::Open => other == Self::InProgress,
-            Self::InProgress => other == Self::Accepted || other == Self::Refused,
-            Self::Accepted => other == Self::Completed,
-            Self::Refused | Self::Completed => false,
+    pub const OPEN: Self = Self(Cow::Borrowed("open"));
+    pub const IN_PROGRESS: Self = Self(Cow::Borrowed("in-progress"));
+    pub const ACCEPTED: Self = Self(Cow::Borrowed("accepted"));
+    pub const REFUSED: Self = Self(Cow::Borrowed("refused"));
+    pub const COMPLETED: Self = Self(Cow::Borrowed("completed"));
+    pub const DUPLICATE: Self = Self(Cow::Borrowed("duplicate"));
+}
+
+impl FromStr for Status {
+    type Err = ParseError;
+
+    /// Parse a status key, which must be non-empty and only consist of lowercase ASCII letters,
+    /// digits and dashes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = !s.is_empty()
+            && s.bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+
+        if valid {
+            Ok(Self(Cow::Owned(s.to_owned())))
+        } else {
+            Err(ParseError::VariantNotFound)
         }
     }
 }
 
+impl AsRef<str> for Status {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Colors that can be assigned to a [`WorkflowStatus`] or a [`Label`].
+#[derive(Copy, Clone, Eq, PartialEq, Display, EnumString, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum StatusColor {
+    Primary,
+    Link,
+    Info,
+    Success,
+    Warning,
+    Danger,
+    Light,
+    Dark,
+}
+
+impl StatusColor {
+    /// All available colors, in the order they're offered for selection.
+    pub const ALL: [Self; 8] = [
+        Self::Primary,
+        Self::Link,
+        Self::Info,
+        Self::Success,
+        Self::Warning,
+        Self::Danger,
+        Self::Light,
+        Self::Dark,
+    ];
+}
+
+/// A single status of the [`Workflow`] together with its display details.
+#[derive(Clone)]
+pub struct WorkflowStatus {
+    pub key: Status,
+    pub name: String,
+    pub color: StatusColor,
+    /// Name of the icon, for example `fa-envelope`.
+    pub icon: String,
+    /// Whether this is a final status. Tickets in a terminal status can't be edited anymore.
+    pub terminal: bool,
+}
+
+/// An allowed change from one status to another, that can be performed by a specific role.
+pub struct Transition {
+    pub from: Status,
+    pub to: Status,
+    pub role: Role,
+}
+
+/// The workflow defines the lifecycle of all tickets as a state machine, by listing all available
+/// statuses and the transitions between them.
+pub struct Workflow {
+    pub statuses: Vec<WorkflowStatus>,
+    pub transitions: Vec<Transition>,
+}
+
+impl Workflow {
+    /// Find the full status for the given key.
+    pub fn status(&self, key: &Status) -> Option<&WorkflowStatus> {
+        self.statuses.iter().find(|s| s.key == *key)
+    }
+
+    /// Determine whether the `from` status can be changed into the `to` status by a user with the
+    /// given role.
+    pub fn can_change(&self, from: &Status, to: &Status, role: Role) -> bool {
+        self.transitions
+            .iter()
+            .any(|t| t.from == *from && t.to == *to && t.role == role)
+    }
+
+    /// List all statuses that the `from` status can be changed into by the given role.
+    pub fn next(&self, from: &Status, role: Role) -> Vec<WorkflowStatus> {
+        self.statuses
+            .iter()
+            .filter(|s| self.can_change(from, &s.key, role))
+            .cloned()
+            .collect()
+    }
+}
+
 /// A full ticket with all available details.
 pub struct Ticket {
     pub id: Id,
@@ -185,57 +400,133 @@ pub struct Ticket {
     pub forwarded: bool,
     pub course_id: Id,
     pub creator_id: Id,
+    pub assignee_id: Option<Id>,
+    /// Point in time when the ticket entered a terminal status, if it's currently in one.
+    pub closed_at: Option<DateTime<Utc>>,
+    /// How often the ticket was reopened after being closed.
+    pub reopen_count: u32,
+    /// The primary ticket, if this ticket was marked as a duplicate of it.
+    pub duplicate_of: Option<Id>,
+    /// How many other users reported to have the same issue.
+    pub votes: u32,
+    /// Point in time when the ticket was created.
+    pub created_at: DateTime<Utc>,
+    /// Point in time when a staff member first answered the ticket with a comment.
+    pub first_response_at: Option<DateTime<Utc>>,
+    /// How far the ticket was escalated for being overdue. `0` if it wasn't escalated yet, `1`
+    /// after the assignee was notified and `2` after the course author and admins were notified.
+    pub escalation_level: u32,
+    /// Point in time of the last escalation, if there was any.
+    pub escalated_at: Option<DateTime<Utc>>,
 }
 
-impl Ticket {
-    /// Check whether the ticket is editable, based on its status.
-    pub fn is_editable(&self) -> bool {
-        self.status == Status::Open
-            || self.status == Status::InProgress
-            || self.status == Status::Accepted
-    }
-
-    /// Whether a ticket can be changed into [`Status::Accepted`].
-    pub fn can_accept(&self) -> bool {
-        self.is_editable() && self.status == Status::Open || self.status == Status::InProgress
-    }
+/// Points in time until when a ticket should be answered and resolved, based on the service level
+/// targets for its priority.
+pub struct DueDates {
+    /// Due date for the first response, or [`None`] if the ticket was already answered or closed.
+    pub response: Option<DateTime<Utc>>,
+    /// Due date for the resolution, or [`None`] if the ticket is already closed.
+    pub resolution: Option<DateTime<Utc>>,
+}
 
-    /// Whether a ticket can be changed into [`Status::Refused`].
-    pub fn can_refuse(&self) -> bool {
-        self.is_editable() && self.status == Status::Open || self.status == Status::InProgress
-    }
+impl DueDates {
+    /// Check whether any of the due dates already passed.
+    pub fn is_overdue(&self) -> bool {
+        let now = Utc::now();
 
-    /// Whether a ticket can be changed into [`Status::Completed`].
-    pub fn can_complete(&self) -> bool {
-        self.is_editable() && self.status == Status::Accepted
-    }
-
-    /// Whether a ticket can be forwarded to the course's author.
-    pub fn can_forward(&self) -> bool {
-        self.is_editable() && !self.forwarded
+        self.response.map_or(false, |due| due < now)
+            || self.resolution.map_or(false, |due| due < now)
     }
 }
 
-/// A ticket with its course and creator names included.
+/// A ticket with its course, creator and assignee names included, as well as the details of its
+/// current status.
 pub struct TicketWithNames {
     pub ticket: Ticket,
+    pub status: WorkflowStatus,
     pub course_name: String,
     pub creator_name: String,
-    pub editor_name: String,
+    pub assignee_name: Option<String>,
+    pub labels: Vec<Label>,
 }
 
 /// A ticket with the same information as [`TicketWithNames`] plus the related medium.
 pub struct TicketWithRels {
     pub ticket: Ticket,
+    pub status: WorkflowStatus,
     pub course_name: String,
     pub creator_name: String,
-    pub editor_name: String,
+    pub assignee_name: Option<String>,
+    pub labels: Vec<Label>,
     pub medium: Medium,
-    pub comments: Vec<CommentWithNames>,
+    pub timeline: Vec<TimelineEntry>,
+    pub attachments: Vec<Attachment>,
+    /// IDs of all tickets that were marked as duplicates of this ticket.
+    pub duplicates: Vec<Id>,
+    /// Links to other tickets, in both directions.
+    pub links: Vec<LinkedTicket>,
+    /// Answers to the extra fields of the intake template, at the time of creation.
+    pub fields: Vec<TicketField>,
+}
+
+impl TicketWithRels {
+    /// Check whether the ticket is editable, based on its status.
+    pub const fn is_editable(&self) -> bool {
+        !self.status.terminal
+    }
+
+    /// Whether a ticket can be forwarded to the course's author.
+    pub const fn can_forward(&self) -> bool {
+        self.is_editable() && !self.ticket.forwarded
+    }
+
+    /// Whether a forwarded ticket can be returned to the course's tutor by the given user. Only
+    /// the author it was forwarded to and admins can hand it back.
+    pub fn can_return(&self, user_id: Id, role: Role) -> bool {
+        self.is_editable()
+            && self.ticket.forwarded
+            && (role == Role::Admin || self.ticket.assignee_id == Some(user_id))
+    }
+
+    /// IDs of all labels that are assigned to the ticket.
+    pub fn label_ids(&self) -> Vec<Id> {
+        self.labels.iter().map(|label| label.id).collect()
+    }
+
+    /// Remove all internal comments and their attachments, for users that aren't allowed to see
+    /// them.
+    pub fn hide_internal(&mut self) {
+        let internal = self
+            .timeline
+            .iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Comment(c) if c.comment.internal => Some(c.comment.id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        self.timeline.retain(|entry| match entry {
+            TimelineEntry::Comment(c) => !c.comment.internal,
+            TimelineEntry::Event(_) => true,
+        });
+        self.attachments.retain(|a| {
+            a.comment_id
+                .map_or(true, |comment_id| !internal.contains(&comment_id))
+        });
+    }
+
+    /// Attachments of the ticket itself, excluding the ones of its comments.
+    pub fn ticket_attachments(&self) -> Vec<&Attachment> {
+        self.attachments
+            .iter()
+            .filter(|a| a.comment_id.is_none())
+            .collect()
+    }
 }
 
 /// A medium contains additional information to locate content for a [`Ticket`]. The specific type
-/// depends on the [`TicketType`].
+/// depends on the [`TicketType`]. A ticket can point to several locations within its medium, which
+/// are kept in the order of their start.
 pub enum Medium {
     /// A medium which describes locations in text based content.
     ///
@@ -243,19 +534,25 @@ pub enum Medium {
     /// - [`TicketType::CourseBook`]
     /// - [`TicketType::ReadingList`]
     /// - [`TicketType::Presentation`]
-    Text { ticket_id: Id, page: u16, line: u16 },
+    Text {
+        ticket_id: Id,
+        locations: Vec<Span<TextPosition>>,
+    },
     /// A medium which describes locations in recorded content like videos.
     ///
     /// This is the content for:
     /// - [`TicketType::Vodcast`]
     /// - [`TicketType::Podcast`]
     /// - [`TicketType::LiveTutorialRecording`]
-    Recording { ticket_id: Id, time: NaiveTime },
+    Recording {
+        ticket_id: Id,
+        locations: Vec<Span<NaiveTime>>,
+    },
     /// A medium which describes locations in interactive content like websites.
     ///
     /// This is the content for:
     /// - [`TicketType::InteractiveBook`]
-    Interactive { ticket_id: Id, url: Url },
+    Interactive { ticket_id: Id, urls: Vec<Url> },
     /// A medium which describes locations in question-answer structured content like tests.
     ///
     /// This is the content for:
@@ -264,11 +561,123 @@ pub enum Medium {
     /// - [`TicketType::OnlineTest`]
     Questionaire {
         ticket_id: Id,
-        question: u16,
-        answer: String,
+        locations: Vec<QuestionLocation>,
     },
 }
 
+impl Medium {
+    /// The ID of the ticket that this medium belongs to.
+    pub const fn ticket_id(&self) -> Id {
+        match *self {
+            Self::Text { ticket_id, .. }
+            | Self::Recording { ticket_id, .. }
+            | Self::Interactive { ticket_id, .. }
+            | Self::Questionaire { ticket_id, .. } => ticket_id,
+        }
+    }
+
+    /// The type of medium this is.
+    pub const fn medium_type(&self) -> MediumType {
+        match self {
+            Self::Text { .. } => MediumType::Text,
+            Self::Recording { .. } => MediumType::Recording,
+            Self::Interactive { .. } => MediumType::Interactive,
+            Self::Questionaire { .. } => MediumType::Questionaire,
+        }
+    }
+
+    /// Compare the first locations of two media, to sort tickets by where their issue is located.
+    /// Media of different types are ordered by their type, and media without any location last.
+    pub fn cmp_location(&self, other: &Self) -> Ordering {
+        fn first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        match (self, other) {
+            (Self::Text { locations: a, .. }, Self::Text { locations: b, .. }) => {
+                first(a.first(), b.first())
+            }
+            (Self::Recording { locations: a, .. }, Self::Recording { locations: b, .. }) => {
+                first(a.first(), b.first())
+            }
+            (Self::Interactive { urls: a, .. }, Self::Interactive { urls: b, .. }) => {
+                first(a.first().map(Url::as_str), b.first().map(Url::as_str))
+            }
+            (Self::Questionaire { locations: a, .. }, Self::Questionaire { locations: b, .. }) => {
+                first(
+                    a.first().map(|l| l.questions),
+                    b.first().map(|l| l.questions),
+                )
+            }
+            _ => self.medium_type().cmp(&other.medium_type()),
+        }
+    }
+}
+
+impl fmt::Display for Medium {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NewMedium::from(self).fmt(f)
+    }
+}
+
+/// A single location or an inclusive range of locations within a medium.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Span<T> {
+    pub start: T,
+    /// The last location of a range, or `None` if the span is a single location.
+    pub end: Option<T>,
+}
+
+impl<T: Copy + Ord> Span<T> {
+    /// Create a new span, failing if the end lies before the start. An end that equals the start
+    /// is dropped, as the span describes a single location then.
+    pub fn new(start: T, end: Option<T>) -> Option<Self> {
+        match end {
+            Some(end) if end < start => None,
+            Some(end) if end == start => Some(Self { start, end: None }),
+            end => Some(Self { start, end }),
+        }
+    }
+
+    /// The last location that is covered by the span.
+    pub fn last(&self) -> T {
+        self.end.unwrap_or(self.start)
+    }
+
+    /// The two locations where this and the other span are closest to each other, which are the
+    /// same location if the spans overlap.
+    pub fn closest(&self, other: &Self) -> (T, T) {
+        if self.last() < other.start {
+            (self.last(), other.start)
+        } else if other.last() < self.start {
+            (self.start, other.last())
+        } else {
+            let overlap = self.start.max(other.start);
+            (overlap, overlap)
+        }
+    }
+}
+
+/// A position within text based content, ordered by page first and then by line.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TextPosition {
+    pub page: u16,
+    pub line: u16,
+}
+
+/// A location within question-answer structured content, covering one or more questions that
+/// share the same answer.
+#[derive(Clone)]
+pub struct QuestionLocation {
+    pub questions: Span<u16>,
+    pub answer: String,
+}
+
 /// A new ticket to be added to the system.
 pub struct NewTicket {
     pub type_: TicketType,
@@ -277,6 +686,130 @@ pub struct NewTicket {
     pub category: Category,
     pub course_id: Id,
     pub creator_id: Id,
+    /// Answers to the extra fields of the ticket type's [`TicketTemplate`].
+    pub fields: Vec<TicketField>,
+}
+
+/// A report that was sent through the public report form of a course by someone without an
+/// account. It has to be approved by the course's staff before it becomes a regular [`Ticket`].
+pub struct PublicReport {
+    pub id: Id,
+    pub course_id: Id,
+    pub type_: TicketType,
+    pub category: Category,
+    pub title: String,
+    pub description: String,
+    pub medium: NewMedium,
+    /// Answers to the extra fields of the ticket type's intake template.
+    pub fields: Vec<TicketField>,
+    /// Optional email address of the reporter, in case there are questions.
+    pub contact_email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PublicReport {
+    /// Label of the ticket field that holds the reporter's contact email after approval.
+    pub const CONTACT_EMAIL_LABEL: &'static str = "Kontakt-E-Mail";
+
+    /// Turn the report into a new ticket on behalf of the given creator, which is the staff member
+    /// that approved it.
+    pub fn into_ticket(self, creator_id: Id) -> (NewTicket, NewMedium) {
+        let fields = self
+            .fields
+            .into_iter()
+            .chain(self.contact_email.map(|value| TicketField {
+                label: Self::CONTACT_EMAIL_LABEL.to_owned(),
+                value,
+            }))
+            .collect();
+
+        (
+            NewTicket {
+                type_: self.type_,
+                title: self.title,
+                description: self.description,
+                category: self.category,
+                course_id: self.course_id,
+                creator_id,
+                fields,
+            },
+            self.medium,
+        )
+    }
+}
+
+/// A new public report to be added to the system.
+pub struct NewPublicReport {
+    pub course_id: Id,
+    pub type_: TicketType,
+    pub category: Category,
+    pub title: String,
+    pub description: String,
+    pub medium: NewMedium,
+    pub fields: Vec<TicketField>,
+    pub contact_email: Option<String>,
+    /// Address the report was sent from, only kept to limit the amount of reports per sender.
+    pub ip_address: String,
+}
+
+/// A public report with the name of its course included.
+pub struct PublicReportWithNames {
+    pub report: PublicReport,
+    pub course_name: String,
+}
+
+/// An intake template for a [`TicketType`], asking users for the information that is usually
+/// needed to handle this kind of ticket.
+pub struct TicketTemplate {
+    pub type_: TicketType,
+    /// Initial content of the description, for example a structure to follow.
+    pub description: String,
+    /// Explanation shown above the ticket creation form.
+    pub help: String,
+    /// Labels of extra fields that must be filled in, in the order they're shown.
+    pub fields: Vec<String>,
+}
+
+impl TicketTemplate {
+    /// Create an empty template, which is used for ticket types that don't have one yet.
+    pub const fn empty(type_: TicketType) -> Self {
+        Self {
+            type_,
+            description: String::new(),
+            help: String::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Match the submitted values against the template's fields, which are identified by their
+    /// position. Returns [`None`] if any field is left empty or a value doesn't belong to a field.
+    pub fn fill(&self, mut values: Vec<(usize, String)>) -> Option<Vec<TicketField>> {
+        values.sort_unstable_by_key(|(position, _)| *position);
+
+        if values.len() != self.fields.len() {
+            return None;
+        }
+
+        self.fields
+            .iter()
+            .zip(values)
+            .enumerate()
+            .map(|(i, (label, (position, value)))| {
+                let value = value.trim();
+                (i == position && !value.is_empty()).then(|| TicketField {
+                    label: label.clone(),
+                    value: value.to_owned(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The answer to one of the extra fields of a [`TicketTemplate`]. The label is stored together
+/// with the value, so later changes to the template don't alter existing tickets.
+pub struct TicketField {
+    pub label: String,
+    pub value: String,
 }
 
 /// An existing ticket to be updated.
@@ -285,12 +818,124 @@ pub struct EditTicket {
     pub priority: Priority,
 }
 
+/// An existing ticket to be updated by its creator or staff, changing its content.
+#[derive(Clone)]
+pub struct EditTicketContent {
+    pub id: Id,
+    pub title: String,
+    pub description: String,
+    pub medium: NewMedium,
+}
+
+/// An existing ticket to be moved by staff into another course or ticket type. A new medium is only
+/// needed if the [`MediumType`] of the ticket changes, otherwise the current one is kept.
+pub struct MoveTicket {
+    pub id: Id,
+    pub course_id: Id,
+    pub type_: TicketType,
+    pub medium: Option<NewMedium>,
+}
+
 /// A new medium that belongs to a [`Ticket`] that is to be added to the system.
+#[derive(Clone)]
 pub enum NewMedium {
-    Text { page: u16, line: u16 },
-    Recording { time: NaiveTime },
-    Interactive { url: Url },
-    Questionaire { question: u16, answer: String },
+    Text { locations: Vec<Span<TextPosition>> },
+    Recording { locations: Vec<Span<NaiveTime>> },
+    Interactive { urls: Vec<Url> },
+    Questionaire { locations: Vec<QuestionLocation> },
+}
+
+impl NewMedium {
+    /// The type of medium this is.
+    pub const fn medium_type(&self) -> MediumType {
+        match self {
+            Self::Text { .. } => MediumType::Text,
+            Self::Recording { .. } => MediumType::Recording,
+            Self::Interactive { .. } => MediumType::Interactive,
+            Self::Questionaire { .. } => MediumType::Questionaire,
+        }
+    }
+
+    /// Bring the locations into the order of their start, so they're always listed the same way,
+    /// regardless of the order they were entered in. URLs keep their order, as they have no
+    /// natural one.
+    pub fn sort(&mut self) {
+        match self {
+            Self::Text { locations } => locations.sort(),
+            Self::Recording { locations } => locations.sort(),
+            Self::Interactive { .. } => {}
+            Self::Questionaire { locations } => locations.sort_by_key(|l| l.questions),
+        }
+    }
+}
+
+impl From<&Medium> for NewMedium {
+    fn from(value: &Medium) -> Self {
+        match value {
+            Medium::Text { locations, .. } => Self::Text {
+                locations: locations.clone(),
+            },
+            Medium::Recording { locations, .. } => Self::Recording {
+                locations: locations.clone(),
+            },
+            Medium::Interactive { urls, .. } => Self::Interactive { urls: urls.clone() },
+            Medium::Questionaire { locations, .. } => Self::Questionaire {
+                locations: locations.clone(),
+            },
+        }
+    }
+}
+
+/// Human readable form of all locations, in German.
+impl fmt::Display for NewMedium {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let locations = match self {
+            Self::Text { locations } => locations
+                .iter()
+                .map(|l| match l.end {
+                    Some(end) if end.page == l.start.page => format!(
+                        "Seite {}, Zeile {}\u{2013}{}",
+                        l.start.page, l.start.line, end.line
+                    ),
+                    Some(end) => format!(
+                        "Seite {}, Zeile {} bis Seite {}, Zeile {}",
+                        l.start.page, l.start.line, end.page, end.line
+                    ),
+                    None => format!("Seite {}, Zeile {}", l.start.page, l.start.line),
+                })
+                .collect::<Vec<_>>(),
+            Self::Recording { locations } => locations
+                .iter()
+                .map(|l| {
+                    if let Some(end) = l.end {
+                        format!(
+                            "Zeitraum {}\u{2013}{}",
+                            l.start.format("%H:%M:%S"),
+                            end.format("%H:%M:%S")
+                        )
+                    } else {
+                        format!("Zeitpunkt {}", l.start.format("%H:%M:%S"))
+                    }
+                })
+                .collect(),
+            Self::Interactive { urls } => urls.iter().map(ToString::to_string).collect(),
+            Self::Questionaire { locations } => locations
+                .iter()
+                .map(|l| {
+                    if let Some(end) = l.questions.end {
+                        format!(
+                            "Fragen {}\u{2013}{}, Antwort: {}",
+                            l.questions.start, end, l.answer
+                        )
+                    } else {
+                        format!("Frage {}, Antwort: {}", l.questions.start, l.answer)
+                    }
+                })
+                .collect(),
+        };
+
+        f.write_str(&locations.join("; "))
+    }
 }
 
 /// A full comment with all available details.
@@ -300,6 +945,39 @@ pub struct Comment {
     pub creator_id: Id,
     pub timestamp: DateTime<Utc>,
     pub message: String,
+    /// The ticket this comment was originally written on, if it was copied over from a duplicate.
+    pub source_ticket_id: Option<Id>,
+    /// Internal comments are only visible to staff members.
+    pub internal: bool,
+    /// Point in time when the comment was last edited or deleted, if it ever was.
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Deleted comments are kept in the timeline, but their message is removed.
+    pub deleted: bool,
+}
+
+/// A previous version of a comment's message, recorded whenever the comment is edited or deleted.
+pub struct CommentRevision {
+    pub id: Id,
+    pub comment_id: Id,
+    /// The user who replaced this version.
+    pub editor_id: Id,
+    /// Point in time when this version was replaced.
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+}
+
+/// A new comment revision to be added to the system.
+pub struct NewCommentRevision {
+    pub comment_id: Id,
+    pub editor_id: Id,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+}
+
+/// A comment revision with the editor's name included.
+pub struct CommentRevisionWithNames {
+    pub revision: CommentRevision,
+    pub editor_name: String,
 }
 
 /// A comment with its creator name included.
@@ -308,12 +986,432 @@ pub struct CommentWithNames {
     pub creator_name: String,
 }
 
+/// A file that is attached to a ticket or one of its comments.
+pub struct Attachment {
+    pub id: Id,
+    pub ticket_id: Id,
+    pub comment_id: Option<Id>,
+    pub creator_id: Id,
+    /// Original name of the file, as provided by the uploader.
+    pub file_name: String,
+    /// MIME type, as detected from the file content.
+    pub mime_type: String,
+    pub size: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Whether the attachment is an image that has a thumbnail.
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+}
+
+/// A new attachment to be added to the system.
+pub struct NewAttachment {
+    pub ticket_id: Id,
+    pub comment_id: Option<Id>,
+    pub creator_id: Id,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A file that was uploaded by a user but not yet validated or stored.
+pub struct Upload {
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// An uploaded file that passed validation and is ready to be stored as attachment.
+pub struct ValidUpload {
+    pub file_name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// The type of a link between two tickets, always seen from the perspective of one of them.
+///
+/// Each type has an [`inverse`](Self::inverse) that describes the same link from the other
+/// ticket. Only the canonical types are stored, the others are turned into their inverse with
+/// source and target swapped.
+#[derive(Copy, Clone, Eq, PartialEq, Display, EnumString, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum LinkType {
+    RelatesTo,
+    Duplicates,
+    DuplicatedBy,
+    Blocks,
+    BlockedBy,
+    FollowsUp,
+    FollowedUpBy,
+}
+
+impl LinkType {
+    /// All available link types, in the order they're offered for selection.
+    pub const ALL: [Self; 7] = [
+        Self::RelatesTo,
+        Self::Duplicates,
+        Self::DuplicatedBy,
+        Self::Blocks,
+        Self::BlockedBy,
+        Self::FollowsUp,
+        Self::FollowedUpBy,
+    ];
+
+    /// The same link type, seen from the other ticket.
+    pub const fn inverse(self) -> Self {
+        match self {
+            Self::RelatesTo => Self::RelatesTo,
+            Self::Duplicates => Self::DuplicatedBy,
+            Self::DuplicatedBy => Self::Duplicates,
+            Self::Blocks => Self::BlockedBy,
+            Self::BlockedBy => Self::Blocks,
+            Self::FollowsUp => Self::FollowedUpBy,
+            Self::FollowedUpBy => Self::FollowsUp,
+        }
+    }
+
+    /// Whether this link type is stored as is.
+    pub const fn is_canonical(self) -> bool {
+        matches!(
+            self,
+            Self::RelatesTo | Self::Duplicates | Self::Blocks | Self::FollowsUp
+        )
+    }
+}
+
+/// A directed link between two tickets.
+pub struct TicketLink {
+    pub id: Id,
+    pub source_id: Id,
+    pub target_id: Id,
+    /// Type of the link, seen from the source ticket.
+    pub kind: LinkType,
+}
+
+/// A new link to be added to the system.
+pub struct NewTicketLink {
+    pub source_id: Id,
+    pub target_id: Id,
+    pub kind: LinkType,
+}
+
+/// A ticket that is linked to another one, with the link type seen from that other ticket.
+pub struct LinkedTicket {
+    pub link_id: Id,
+    pub kind: LinkType,
+    pub ticket_id: Id,
+    pub title: String,
+    pub status: WorkflowStatus,
+}
+
+/// The field of a ticket that was changed by a [`TicketEvent`].
+#[derive(Copy, Clone, Eq, PartialEq, Display, EnumString, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum EventField {
+    /// The status changed, values are [`Status`] keys.
+    Status,
+    /// The priority changed, values are [`Priority`] values.
+    Priority,
+    /// The ticket was forwarded to the course's author, values are user IDs of the assignees.
+    Forwarded,
+    /// The assignee changed, values are user IDs.
+    Assignee,
+    /// A closed ticket was reopened, values are [`Status`] keys.
+    Reopened,
+    /// The creator of a closed ticket asked for it to be reopened, without values.
+    ReopenRequested,
+    /// A staff member refused to reopen a closed ticket, without values.
+    ReopenRefused,
+    /// The title, description or medium location was edited, without values as the previous
+    /// version is kept as revision.
+    Edited,
+    /// A [`Rule`] fired when the ticket was created, the new value is the rule's name.
+    Rule,
+    /// A forwarded ticket was returned to the course's tutor, values are user IDs of the
+    /// assignees.
+    Returned,
+    /// The ticket was moved to another course, values are course IDs.
+    Course,
+    /// The ticket was moved to another ticket type, values are [`TicketType`] keys.
+    Type,
+}
+
+/// A single recorded change of a ticket. Events are never changed or deleted once recorded.
+pub struct TicketEvent {
+    pub id: Id,
+    pub ticket_id: Id,
+    pub actor_id: Id,
+    pub timestamp: DateTime<Utc>,
+    pub field: EventField,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A previous version of a ticket's content, recorded whenever the ticket is edited.
+pub struct TicketRevision {
+    pub id: Id,
+    pub ticket_id: Id,
+    /// The user whose edit replaced this version.
+    pub editor_id: Id,
+    /// Point in time when this version was replaced.
+    pub timestamp: DateTime<Utc>,
+    pub title: String,
+    pub description: String,
+    /// Human readable form of the medium location.
+    pub location: String,
+}
+
+/// A new ticket revision to be added to the system.
+#[derive(Clone)]
+pub struct NewTicketRevision {
+    pub ticket_id: Id,
+    pub editor_id: Id,
+    pub timestamp: DateTime<Utc>,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+}
+
+/// A ticket revision with the editor's name included.
+pub struct TicketRevisionWithNames {
+    pub revision: TicketRevision,
+    pub editor_name: String,
+}
+
+/// The kind of change of a [`DiffPart`].
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A piece of text that was either kept, added or removed between two versions.
+pub struct DiffPart {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// The changes of a single edit of a ticket, compared to the version before it.
+pub struct RevisionDiff {
+    pub editor_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub title: Vec<DiffPart>,
+    pub description: Vec<DiffPart>,
+    pub location: Vec<DiffPart>,
+}
+
+/// A ticket event with the actor's name included and the old and new values resolved into a
+/// human readable form.
+pub struct TicketEventWithNames {
+    pub event: TicketEvent,
+    pub actor_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A new ticket event to be recorded.
+pub struct NewTicketEvent {
+    pub ticket_id: Id,
+    pub actor_id: Id,
+    pub timestamp: DateTime<Utc>,
+    pub field: EventField,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A single entry in the timeline of a ticket, which interleaves comments and changes.
+pub enum TimelineEntry {
+    Comment(CommentWithNames),
+    Event(TicketEventWithNames),
+}
+
+impl TimelineEntry {
+    /// The point in time when this entry was created.
+    pub const fn timestamp(&self) -> &DateTime<Utc> {
+        match self {
+            Self::Comment(c) => &c.comment.timestamp,
+            Self::Event(e) => &e.event.timestamp,
+        }
+    }
+}
+
 /// A new comment to be added to the system.
 pub struct NewComment {
     pub ticket_id: Id,
     pub creator_id: Id,
     pub timestamp: DateTime<Utc>,
     pub message: String,
+    pub source_ticket_id: Option<Id>,
+    pub internal: bool,
+}
+
+/// A label to group tickets beyond their [`Category`], for example by chapter or exam relevance.
+///
+/// Labels without a course are available for all tickets, others only for the tickets of their
+/// course.
+#[derive(Clone)]
+pub struct Label {
+    pub id: Id,
+    pub name: String,
+    pub color: StatusColor,
+    pub course_id: Option<Id>,
+}
+
+impl Label {
+    /// Check whether this label can be assigned to the tickets of the given course.
+    pub fn is_available(&self, course_id: Id) -> bool {
+        self.course_id.map_or(true, |id| id == course_id)
+    }
+}
+
+/// A new label to be added to the system.
+pub struct NewLabel {
+    pub name: String,
+    pub color: StatusColor,
+    pub course_id: Option<Id>,
+}
+
+/// A label with the name of its course included.
+pub struct LabelWithNames {
+    pub label: Label,
+    pub course_name: Option<String>,
+}
+
+/// A rule that is evaluated whenever a new ticket is created, to decide its priority and route it
+/// to the right person right away.
+///
+/// The rule fires if all of its conditions match the new ticket. Conditions that aren't set match
+/// any ticket.
+pub struct Rule {
+    pub id: Id,
+    pub name: String,
+    pub course_id: Option<Id>,
+    pub type_: Option<TicketType>,
+    pub category: Option<Category>,
+    pub medium: Option<MediumType>,
+    /// Words of which at least one must be contained in the title or description, ignoring case.
+    pub keywords: Vec<String>,
+    /// Priority to set for the ticket, instead of the one derived from its category.
+    pub priority: Option<Priority>,
+    /// Whether to forward the ticket to its course's author.
+    pub forward: bool,
+    /// User to assign the ticket to.
+    pub assignee_id: Option<Id>,
+    /// Labels to add to the ticket.
+    pub labels: Vec<Label>,
+}
+
+impl Rule {
+    /// Check whether all conditions of this rule match the given ticket.
+    pub fn matches(&self, ticket: &NewTicket) -> bool {
+        if self.course_id.map_or(false, |id| id != ticket.course_id)
+            || self.type_.map_or(false, |ty| ty != ticket.type_)
+            || self.category.map_or(false, |c| c != ticket.category)
+            || self.medium.map_or(false, |m| m != ticket.type_.medium())
+        {
+            return false;
+        }
+
+        if self.keywords.is_empty() {
+            return true;
+        }
+
+        let title = ticket.title.to_lowercase();
+        let description = ticket.description.to_lowercase();
+
+        self.keywords.iter().any(|keyword| {
+            let keyword = keyword.to_lowercase();
+            title.contains(&keyword) || description.contains(&keyword)
+        })
+    }
+}
+
+/// A new rule to be added to the system.
+pub struct NewRule {
+    pub name: String,
+    pub course_id: Option<Id>,
+    pub type_: Option<TicketType>,
+    pub category: Option<Category>,
+    pub medium: Option<MediumType>,
+    pub keywords: Vec<String>,
+    pub priority: Option<Priority>,
+    pub forward: bool,
+    pub assignee_id: Option<Id>,
+    pub label_ids: Vec<Id>,
+}
+
+/// A rule with the names of its course and assignee included.
+pub struct RuleWithNames {
+    pub rule: Rule,
+    pub course_name: Option<String>,
+    pub assignee_name: Option<String>,
+}
+
+/// The combined actions of all rules that fired for a new ticket.
+#[derive(Default)]
+pub struct RuleOutcome {
+    /// IDs of the fired rules, in the order they were evaluated.
+    pub rule_ids: Vec<Id>,
+    /// The highest priority of all fired rules.
+    pub priority: Option<Priority>,
+    /// Whether any of the fired rules forwards the ticket.
+    pub forward: bool,
+    /// The assignee of the first fired rule that assigns the ticket to someone.
+    pub assignee_id: Option<Id>,
+    /// IDs of all labels added by the fired rules, that are available in the ticket's course.
+    pub label_ids: Vec<Id>,
+}
+
+impl RuleOutcome {
+    /// Evaluate the rules for a new ticket in their given order and combine the actions of all
+    /// rules that fire.
+    pub fn evaluate<'a>(rules: impl IntoIterator<Item = &'a Rule>, ticket: &NewTicket) -> Self {
+        rules.into_iter().filter(|rule| rule.matches(ticket)).fold(
+            Self::default(),
+            |mut outcome, rule| {
+                outcome.rule_ids.push(rule.id);
+                outcome.priority = match (outcome.priority, rule.priority) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                outcome.forward |= rule.forward;
+                outcome.assignee_id = outcome.assignee_id.or(rule.assignee_id);
+                for label in &rule.labels {
+                    if label.is_available(ticket.course_id)
+                        && !outcome.label_ids.contains(&label.id)
+                    {
+                        outcome.label_ids.push(label.id);
+                    }
+                }
+                outcome
+            },
+        )
+    }
+
+    /// Check whether the rule with the given ID fired.
+    pub fn fired(&self, id: Id) -> bool {
+        self.rule_ids.contains(&id)
+    }
+}
+
+/// Details of a ticket that is about to be created, used to find similar existing tickets.
+pub struct SimilarSearch {
+    pub course_id: Id,
+    pub type_: TicketType,
+    pub title: String,
+    /// Location of the issue within the medium, if already known.
+    pub location: Option<NewMedium>,
+}
+
+/// An existing ticket that is similar to a new one.
+pub struct SimilarTicket {
+    pub ticket: TicketWithNames,
+    /// Similarity between `0.0` and `1.0`, based on the location within the medium and the title.
+    pub score: f64,
 }
 
 /// Different options to search and filter tickets.
@@ -321,8 +1419,68 @@ pub struct NewComment {
 /// All options can be combined to further narrow down the search.
 pub struct TicketSearch {
     pub title: Option<String>,
+    pub program_id: Option<Id>,
     pub course_id: Option<Id>,
     pub category: Option<Category>,
     pub priority: Option<Priority>,
     pub status: Option<Status>,
+    /// Only include tickets of active courses.
+    pub active_only: bool,
+    /// Labels that must all be assigned to a ticket.
+    pub labels: Vec<Id>,
+    pub order: Option<TicketOrder>,
+}
+
+/// Different orders in which the results of a [`TicketSearch`] can be sorted. Without any
+/// explicit order, tickets are sorted by their ID.
+#[derive(Copy, Clone, Eq, PartialEq, Display, EnumString, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum TicketOrder {
+    /// Most recently created tickets first.
+    Newest,
+    /// Tickets with the most votes first.
+    Votes,
+    /// Tickets grouped by course and medium, ordered by their first location inside the material.
+    Location,
+}
+
+/// An action that is applied to several tickets at once.
+pub enum BulkAction {
+    /// Change the status, following the transitions of the workflow.
+    Status(Status),
+    /// Set a new priority.
+    Priority(Priority),
+    /// Forward to the author of each ticket's course.
+    Forward,
+    /// Assign to the given staff member.
+    Assign(Id),
+    /// Add a label, keeping the already assigned ones.
+    AddLabel(Label),
+}
+
+/// Reasons why a [`BulkAction`] couldn't be applied to a single ticket.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum BulkError {
+    /// The ticket doesn't exist.
+    NotFound,
+    /// The ticket is already in a terminal status.
+    Closed,
+    /// The workflow doesn't allow the status change.
+    Transition,
+    /// The ticket was already forwarded.
+    Forwarded,
+    /// The user can't be assigned to tickets.
+    Assignee,
+    /// The label isn't available in the ticket's course.
+    Label,
+    /// Any other unexpected error.
+    Internal,
+}
+
+/// Outcome of a [`BulkAction`], listing the tickets that were changed and the ones that failed,
+/// together with the reason.
+#[derive(Default)]
+pub struct BulkOutcome {
+    pub changed: Vec<Id>,
+    pub failed: Vec<(Id, BulkError)>,
 }
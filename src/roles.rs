@@ -5,7 +5,7 @@ use rocket::request::{self, FromRequest};
 use rocket::{Outcome, Request};
 
 use crate::db::connection::DbConn;
-use crate::db::repositories::{self, UserRepository};
+use crate::db::repositories::{self, ProgramRepository, UserRepository};
 use crate::models::{Id, Role, User};
 
 /// Any user that is authenticated but not checked to have a specific role.
@@ -100,6 +100,25 @@ ranked_user_guard!(
     Role::Student
 );
 
+/// A user that manages at least one study program, independent of the user's role. Program
+/// managers get read access to all tickets of the courses within their programs.
+pub struct ProgramManagerUser<'a>(pub &'a User);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ProgramManagerUser<'a> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let user = request.guard::<&AuthUser>()?;
+        let conn = request.guard::<DbConn>()?;
+        let repo = repositories::program_repo(&conn);
+
+        match repo.list_managed_ids(user.0.id) {
+            Ok(ids) if !ids.is_empty() => Outcome::Success(Self(&user.0)),
+            _ => Outcome::Forward(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::Role;
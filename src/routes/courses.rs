@@ -10,21 +10,22 @@ use super::{NonEmptyString, PositiveId, ServerError};
 use crate::db::connection::DbConn;
 use crate::db::repositories;
 use crate::roles::AdminUser;
-use crate::services::{self, CourseService};
+use crate::services::{self, CourseService, ProgramService};
 use crate::templates::{self, MessageCode};
 
-/// Course management page for administrators.
+/// Course management page for administrators, grouping the courses by faculty and program.
 #[get("/")]
 pub fn list(
     user: AdminUser<'_>,
     conn: DbConn,
     flash: Option<FlashMessage<'_, '_>>,
 ) -> Result<templates::Courses, ServerError> {
-    let service = services::course_service(
-        repositories::user_repo(&conn),
+    let service = services::program_service(
+        repositories::program_repo(&conn),
         repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
     );
-    let courses = service.list()?;
+    let tree = service.tree()?;
 
     Ok(templates::Courses {
         role: user.0.role,
@@ -34,7 +35,7 @@ pub fn list(
                 f.msg().parse().unwrap_or(MessageCode::Unknown),
             )
         }),
-        courses,
+        tree,
     })
 }
 
@@ -143,6 +144,7 @@ pub struct EditCourse {
     title: NonEmptyString,
     author: PositiveId,
     tutor: PositiveId,
+    public_reports: bool,
 }
 
 /// Edit course POST endpoint to handle course editing, only for administrators.
@@ -158,7 +160,13 @@ pub fn post_edit(
         repositories::course_repo(&conn),
     );
 
-    match service.update(id.0, data.0.title.0, data.0.author.0, data.0.tutor.0) {
+    match service.update(
+        id.0,
+        data.0.title.0,
+        data.0.author.0,
+        data.0.tutor.0,
+        data.0.public_reports,
+    ) {
         Ok(()) => Flash::success(
             Redirect::to(uri!("/courses", list)),
             MessageCode::CourseUpdated,
@@ -0,0 +1,113 @@
+//! Intake template related routes, to manage the templates that guide users when creating tickets
+//! of a specific type.
+
+use anyhow::Result;
+use log::error;
+use rocket::request::{FlashMessage, Form, FromForm};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri};
+
+use super::ServerError;
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::models::{TicketTemplate, TicketType};
+use crate::roles::AdminUser;
+use crate::services::{self, TemplateService};
+use crate::templates::{self, MessageCode};
+
+/// Intake template management page for administrators, listing the templates of all ticket types.
+#[get("/")]
+pub fn list(
+    user: AdminUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::TicketTemplates, ServerError> {
+    let templates = services::template_service(repositories::template_repo(&conn)).list()?;
+
+    Ok(templates::TicketTemplates {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        templates,
+    })
+}
+
+/// Form data from the template edit form. Extra fields are entered one per line.
+#[derive(FromForm)]
+pub struct TemplateData {
+    description: String,
+    help: String,
+    fields: String,
+}
+
+/// Template POST endpoint to replace the intake template of a ticket type.
+#[post("/<ty>", data = "<data>")]
+pub fn post_update(
+    _user: AdminUser<'_>,
+    ty: TicketType,
+    data: Form<TemplateData>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::template_service(repositories::template_repo(&conn));
+
+    let fields = data
+        .0
+        .fields
+        .lines()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    match service.update(TicketTemplate {
+        type_: ty,
+        description: data.0.description,
+        help: data.0.help,
+        fields,
+    }) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/intake", list)),
+            MessageCode::TemplateUpdated,
+        ),
+        Err(e) => {
+            error!("error during template update: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/intake", list)),
+                MessageCode::FailedTemplateUpdate,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::Status;
+    use rocket::uri;
+
+    use crate::models::TicketType;
+    use crate::tests::{check_form, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_update() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/intake", super::post_update: TicketType::OnlineTest).to_string();
+
+        for data in &[
+            "help=&fields=",
+            "description=&fields=",
+            "description=&help=",
+        ] {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Label related routes, to manage the labels that can be assigned to tickets.
+
+use anyhow::Result;
+use log::error;
+use rocket::request::{FlashMessage, Form, FromForm};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri};
+
+use super::{NonEmptyString, PositiveId, ServerError};
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::models::{NewLabel, StatusColor};
+use crate::roles::AdminUser;
+use crate::services::{self, LabelService};
+use crate::templates::{self, MessageCode};
+
+/// Label management page for administrators.
+#[get("/")]
+pub fn list(
+    user: AdminUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::Labels, ServerError> {
+    let service = services::label_service(
+        repositories::label_repo(&conn),
+        repositories::course_repo(&conn),
+    );
+    let labels = service.list_with_names()?;
+    let courses = service.list_courses()?;
+
+    Ok(templates::Labels {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        labels,
+        courses,
+    })
+}
+
+/// Form data from the label creation form.
+#[derive(FromForm)]
+pub struct NewLabelData {
+    name: NonEmptyString,
+    color: StatusColor,
+    course: Option<PositiveId>,
+}
+
+/// New label POST endpoint to handle label creation. Labels without course are available for all
+/// tickets.
+#[post("/", data = "<data>")]
+pub fn post_new(_user: AdminUser<'_>, data: Form<NewLabelData>, conn: DbConn) -> Flash<Redirect> {
+    let service = services::label_service(
+        repositories::label_repo(&conn),
+        repositories::course_repo(&conn),
+    );
+
+    match service.create(NewLabel {
+        name: data.0.name.0,
+        color: data.0.color,
+        course_id: data.0.course.map(|c| c.0),
+    }) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/labels", list)),
+            MessageCode::LabelCreated,
+        ),
+        Err(e) => {
+            error!("error during label creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/labels", list)),
+                MessageCode::FailedLabelCreation,
+            )
+        }
+    }
+}
+
+/// Delete label POST endpoint to remove a label from the system and all tickets.
+#[post("/<id>/delete")]
+pub fn post_delete(_user: AdminUser<'_>, id: PositiveId, conn: DbConn) -> Flash<Redirect> {
+    let service = services::label_service(
+        repositories::label_repo(&conn),
+        repositories::course_repo(&conn),
+    );
+
+    match service.delete(id.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/labels", list)),
+            MessageCode::LabelDeleted,
+        ),
+        Err(e) => {
+            error!("error during label removal: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/labels", list)),
+                MessageCode::FailedLabelDeletion,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::Status;
+    use rocket::uri;
+
+    use crate::tests::{check_form, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_new() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/labels", super::post_new).to_string();
+
+        let data_list = &["name=&color=info", "name=a&color=", "name=a&color=purple"];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+}
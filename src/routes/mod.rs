@@ -4,23 +4,26 @@
 #![cfg_attr(doc, allow(unused_braces))]
 
 use std::convert::{TryFrom, TryInto};
+use std::io::Read;
 use std::str::FromStr;
 
 use log::error;
+use multipart::server::Multipart;
 use num_traits::PrimInt;
+use rocket::data::{self, FromDataSimple};
 use rocket::http::uri::{Formatter, Path, Query, UriDisplay};
 use rocket::http::{impl_from_uri_param_identity, RawStr, Status};
-use rocket::request::{FromFormValue, FromParam};
+use rocket::request::{FormItem, FormItems, FormParseError, FromForm, FromFormValue, FromParam};
 use rocket::response::{self, Redirect, Responder};
-use rocket::{get, uri, Request, State, UriDisplayPath};
-use url::Url;
+use rocket::{get, uri, Data, Outcome, Request, State, UriDisplayPath};
+use url::{form_urlencoded, Url};
 
 use crate::config::Config;
 use crate::db::connection::DbConn;
 use crate::db::repositories;
-use crate::models::Id;
+use crate::models::{Id, Upload};
 use crate::roles::AuthUser;
-use crate::services::{self, TicketService};
+use crate::services::{self, ProgramService, TicketService};
 use crate::{email, templates};
 
 pub mod assets;
@@ -28,8 +31,14 @@ pub mod auth;
 pub mod courses;
 pub mod errors;
 pub mod fairing;
+pub mod intake;
+pub mod labels;
+pub mod programs;
+pub mod reports;
+pub mod rules;
 pub mod tickets;
 pub mod users;
+pub mod workflow;
 
 /// Index page for authenticated users.
 #[get("/")]
@@ -40,6 +49,7 @@ pub fn index_user(
 ) -> Result<templates::Index, ServerError> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
@@ -48,12 +58,23 @@ pub fn index_user(
 
     let created_tickets = service.list_created(user.0.id)?;
     let assigned_tickets = service.list_assigned(user.0.id, user.0.role)?;
+    let program_manager = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    )
+    .is_manager(user.0.id)?;
+
+    let mut overdue = service.list_overdue(&created_tickets, &config.tickets.sla);
+    overdue.extend(service.list_overdue(&assigned_tickets, &config.tickets.sla));
 
     Ok(templates::Index {
         role: user.0.role,
         name: user.0.name.clone(),
         created_tickets,
         assigned_tickets,
+        program_manager,
+        overdue,
     })
 }
 
@@ -75,6 +96,165 @@ pub fn faq() -> Redirect {
     Redirect::to(uri!(auth::login))
 }
 
+/// Parse the value of a single form item, turning failures into a [`FormParseError::BadValue`].
+///
+/// This is useful for manual [`FromForm`](rocket::request::FromForm) implementations, that need to
+/// handle repeated fields like the values of a `<select multiple>` element.
+fn parse_form_value<'f, T: FromFormValue<'f>>(item: FormItem<'f>) -> Result<T, FormParseError<'f>> {
+    T::from_form_value(item.value).map_err(|_| FormParseError::BadValue(item.key, item.value))
+}
+
+/// Get Rocket's size limit for forms, which applies to all non-file fields of an [`UploadForm`].
+fn form_limit(request: &Request<'_>) -> u64 {
+    request.limits().get("forms").unwrap_or(32 * 1024)
+}
+
+/// Maximum amount of non-file fields in an [`UploadForm`], far more than any of the forms needs.
+const MAX_FORM_FIELDS: usize = 100;
+
+/// Create a [`FormParseError::Missing`] error for the given field name.
+fn missing(name: &str) -> FormParseError<'_> {
+    FormParseError::Missing(RawStr::from_str(name))
+}
+
+/// Form data that can be sent either URL encoded or as multipart form data. Regular fields are
+/// parsed into `T` the same way as with [`Form`](rocket::request::Form), while uploaded files are
+/// collected separately.
+///
+/// The amount and size of uploaded files is limited by the
+/// [`AttachmentConfig`](crate::config::AttachmentConfig). All other fields
+/// together are limited to the form limit of Rocket and at most
+/// [`MAX_FORM_FIELDS`] fields. Exceeding these limits results in a
+/// [`Status::PayloadTooLarge`] failure.
+pub struct UploadForm<T> {
+    pub form: T,
+    pub files: Vec<Upload>,
+}
+
+impl<T> FromDataSimple for UploadForm<T>
+where
+    T: for<'f> FromForm<'f>,
+{
+    type Error = String;
+
+    fn from_data(request: &Request<'_>, data: Data) -> data::Outcome<Self, Self::Error> {
+        let content_type = match request.content_type() {
+            Some(ct) => ct,
+            None => return Outcome::Forward(data),
+        };
+
+        if content_type.is_form() {
+            let mut form = String::new();
+            if let Err(e) = data
+                .open()
+                .take(form_limit(request))
+                .read_to_string(&mut form)
+            {
+                return Outcome::Failure((Status::InternalServerError, e.to_string()));
+            }
+
+            return Self::parse(&form, Vec::new());
+        }
+
+        if !content_type.is_form_data() {
+            return Outcome::Forward(data);
+        }
+
+        let boundary = match content_type.params().find(|&(k, _)| k == "boundary") {
+            Some((_, boundary)) => boundary,
+            None => {
+                return Outcome::Failure((Status::BadRequest, "missing boundary".to_owned()));
+            }
+        };
+
+        let limits = match request.guard::<State<'_, Config>>() {
+            Outcome::Success(config) => config.inner().attachments.clone(),
+            _ => {
+                return Outcome::Failure((Status::InternalServerError, "missing config".to_owned()))
+            }
+        };
+
+        let mut multipart = Multipart::with_body(data.open(), boundary);
+        let mut fields = form_urlencoded::Serializer::new(String::new());
+        let mut field_count = 0;
+        let mut remaining = form_limit(request);
+        let mut files = Vec::new();
+
+        loop {
+            let mut entry = match multipart.read_entry() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => return Outcome::Failure((Status::BadRequest, e.to_string())),
+            };
+
+            if let Some(file_name) = entry.headers.filename.clone() {
+                let mut content = Vec::new();
+                if let Err(e) = (&mut entry.data)
+                    .take(limits.max_size + 1)
+                    .read_to_end(&mut content)
+                {
+                    return Outcome::Failure((Status::BadRequest, e.to_string()));
+                }
+
+                // Browsers send an empty file part if no file was selected.
+                if file_name.is_empty() && content.is_empty() {
+                    continue;
+                }
+
+                if content.len() as u64 > limits.max_size || files.len() >= limits.max_files {
+                    return Outcome::Failure((Status::PayloadTooLarge, file_name));
+                }
+
+                files.push(Upload {
+                    file_name,
+                    data: content,
+                });
+            } else {
+                let mut value = String::new();
+                if let Err(e) = (&mut entry.data)
+                    .take(remaining + 1)
+                    .read_to_string(&mut value)
+                {
+                    return Outcome::Failure((Status::BadRequest, e.to_string()));
+                }
+
+                field_count += 1;
+                if value.len() as u64 > remaining || field_count > MAX_FORM_FIELDS {
+                    return Outcome::Failure((
+                        Status::PayloadTooLarge,
+                        entry.headers.name.to_string(),
+                    ));
+                }
+
+                remaining -= value.len() as u64;
+                fields.append_pair(&entry.headers.name, &value);
+            }
+        }
+
+        Self::parse(&fields.finish(), files)
+    }
+}
+
+impl<T> UploadForm<T>
+where
+    T: for<'f> FromForm<'f>,
+{
+    /// Parse the URL encoded fields into `T`, strictly like [`Form`](rocket::request::Form) does.
+    fn parse(form: &str, files: Vec<Upload>) -> data::Outcome<Self, String> {
+        let mut items = FormItems::from(form);
+        let result = T::from_form(&mut items, true);
+
+        if !items.exhaust() {
+            return Outcome::Failure((Status::BadRequest, "malformed form".to_owned()));
+        }
+
+        match result {
+            Ok(form) => Outcome::Success(Self { form, files }),
+            Err(_) => Outcome::Failure((Status::UnprocessableEntity, "invalid form".to_owned())),
+        }
+    }
+}
+
 /// A wrapper around [`anyhow::Error`] that will print the error and respond with a
 /// [`Status::InternalServerError`].
 #[derive(Debug)]
@@ -148,9 +328,13 @@ macro_rules! enum_from_request {
 
 enum_from_request!(crate::models::Role);
 enum_from_request!(crate::models::TicketType);
+enum_from_request!(crate::models::MediumType);
 enum_from_request!(crate::models::Category);
 enum_from_request!(crate::models::Priority);
 enum_from_request!(crate::models::Status);
+enum_from_request!(crate::models::StatusColor);
+enum_from_request!(crate::models::LinkType);
+enum_from_request!(crate::models::TicketOrder);
 
 /// A string that is guaranteed to not be empty when parsed from a request param or form value.
 pub struct NonEmptyString(String);
@@ -171,6 +355,26 @@ impl<'a> TryFrom<&'a RawStr> for NonEmptyString {
 
 from_request!(NonEmptyString);
 
+/// An email address that is guaranteed to be well-formed when parsed from a request param or form
+/// value.
+pub struct EmailAddress(String);
+
+impl<'a> TryFrom<&'a RawStr> for EmailAddress {
+    type Error = &'a RawStr;
+
+    fn try_from(value: &'a RawStr) -> Result<Self, Self::Error> {
+        let parsed = value.url_decode().map_err(|_| value)?;
+
+        if parsed.parse::<lettre::Address>().is_ok() {
+            Ok(Self(parsed))
+        } else {
+            Err(value)
+        }
+    }
+}
+
+from_request!(EmailAddress);
+
 /// An ID that is guaranteed to be equal or greater than `1` when parsed from a request param or
 /// form value.
 pub type PositiveId = PositiveNum<Id>;
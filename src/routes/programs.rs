@@ -0,0 +1,380 @@
+//! Faculty and study program related routes.
+
+use anyhow::Result;
+use log::error;
+use rocket::http::{RawStr, Status};
+use rocket::request::{FlashMessage, Form, FormItems, FormParseError, FromForm};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri};
+
+use super::{missing, parse_form_value, NonEmptyString, PositiveId, ServerError};
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::models::Id;
+use crate::roles::{AdminUser, AuthUser, ProgramManagerUser};
+use crate::services::{self, ProgramService};
+use crate::templates::{self, MessageCode};
+
+/// Program management page for administrators, showing all faculties and programs.
+#[get("/")]
+pub fn list(
+    user: AdminUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::Programs, ServerError> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let tree = service.tree()?;
+
+    Ok(templates::Programs {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        tree,
+    })
+}
+
+/// Program overview for program managers, limited to the programs they manage.
+#[get("/", rank = 2)]
+pub fn list_managed(
+    user: ProgramManagerUser<'_>,
+    conn: DbConn,
+) -> Result<templates::Programs, ServerError> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let tree = service.managed_tree(user.0.id)?;
+
+    Ok(templates::Programs {
+        role: user.0.role,
+        flash: None,
+        tree,
+    })
+}
+
+/// Fallback for users that neither administrate nor manage any programs.
+#[get("/", rank = 3)]
+pub const fn list_forbidden(_user: &AuthUser) -> Status {
+    Status::Forbidden
+}
+
+/// Form data from the faculty creation form.
+#[derive(FromForm)]
+pub struct NewFaculty {
+    name: NonEmptyString,
+}
+
+/// New faculty POST endpoint to handle faculty creation, only for administrators.
+#[post("/faculties", data = "<data>")]
+pub fn post_new_faculty(
+    _user: AdminUser<'_>,
+    data: Form<NewFaculty>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+
+    match service.create_faculty(data.0.name.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/programs", list)),
+            MessageCode::FacultyCreated,
+        ),
+        Err(e) => {
+            error!("error during faculty creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/programs", list)),
+                MessageCode::FailedFacultyCreation,
+            )
+        }
+    }
+}
+
+/// Program creation form for administrators.
+#[get("/new")]
+pub fn new(
+    user: AdminUser<'_>,
+    flash: Option<FlashMessage<'_, '_>>,
+    conn: DbConn,
+) -> Result<templates::NewProgram, ServerError> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let faculties = service.list_faculties()?;
+    let (courses, staff) = service.list_courses_staff()?;
+
+    Ok(templates::NewProgram {
+        role: user.0.role,
+        flash: flash.map(|f| f.msg().parse().unwrap_or(MessageCode::Unknown)),
+        faculties,
+        courses,
+        staff,
+    })
+}
+
+/// Form data from the program creation form.
+pub struct NewProgram {
+    faculty: Id,
+    code: String,
+    title: String,
+    courses: Vec<Id>,
+    managers: Vec<Id>,
+}
+
+impl<'f> FromForm<'f> for NewProgram {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let data = ProgramData::from_form(it, strict)?;
+
+        Ok(Self {
+            faculty: data.faculty,
+            code: data.code.ok_or_else(|| missing("code"))?,
+            title: data.title,
+            courses: data.courses,
+            managers: data.managers,
+        })
+    }
+}
+
+/// New program POST endpoint to handle program creation, only for administrators.
+#[post("/new", data = "<data>")]
+pub fn post_new(_user: AdminUser<'_>, data: Form<NewProgram>, conn: DbConn) -> Flash<Redirect> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+
+    match service.create(
+        data.0.faculty,
+        data.0.code,
+        data.0.title,
+        data.0.courses,
+        data.0.managers,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/programs", list)),
+            MessageCode::ProgramCreated,
+        ),
+        Err(e) => {
+            error!("error during program creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/programs", new)),
+                MessageCode::FailedProgramCreation,
+            )
+        }
+    }
+}
+
+/// Program editing form for administrators.
+#[get("/<id>/edit")]
+pub fn edit(
+    user: AdminUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::EditProgram, ServerError> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let program = service.get(id.0)?;
+    let faculties = service.list_faculties()?;
+    let (courses, staff) = service.list_courses_staff()?;
+
+    Ok(templates::EditProgram {
+        role: user.0.role,
+        flash: flash.map(|f| f.msg().parse().unwrap_or(MessageCode::Unknown)),
+        faculties,
+        courses,
+        staff,
+        program,
+    })
+}
+
+/// Form data from the program editing form.
+pub struct EditProgram {
+    faculty: Id,
+    title: String,
+    courses: Vec<Id>,
+    managers: Vec<Id>,
+}
+
+impl<'f> FromForm<'f> for EditProgram {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let data = ProgramData::from_form(it, strict)?;
+
+        if strict && data.code.is_some() {
+            return Err(FormParseError::Unknown(
+                RawStr::from_str("code"),
+                RawStr::from_str(""),
+            ));
+        }
+
+        Ok(Self {
+            faculty: data.faculty,
+            title: data.title,
+            courses: data.courses,
+            managers: data.managers,
+        })
+    }
+}
+
+/// Edit program POST endpoint to handle program editing, only for administrators.
+#[post("/<id>/edit", data = "<data>")]
+pub fn post_edit(
+    _user: AdminUser<'_>,
+    id: PositiveId,
+    data: Form<EditProgram>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+
+    match service.update(
+        id.0,
+        data.0.faculty,
+        data.0.title,
+        data.0.courses,
+        data.0.managers,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/programs", list)),
+            MessageCode::ProgramUpdated,
+        ),
+        Err(e) => {
+            error!("error during program update: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/programs", edit: id)),
+                MessageCode::FailedProgramUpdate,
+            )
+        }
+    }
+}
+
+/// Common form data of the program creation and editing forms. Courses and managers are selected
+/// with `<select multiple>` elements, which send one form field per selected value.
+struct ProgramData {
+    faculty: Id,
+    code: Option<String>,
+    title: String,
+    courses: Vec<Id>,
+    managers: Vec<Id>,
+}
+
+impl<'f> FromForm<'f> for ProgramData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut faculty = None;
+        let mut code = None;
+        let mut title = None;
+        let mut courses = Vec::new();
+        let mut managers = Vec::new();
+
+        for item in it {
+            match item.key.as_str() {
+                "faculty" => faculty = Some(parse_form_value::<PositiveId>(item)?.0),
+                "code" => code = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                "title" => title = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                "courses" => courses.push(parse_form_value::<PositiveId>(item)?.0),
+                "managers" => managers.push(parse_form_value::<PositiveId>(item)?.0),
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            faculty: faculty.ok_or_else(|| missing("faculty"))?,
+            code,
+            title: title.ok_or_else(|| missing("title"))?,
+            courses,
+            managers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::Status;
+    use rocket::uri;
+
+    use crate::routes::PositiveNum;
+    use crate::tests::{check_form, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_new_program() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/programs", super::post_new).to_string();
+
+        let data_list = &[
+            "faculty=&code=a&title=a",
+            "faculty=0&code=a&title=a",
+            "faculty=1&code=&title=a",
+            "faculty=1&code=a&title=",
+            "faculty=1&code=a&title=a&courses=0",
+            "faculty=1&code=a&title=a&managers=a",
+            "faculty=1&code=a&title=a&other=1",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_edit_program() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/programs", super::post_edit: PositiveNum(1)).to_string();
+
+        let data_list = &[
+            "faculty=0&title=a",
+            "faculty=1&title=",
+            "faculty=1&code=a&title=a",
+            "faculty=1&title=a&courses=",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn student_is_forbidden() {
+        let client = prepare_logged_in_client("max.mustermann", "Mustermann");
+        let uri = uri!("/programs", super::list).to_string();
+
+        assert_eq!(Status::Forbidden, client.get(uri).dispatch().status());
+    }
+}
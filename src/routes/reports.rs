@@ -0,0 +1,375 @@
+//! Public report related routes, that let people without an account report errors in the materials
+//! of a course, and let the course's staff moderate these reports.
+
+use std::net::IpAddr;
+
+use log::error;
+use rocket::http::Status;
+use rocket::outcome::IntoOutcome;
+use rocket::request::{self, FlashMessage, Form, FormItems, FormParseError, FromForm, FromRequest};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri, Request, State};
+
+use super::tickets::MediumFields;
+use super::{missing, parse_form_value, EmailAddress, NonEmptyString, PositiveId, ServerError};
+use crate::config::Config;
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::email;
+use crate::models::{Category, NewMedium, NewPublicReport, TicketType};
+use crate::roles::TutorUser;
+use crate::services::{self, PublicReportService, RuleService, TemplateService, TicketService};
+use crate::templates::{self, MessageCode};
+
+/// The IP address of the client that sent a request.
+pub struct ClientIp(IpAddr);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ClientIp {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request
+            .client_ip()
+            .map(Self)
+            .into_outcome((Status::BadRequest, ()))
+    }
+}
+
+/// Public report page of a course, where the type of the affected material is chosen. Only
+/// available for active courses that accept public reports.
+#[get("/<course>")]
+pub fn index(
+    course: PositiveId,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Option<templates::PublicReportCourse> {
+    let course = services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .get_course(course.0)
+    .ok()?;
+
+    Some(templates::PublicReportCourse {
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        course,
+    })
+}
+
+/// Public report form of a course for a single ticket type.
+#[get("/<course>/<ty>")]
+pub fn new(
+    course: PositiveId,
+    ty: TicketType,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<Option<templates::NewPublicReport>, ServerError> {
+    let course = match services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .get_course(course.0)
+    {
+        Ok(course) => course,
+        Err(_) => return Ok(None),
+    };
+    let template = services::template_service(repositories::template_repo(&conn)).get(ty)?;
+
+    Ok(Some(templates::NewPublicReport {
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        course,
+        ty,
+        template,
+    }))
+}
+
+/// Form data from the public report form.
+///
+/// The `website` field is a honeypot that is hidden from humans. Spam bots tend to fill out every
+/// field, so reports with a value in it are silently dropped. Answers to the intake template's
+/// extra fields are sent as `field-<n>`, the same way as in the ticket creation form.
+pub struct PublicReportData {
+    ty: TicketType,
+    category: Category,
+    title: String,
+    description: String,
+    medium: NewMedium,
+    fields: Vec<(usize, String)>,
+    email: Option<String>,
+    honeypot: bool,
+}
+
+impl<'f> FromForm<'f> for PublicReportData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut ty = None;
+        let mut category = None;
+        let mut title = None;
+        let mut description = None;
+        let mut email = None;
+        let mut honeypot = false;
+        let mut medium = MediumFields::default();
+        let mut fields = Vec::new();
+
+        for item in it {
+            if medium.parse(item)? {
+                continue;
+            }
+
+            match item.key.as_str() {
+                "ty" => ty = Some(parse_form_value(item)?),
+                "category" => category = Some(parse_form_value(item)?),
+                "title" => title = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                "description" => description = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                "email" if item.value.is_empty() => email = None,
+                "email" => email = Some(parse_form_value::<EmailAddress>(item)?.0),
+                "website" => honeypot = !item.value.is_empty(),
+                key => match key.strip_prefix("field-") {
+                    Some(position) => fields.push((
+                        position
+                            .parse()
+                            .map_err(|_| FormParseError::BadValue(item.key, item.value))?,
+                        parse_form_value(item)?,
+                    )),
+                    None if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                    None => {}
+                },
+            }
+        }
+
+        let ty = ty.ok_or_else(|| missing("ty"))?;
+
+        Ok(Self {
+            ty,
+            category: category.ok_or_else(|| missing("category"))?,
+            title: title.ok_or_else(|| missing("title"))?,
+            description: description.ok_or_else(|| missing("description"))?,
+            medium: medium.into_medium(ty)?,
+            fields,
+            email,
+            honeypot,
+        })
+    }
+}
+
+/// Public report POST endpoint, storing the report for moderation. The amount of reports per IP
+/// address is limited by [`TicketConfig::public_report_limit`](crate::config::TicketConfig).
+#[post("/<course>", data = "<data>")]
+pub fn post_new(
+    course: PositiveId,
+    ip: ClientIp,
+    data: Form<PublicReportData>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let data = data.into_inner();
+
+    if data.honeypot {
+        return Flash::success(
+            Redirect::to(uri!("/report", index: course)),
+            MessageCode::ReportSubmitted,
+        );
+    }
+
+    // The extra fields depend on the intake template, which can't be checked during form parsing.
+    let fields = match services::template_service(repositories::template_repo(&conn)).get(data.ty) {
+        Ok(template) => match template.fill(data.fields) {
+            Some(fields) => fields,
+            None => {
+                return Flash::error(
+                    Redirect::to(uri!("/report", new: course, data.ty)),
+                    MessageCode::MissingTicketFields,
+                )
+            }
+        },
+        Err(e) => {
+            error!("error during template lookup: {:?}", e);
+            return Flash::error(
+                Redirect::to(uri!("/report", new: course, data.ty)),
+                MessageCode::FailedReportSubmission,
+            );
+        }
+    };
+
+    let service = services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    );
+
+    match service.submit(
+        NewPublicReport {
+            course_id: course.0,
+            type_: data.ty,
+            category: data.category,
+            title: data.title,
+            description: data.description,
+            medium: data.medium,
+            fields,
+            contact_email: data.email,
+            ip_address: ip.0.to_string(),
+        },
+        config.tickets.public_report_limit,
+    ) {
+        Ok(true) => Flash::success(
+            Redirect::to(uri!("/report", index: course)),
+            MessageCode::ReportSubmitted,
+        ),
+        Ok(false) => Flash::error(
+            Redirect::to(uri!("/report", new: course, data.ty)),
+            MessageCode::TooManyReports,
+        ),
+        Err(e) => {
+            error!("error during public report submission: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/report", new: course, data.ty)),
+                MessageCode::FailedReportSubmission,
+            )
+        }
+    }
+}
+
+/// Moderation queue of all pending public reports of the courses the user is responsible for.
+#[get("/")]
+pub fn list(
+    user: TutorUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::PublicReports, ServerError> {
+    let reports = services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .list_pending(user.0.id, user.0.role)?;
+
+    Ok(templates::PublicReports {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        reports,
+    })
+}
+
+/// Endpoint to approve a public report, turning it into a regular ticket that goes through the
+/// ticket rules like any other new ticket. The moderator is recorded as the ticket's creator.
+#[post("/<id>/approve")]
+pub fn post_approve(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let report_service = services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    );
+    let ticket_service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let res = services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    )
+    .list()
+    .and_then(|rules| {
+        report_service.approve(id.0, user.0.id, user.0.role, &mut |report| {
+            let (ticket, medium) = report.into_ticket(user.0.id);
+
+            ticket_service.create(ticket, medium, &rules)
+        })
+    });
+
+    match res {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/reports", list)),
+            MessageCode::ReportApproved,
+        ),
+        Err(e) => {
+            error!("error during public report approval: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/reports", list)),
+                MessageCode::FailedReportApproval,
+            )
+        }
+    }
+}
+
+/// Endpoint to reject a public report, so it never becomes a ticket.
+#[post("/<id>/reject")]
+pub fn post_reject(user: TutorUser<'_>, id: PositiveId, conn: DbConn) -> Flash<Redirect> {
+    match services::public_report_service(
+        repositories::public_report_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .reject(id.0, user.0.id, user.0.role)
+    {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/reports", list)),
+            MessageCode::ReportRejected,
+        ),
+        Err(e) => {
+            error!("error during public report rejection: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/reports", list)),
+                MessageCode::FailedReportRejection,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::Client;
+    use rocket::uri;
+
+    use crate::routes::PositiveNum;
+
+    #[test]
+    fn invalid_post_new() {
+        let client = Client::new(crate::rocket().unwrap()).unwrap();
+        let uri = uri!("/report", super::post_new: PositiveNum(1)).to_string();
+
+        for data in &[
+            "category=content&title=a&description=b&page=1&line=1",
+            "ty=course-book&title=a&description=b&page=1&line=1",
+            "ty=course-book&category=content&description=b&page=1&line=1",
+            "ty=course-book&category=content&title=a&page=1&line=1",
+            "ty=course-book&category=content&title=a&description=b&line=1",
+            "ty=vodcast&category=content&title=a&description=b&page=1&line=1",
+            "ty=course-book&category=content&title=a&description=b&page=1&line=1&email=a",
+            "ty=course-book&category=content&title=a&description=b&page=1&line=1&field-a=b",
+        ] {
+            let res = client
+                .post(uri.as_str())
+                .remote("127.0.0.1:8000".parse().unwrap())
+                .body(data)
+                .header(ContentType::Form)
+                .dispatch();
+
+            assert_eq!(Status::UnprocessableEntity, res.status(), "data = {}", data);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Rule related routes, to manage the rules that decide the priority and routing of new tickets.
+
+use anyhow::Result;
+use log::error;
+use rocket::request::{FlashMessage, Form, FormItems, FormParseError, FromForm};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri};
+
+use super::{missing, parse_form_value, NonEmptyString, PositiveId, ServerError};
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::models::{Category, Id, MediumType, NewRule, NewTicket, Priority, TicketType};
+use crate::roles::AdminUser;
+use crate::services::{self, LabelService, RuleService};
+use crate::templates::{self, MessageCode};
+
+/// Rule management page for administrators.
+#[get("/")]
+pub fn list(
+    user: AdminUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::Rules, ServerError> {
+    let service = services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let rules = service.list_with_names()?;
+    let (courses, staff) = service.list_courses_staff()?;
+    let labels = services::label_service(
+        repositories::label_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .list_with_names()?;
+
+    Ok(templates::Rules {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        rules,
+        courses,
+        staff,
+        labels,
+    })
+}
+
+/// Form data from the rule creation form. Labels are selected with a `<select multiple>`
+/// element, which sends one form field per selected value.
+pub struct NewRuleData {
+    name: NonEmptyString,
+    course: Option<PositiveId>,
+    ty: Option<TicketType>,
+    category: Option<Category>,
+    medium: Option<MediumType>,
+    keywords: String,
+    priority: Option<Priority>,
+    forward: bool,
+    assignee: Option<PositiveId>,
+    labels: Vec<Id>,
+}
+
+impl<'f> FromForm<'f> for NewRuleData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut name = None;
+        let mut course = None;
+        let mut ty = None;
+        let mut category = None;
+        let mut medium = None;
+        let mut keywords = None;
+        let mut priority = None;
+        let mut forward = false;
+        let mut assignee = None;
+        let mut labels = Vec::new();
+
+        for item in it {
+            match item.key.as_str() {
+                "name" => name = Some(parse_form_value(item)?),
+                "course" => course = parse_form_value(item)?,
+                "ty" => ty = parse_form_value(item)?,
+                "category" => category = parse_form_value(item)?,
+                "medium" => medium = parse_form_value(item)?,
+                "keywords" => keywords = Some(parse_form_value(item)?),
+                "priority" => priority = parse_form_value(item)?,
+                "forward" => forward = parse_form_value(item)?,
+                "assignee" => assignee = parse_form_value(item)?,
+                "labels" => labels.push(parse_form_value::<PositiveId>(item)?.0),
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| missing("name"))?,
+            course,
+            ty,
+            category,
+            medium,
+            keywords: keywords.ok_or_else(|| missing("keywords"))?,
+            priority,
+            forward,
+            assignee,
+            labels,
+        })
+    }
+}
+
+/// New rule POST endpoint to handle rule creation.
+#[post("/", data = "<data>")]
+pub fn post_new(_user: AdminUser<'_>, data: Form<NewRuleData>, conn: DbConn) -> Flash<Redirect> {
+    let service = services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+
+    let keywords = data
+        .0
+        .keywords
+        .split(',')
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    match service.create(NewRule {
+        name: data.0.name.0,
+        course_id: data.0.course.map(|c| c.0),
+        type_: data.0.ty,
+        category: data.0.category,
+        medium: data.0.medium,
+        keywords,
+        priority: data.0.priority,
+        forward: data.0.forward,
+        assignee_id: data.0.assignee.map(|a| a.0),
+        label_ids: data.0.labels,
+    }) {
+        Ok(()) => Flash::success(Redirect::to(uri!("/rules", list)), MessageCode::RuleCreated),
+        Err(e) => {
+            error!("error during rule creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/rules", list)),
+                MessageCode::FailedRuleCreation,
+            )
+        }
+    }
+}
+
+/// Delete rule POST endpoint to remove an existing rule.
+#[post("/<id>/delete")]
+pub fn post_delete(_user: AdminUser<'_>, id: PositiveId, conn: DbConn) -> Flash<Redirect> {
+    let service = services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+
+    match service.delete(id.0) {
+        Ok(()) => Flash::success(Redirect::to(uri!("/rules", list)), MessageCode::RuleDeleted),
+        Err(e) => {
+            error!("error during rule removal: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/rules", list)),
+                MessageCode::FailedRuleDeletion,
+            )
+        }
+    }
+}
+
+/// Form data for the rule test form, describing a sample ticket.
+#[derive(FromForm)]
+pub struct SampleTicket {
+    course: Option<PositiveId>,
+    ty: Option<TicketType>,
+    category: Option<Category>,
+    title: Option<String>,
+    description: Option<String>,
+}
+
+/// Rule test page, showing which rules would fire for a sample ticket.
+#[get("/test?<data..>")]
+pub fn test(
+    user: AdminUser<'_>,
+    data: Form<SampleTicket>,
+    conn: DbConn,
+) -> Result<templates::TestRules, ServerError> {
+    let service = services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    );
+    let rules = service.list_with_names()?;
+    let (courses, staff) = service.list_courses_staff()?;
+
+    let SampleTicket {
+        course,
+        ty,
+        category,
+        title,
+        description,
+    } = data.0;
+    let course_id = course.map(|c| c.0);
+    let title = title.unwrap_or_default();
+    let description = description.unwrap_or_default();
+
+    // Only complete samples can be tested, as the conditions of most rules depend on these fields.
+    let outcome = match (course_id, ty, category) {
+        (Some(course_id), Some(type_), Some(category)) => Some(service.test(&NewTicket {
+            type_,
+            title: title.clone(),
+            description: description.clone(),
+            category,
+            course_id,
+            creator_id: user.0.id,
+            fields: Vec::new(),
+        })?),
+        _ => None,
+    };
+
+    let assignee_name = outcome
+        .as_ref()
+        .and_then(|o| o.assignee_id)
+        .and_then(|id| staff.into_iter().find(|(staff_id, _)| *staff_id == id))
+        .map(|(_, name)| name);
+
+    Ok(templates::TestRules {
+        role: user.0.role,
+        rules,
+        courses,
+        course_id,
+        ty,
+        category,
+        title,
+        description,
+        outcome,
+        assignee_name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::Status;
+    use rocket::uri;
+
+    use crate::tests::{check_form, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_new() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/rules", super::post_new).to_string();
+
+        let data_list = &[
+            "name=&keywords=&priority=high",
+            "name=a&priority=high",
+            "name=a&keywords=&priority=high&forward=maybe",
+            "name=a&keywords=&labels=0",
+            "name=a&keywords=&labels=a",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+}
@@ -1,22 +1,40 @@
 //! Ticket related routes.
 
-use anyhow::Result;
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use chrono::NaiveTime;
 use log::error;
-use rocket::http::RawStr;
-use rocket::request::{FlashMessage, Form, FormItems, FormParseError, FromForm};
-use rocket::response::{Flash, Redirect};
-use rocket::{get, post, uri, State};
-use url::Url;
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, RawStr};
+use rocket::request::{
+    FlashMessage, Form, FormItem, FormItems, FormParseError, FromForm, FromFormValue,
+};
+use rocket::response::{self, content, Flash, Redirect, Responder};
+use rocket::{get, post, uri, Request, Response, State, UriDisplayQuery};
+use serde::Serialize;
+use url::form_urlencoded;
 
-use super::{Hour, Minute, NonEmptyString, PositiveId, PositiveNum, Second, ServerError, ValidUrl};
+use super::{
+    missing, parse_form_value, Hour, Minute, NonEmptyString, PositiveId, PositiveNum, Second,
+    ServerError, UploadForm, ValidUrl,
+};
 use crate::config::Config;
 use crate::db::connection::DbConn;
 use crate::db::repositories;
-use crate::email;
-use crate::models::{Category, Id, Priority, Status, TicketSearch, TicketType};
-use crate::roles::{StudentUser, TutorUser};
-use crate::services::{self, TicketService};
+use crate::dirs::DIRS;
+use crate::models::{
+    Attachment, BulkAction, Category, EditTicketContent, Id, LinkType, MediumType, MoveTicket,
+    NewMedium, Priority, QuestionLocation, Role, SimilarSearch, Span, Status, TextPosition,
+    TicketOrder, TicketSearch, TicketType, Upload, ValidUpload,
+};
+use crate::roles::{AdminUser, StudentUser, TutorUser};
+use crate::services::{
+    self, AttachmentService, LabelService, ProgramService, RuleService, TemplateService,
+    TicketService,
+};
 use crate::templates::{self, MessageCode};
+use crate::{email, markdown, storage};
 
 /// Ticket creation form for students or higher ranked users.
 #[get("/new/<ty>")]
@@ -25,40 +43,94 @@ pub fn new(
     ty: TicketType,
     conn: DbConn,
     config: State<'_, Config>,
+    flash: Option<FlashMessage<'_, '_>>,
 ) -> Result<templates::NewTicket, ServerError> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
     let courses = service.list_course_names()?;
+    let template = services::template_service(repositories::template_repo(&conn)).get(ty)?;
 
     Ok(templates::NewTicket {
         role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
         ty,
         courses,
+        template,
     })
 }
 
 /// Form data from the ticket creation form. It contains all available data for differnet kinds of
 /// tickets and should never be used directly in a route. Instead use the [`NewTicket`] struct.
-#[derive(FromForm)]
+///
+/// Answers to the extra fields of the ticket type's intake template are sent as `field-<n>`, where
+/// `n` is the position of the field in the template.
 struct NewTicketData {
     ty: TicketType,
     category: Category,
     title: NonEmptyString,
     description: NonEmptyString,
     course: PositiveId,
-    page: Option<PositiveNum<u16>>,
-    line: Option<PositiveNum<u16>>,
-    url: Option<ValidUrl>,
-    question: Option<PositiveNum<u16>>,
-    answer: Option<NonEmptyString>,
-    hour: Option<Hour>,
-    minute: Option<Minute>,
-    second: Option<Second>,
+    medium: MediumFields,
+    fields: Vec<(usize, String)>,
+}
+
+impl<'f> FromForm<'f> for NewTicketData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut ty = None;
+        let mut category = None;
+        let mut title = None;
+        let mut description = None;
+        let mut course = None;
+        let mut medium = MediumFields::default();
+        let mut fields = Vec::new();
+
+        for item in it {
+            if medium.parse(item)? {
+                continue;
+            }
+
+            match item.key.as_str() {
+                "ty" => ty = Some(parse_form_value(item)?),
+                "category" => category = Some(parse_form_value(item)?),
+                "title" => title = Some(parse_form_value(item)?),
+                "description" => description = Some(parse_form_value(item)?),
+                "course" => course = Some(parse_form_value(item)?),
+                key => match key.strip_prefix("field-") {
+                    Some(position) => fields.push((
+                        position
+                            .parse()
+                            .map_err(|_| FormParseError::BadValue(item.key, item.value))?,
+                        parse_form_value(item)?,
+                    )),
+                    None if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                    None => {}
+                },
+            }
+        }
+
+        Ok(Self {
+            ty: ty.ok_or_else(|| missing("ty"))?,
+            category: category.ok_or_else(|| missing("category"))?,
+            title: title.ok_or_else(|| missing("title"))?,
+            description: description.ok_or_else(|| missing("description"))?,
+            course: course.ok_or_else(|| missing("course"))?,
+            medium,
+            fields,
+        })
+    }
 }
 
 /// Form data for the ticket creation form.
@@ -68,48 +140,221 @@ pub struct NewTicket {
     title: String,
     description: String,
     course: Id,
-    medium: Medium,
+    medium: NewMedium,
+    /// Answers to the extra fields of the intake template, by their position. They're validated
+    /// against the template during creation.
+    fields: Vec<(usize, String)>,
+}
+
+/// The medium fields of the ticket creation and edit forms, of which only the ones that belong to
+/// the ticket's type are required.
+///
+/// A medium can have several locations, which are sent as repeated fields. The n-th value of each
+/// field belongs to the n-th location, so forms must send every field of a location, even if it's
+/// empty. The `end_*` fields are optional and turn a location into a range. At most
+/// [`MAX_LOCATIONS`] locations are accepted.
+#[derive(Default)]
+pub(super) struct MediumFields {
+    page: Vec<Option<PositiveNum<u16>>>,
+    line: Vec<Option<PositiveNum<u16>>>,
+    end_page: Vec<Option<PositiveNum<u16>>>,
+    end_line: Vec<Option<PositiveNum<u16>>>,
+    url: Vec<Option<ValidUrl>>,
+    question: Vec<Option<PositiveNum<u16>>>,
+    end_question: Vec<Option<PositiveNum<u16>>>,
+    answer: Vec<Option<NonEmptyString>>,
+    hour: Vec<Option<Hour>>,
+    minute: Vec<Option<Minute>>,
+    second: Vec<Option<Second>>,
+    end_hour: Vec<Option<Hour>>,
+    end_minute: Vec<Option<Minute>>,
+    end_second: Vec<Option<Second>>,
 }
 
-/// Different kinds of media from the ticket creation form.
-pub enum Medium {
-    Text { page: u16, line: u16 },
-    Interactive { url: Url },
-    Questionaire { question: u16, answer: String },
-    Recording { hour: u8, minute: u8, second: u8 },
+/// Maximum amount of locations in a single medium, which is plenty for any error report.
+pub(super) const MAX_LOCATIONS: usize = 20;
+
+impl MediumFields {
+    /// Take over the value of a form item if it's one of the medium fields. Returns whether the
+    /// item was consumed, so other items can be handled by the caller.
+    pub(super) fn parse<'f>(&mut self, item: FormItem<'f>) -> Result<bool, FormParseError<'f>> {
+        /// Add the value of a repeated field, failing if it already holds the maximum amount of
+        /// locations.
+        fn push<'f, T: FromFormValue<'f>>(
+            values: &mut Vec<Option<T>>,
+            item: FormItem<'f>,
+        ) -> Result<(), FormParseError<'f>> {
+            if values.len() >= MAX_LOCATIONS {
+                return Err(FormParseError::BadValue(item.key, item.value));
+            }
+
+            values.push(parse_form_value(item)?);
+            Ok(())
+        }
+
+        match item.key.as_str() {
+            "page" => push(&mut self.page, item)?,
+            "line" => push(&mut self.line, item)?,
+            "end_page" => push(&mut self.end_page, item)?,
+            "end_line" => push(&mut self.end_line, item)?,
+            "url" => push(&mut self.url, item)?,
+            "question" => push(&mut self.question, item)?,
+            "end_question" => push(&mut self.end_question, item)?,
+            "answer" => push(&mut self.answer, item)?,
+            "hour" => push(&mut self.hour, item)?,
+            "minute" => push(&mut self.minute, item)?,
+            "second" => push(&mut self.second, item)?,
+            "end_hour" => push(&mut self.end_hour, item)?,
+            "end_minute" => push(&mut self.end_minute, item)?,
+            "end_second" => push(&mut self.end_second, item)?,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Check whether none of the medium fields were sent at all.
+    pub(super) fn is_empty(&self) -> bool {
+        self.page.is_empty()
+            && self.line.is_empty()
+            && self.end_page.is_empty()
+            && self.end_line.is_empty()
+            && self.url.is_empty()
+            && self.question.is_empty()
+            && self.end_question.is_empty()
+            && self.answer.is_empty()
+            && self.hour.is_empty()
+            && self.minute.is_empty()
+            && self.second.is_empty()
+            && self.end_hour.is_empty()
+            && self.end_minute.is_empty()
+            && self.end_second.is_empty()
+    }
+
+    /// Turn the fields into the medium for the given ticket type, failing if any of the needed
+    /// fields are missing or a range ends before it starts.
+    pub(super) fn into_medium<'f>(
+        mut self,
+        ty: TicketType,
+    ) -> Result<NewMedium, FormParseError<'f>> {
+        /// Take the value of a repeated field that belongs to the given location.
+        fn nth<T>(values: &mut [Option<T>], row: usize) -> Option<T> {
+            values.get_mut(row).and_then(Option::take)
+        }
+
+        /// Take the value of a required repeated field that belongs to the given location.
+        fn required<'f, T>(
+            values: &mut [Option<T>],
+            row: usize,
+            name: &'f str,
+        ) -> Result<T, FormParseError<'f>> {
+            nth(values, row).ok_or_else(|| missing(name))
+        }
+
+        /// Create a span from a start and an optional end, failing if the end lies before the
+        /// start.
+        fn span<T: Copy + Ord>(
+            start: T,
+            end: Option<T>,
+            name: &str,
+        ) -> Result<Span<T>, FormParseError<'_>> {
+            Span::new(start, end).ok_or_else(|| {
+                FormParseError::BadValue(RawStr::from_str(name), RawStr::from_str(""))
+            })
+        }
+
+        // At least one location is required, so a missing first location fails as missing field.
+        Ok(match ty.medium() {
+            MediumType::Text => {
+                let locations = (0..self.page.len().max(1))
+                    .map(|row| {
+                        let start = TextPosition {
+                            page: required(&mut self.page, row, "page")?.0,
+                            line: required(&mut self.line, row, "line")?.0,
+                        };
+                        let end = match (nth(&mut self.end_page, row), nth(&mut self.end_line, row))
+                        {
+                            (Some(page), Some(line)) => Some(TextPosition {
+                                page: page.0,
+                                line: line.0,
+                            }),
+                            // A range on the same page only needs the last line.
+                            (None, Some(line)) => Some(TextPosition {
+                                page: start.page,
+                                line: line.0,
+                            }),
+                            (Some(_), None) => return Err(missing("end_line")),
+                            (None, None) => None,
+                        };
+
+                        span(start, end, "end_line")
+                    })
+                    .collect::<Result<_, FormParseError<'f>>>()?;
+
+                NewMedium::Text { locations }
+            }
+            MediumType::Interactive => {
+                let urls = (0..self.url.len().max(1))
+                    .map(|row| Ok(required(&mut self.url, row, "url")?.0))
+                    .collect::<Result<_, FormParseError<'f>>>()?;
+
+                NewMedium::Interactive { urls }
+            }
+            MediumType::Questionaire => {
+                let locations = (0..self.question.len().max(1))
+                    .map(|row| {
+                        let start = required(&mut self.question, row, "question")?.0;
+                        let end = nth(&mut self.end_question, row).map(|q| q.0);
+
+                        Ok(QuestionLocation {
+                            questions: span(start, end, "end_question")?,
+                            answer: required(&mut self.answer, row, "answer")?.0,
+                        })
+                    })
+                    .collect::<Result<_, FormParseError<'f>>>()?;
+
+                NewMedium::Questionaire { locations }
+            }
+            MediumType::Recording => {
+                let locations = (0..self.hour.len().max(1))
+                    .map(|row| {
+                        let start = NaiveTime::from_hms(
+                            required(&mut self.hour, row, "hour")?.0.into(),
+                            required(&mut self.minute, row, "minute")?.0.into(),
+                            required(&mut self.second, row, "second")?.0.into(),
+                        );
+                        let end = match (
+                            nth(&mut self.end_hour, row),
+                            nth(&mut self.end_minute, row),
+                            nth(&mut self.end_second, row),
+                        ) {
+                            (Some(hour), Some(minute), Some(second)) => Some(NaiveTime::from_hms(
+                                hour.0.into(),
+                                minute.0.into(),
+                                second.0.into(),
+                            )),
+                            (None, None, None) => None,
+                            (_, None, _) => return Err(missing("end_minute")),
+                            (None, ..) => return Err(missing("end_hour")),
+                            (.., None) => return Err(missing("end_second")),
+                        };
+
+                        span(start, end, "end_second")
+                    })
+                    .collect::<Result<_, FormParseError<'f>>>()?;
+
+                NewMedium::Recording { locations }
+            }
+        })
+    }
 }
 
 impl<'f> FromForm<'f> for NewTicket {
     type Error = rocket::request::FormParseError<'f>;
 
     fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
-        let missing = |name| FormParseError::Missing(RawStr::from_str(name));
-
         let data = NewTicketData::from_form(it, strict)?;
-        let medium = match data.ty {
-            TicketType::CourseBook | TicketType::ReadingList | TicketType::Presentation => {
-                Medium::Text {
-                    page: data.page.ok_or_else(|| missing("page"))?.0,
-                    line: data.line.ok_or_else(|| missing("line"))?.0,
-                }
-            }
-            TicketType::InteractiveBook => Medium::Interactive {
-                url: data.url.ok_or_else(|| missing("url"))?.0,
-            },
-            TicketType::PracticeExam
-            | TicketType::PracticeExamSolution
-            | TicketType::OnlineTest => Medium::Questionaire {
-                question: data.question.ok_or_else(|| missing("question"))?.0,
-                answer: data.answer.ok_or_else(|| missing("answer"))?.0,
-            },
-            TicketType::Vodcast | TicketType::Podcast | TicketType::LiveTutorialRecording => {
-                Medium::Recording {
-                    hour: data.hour.ok_or_else(|| missing("hour"))?.0,
-                    minute: data.minute.ok_or_else(|| missing("minute"))?.0,
-                    second: data.second.ok_or_else(|| missing("second"))?.0,
-                }
-            }
-        };
+        let medium = data.medium.into_medium(data.ty)?;
 
         Ok(Self {
             ty: data.ty,
@@ -118,6 +363,7 @@ impl<'f> FromForm<'f> for NewTicket {
             description: data.description.0,
             course: data.course.0,
             medium,
+            fields: data.fields,
         })
     }
 }
@@ -126,56 +372,133 @@ impl<'f> FromForm<'f> for NewTicket {
 #[post("/new", data = "<data>")]
 pub fn post_new(
     user: StudentUser<'_>,
-    data: Form<NewTicket>,
+    data: UploadForm<NewTicket>,
     conn: DbConn,
     config: State<'_, Config>,
 ) -> Flash<Redirect> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    match service.create(
-        crate::models::NewTicket {
-            type_: data.ty,
-            title: data.0.title,
-            description: data.0.description,
-            category: data.0.category,
-            course_id: data.0.course,
-            creator_id: user.0.id,
+    let UploadForm { form, files } = data;
+
+    // The extra fields depend on the intake template, which can't be checked during form parsing.
+    let fields = match services::template_service(repositories::template_repo(&conn)).get(form.ty) {
+        Ok(template) => match template.fill(form.fields) {
+            Some(fields) => fields,
+            None => {
+                return Flash::error(
+                    Redirect::to(uri!("/tickets", new: form.ty)),
+                    MessageCode::MissingTicketFields,
+                )
+            }
         },
-        match data.0.medium {
-            Medium::Text { page, line } => crate::models::NewMedium::Text { page, line },
-            Medium::Recording {
-                hour,
-                minute,
-                second,
-            } => crate::models::NewMedium::Recording {
-                time: chrono::NaiveTime::from_hms(hour.into(), minute.into(), second.into()),
-            },
-            Medium::Interactive { url } => crate::models::NewMedium::Interactive { url },
-            Medium::Questionaire { question, answer } => {
-                crate::models::NewMedium::Questionaire { question, answer }
+        Err(e) => {
+            error!("error during template lookup: {:?}", e);
+            return Flash::error(
+                Redirect::to(uri!("/tickets", new: form.ty)),
+                MessageCode::FailedTicketCreation,
+            );
+        }
+    };
+
+    let ticket = crate::models::NewTicket {
+        type_: form.ty,
+        title: form.title,
+        description: form.description,
+        category: form.category,
+        course_id: form.course,
+        creator_id: user.0.id,
+        fields,
+    };
+
+    // Check the attachments first, so an invalid file doesn't leave a ticket without them.
+    let files = match validate_attachments(&conn, &config, files) {
+        Ok(files) => files,
+        Err(e) => {
+            error!("error during attachment validation: {:?}", e);
+            return Flash::error(
+                Redirect::to(uri!("/tickets", new: form.ty)),
+                MessageCode::FailedAttachmentUpload,
+            );
+        }
+    };
+
+    match services::rule_service(
+        repositories::rule_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    )
+    .list()
+    .and_then(|rules| service.create(ticket, form.medium, &rules))
+    {
+        Ok(id) => match add_attachments(&conn, &config, id, None, user.0.id, files) {
+            Ok(()) => Flash::success(
+                Redirect::to(uri!("/tickets", edit: PositiveNum(id))),
+                MessageCode::TicketCreated,
+            ),
+            Err(e) => {
+                error!("error during attachment upload: {:?}", e);
+                Flash::error(
+                    Redirect::to(uri!("/tickets", edit: PositiveNum(id))),
+                    MessageCode::FailedAttachmentUpload,
+                )
             }
         },
-    ) {
-        Ok(id) => Flash::success(
-            Redirect::to(uri!("/tickets", edit: PositiveNum(id))),
-            MessageCode::TicketCreated,
-        ),
         Err(e) => {
             error!("error during ticket creation: {:?}", e);
             Flash::error(
-                Redirect::to(uri!("/tickets", new: data.0.ty)),
+                Redirect::to(uri!("/tickets", new: form.ty)),
                 MessageCode::FailedTicketCreation,
             )
         }
     }
 }
 
+/// Validate the uploaded files before the ticket or comment they belong to is created.
+fn validate_attachments(
+    conn: &DbConn,
+    config: &Config,
+    files: Vec<Upload>,
+) -> Result<Vec<ValidUpload>> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    services::attachment_service(
+        repositories::attachment_repo(conn),
+        storage::new_disk_store(DIRS.attachment_dir()),
+        &config.attachments,
+    )
+    .validate(files)
+}
+
+/// Store the validated files as attachments of a ticket, or one of its comments.
+fn add_attachments(
+    conn: &DbConn,
+    config: &Config,
+    ticket_id: Id,
+    comment_id: Option<Id>,
+    creator_id: Id,
+    files: Vec<ValidUpload>,
+) -> Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    services::attachment_service(
+        repositories::attachment_repo(conn),
+        storage::new_disk_store(DIRS.attachment_dir()),
+        &config.attachments,
+    )
+    .add(ticket_id, comment_id, creator_id, files)
+}
+
 /// Response of the [`edit`] endpoint.
 #[allow(clippy::large_enum_variant)]
 #[derive(rocket::Responder)]
@@ -195,6 +518,7 @@ pub fn edit(
 ) -> Result<EditResponse, ServerError> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
@@ -206,9 +530,41 @@ pub fn edit(
     }
 
     let ticket = service.get_with_rels(id.0, user.0.id, user.0.role)?;
+    let (assignees, next_statuses, labels) = if user.0.role <= Role::Tutor {
+        (
+            service.list_assignees()?,
+            service.workflow()?.next(&ticket.status.key, user.0.role),
+            services::label_service(
+                repositories::label_repo(&conn),
+                repositories::course_repo(&conn),
+            )
+            .list_for_course(ticket.ticket.course_id)?,
+        )
+    } else {
+        (Vec::new(), Vec::new(), Vec::new())
+    };
+    let can_reopen =
+        service.can_reopen(id.0, user.0.id, user.0.role, config.tickets.reopen_window)?;
+    let reopen_requested = service.has_reopen_request(id.0)?;
+    let has_voted = service.has_voted(id.0, user.0.id)?;
+    let is_watching = service.is_watching(id.0, user.0.id)?;
+    let can_edit = service.can_edit(id.0, user.0.id, user.0.role)?;
+    let label_ids = ticket.label_ids();
+    let can_return = ticket.can_return(user.0.id, user.0.role);
+    let revisions = service.list_revisions(&ticket)?;
+    let editable_comments =
+        service.list_editable_comments(&ticket, user.0.id, config.tickets.comment_edit_window);
+    let comment_revisions = if user.0.role == Role::Admin {
+        service.list_comment_revisions(id.0)?
+    } else {
+        Vec::new()
+    };
+    let mentioned_users = service.list_mentioned_users(&ticket)?;
+    let due = services::due_dates(&ticket.ticket, ticket.status.terminal, &config.tickets.sla);
 
     Ok(EditResponse::Edit(templates::TicketDetail {
         role: user.0.role,
+        user_id: user.0.id,
         flash: flash.map(|f| {
             (
                 f.name().to_owned(),
@@ -216,6 +572,22 @@ pub fn edit(
             )
         }),
         ticket,
+        assignees,
+        next_statuses,
+        labels,
+        label_ids,
+        can_reopen,
+        reopen_requested,
+        has_voted,
+        is_watching,
+        can_edit,
+        can_return,
+        revisions,
+        editable_comments,
+        comment_revisions,
+        mentioned_users,
+        due,
+        link_types: LinkType::ALL,
     }))
 }
 
@@ -228,7 +600,7 @@ pub struct EditTicket {
 /// Endpoint to update ticket details.
 #[post("/<id>/edit", data = "<data>")]
 pub fn post_edit(
-    _user: TutorUser<'_>,
+    user: TutorUser<'_>,
     id: PositiveId,
     data: Form<EditTicket>,
     conn: DbConn,
@@ -236,13 +608,14 @@ pub fn post_edit(
 ) -> Flash<Redirect> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    match service.update(id.0, data.priority) {
+    match service.update(id.0, data.priority, user.0.id) {
         Ok(()) => Flash::success(
             Redirect::to(uri!("/tickets", edit: id)),
             MessageCode::TicketUpdated,
@@ -257,10 +630,93 @@ pub fn post_edit(
     }
 }
 
+/// Form data for the ticket content edit form.
+pub struct EditContent {
+    title: String,
+    description: String,
+    medium: NewMedium,
+}
+
+impl<'f> FromForm<'f> for EditContent {
+    type Error = rocket::request::FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut ty = None;
+        let mut title = None;
+        let mut description = None;
+        let mut medium = MediumFields::default();
+
+        for item in it {
+            if medium.parse(item)? {
+                continue;
+            }
+
+            match item.key.as_str() {
+                "ty" => ty = Some(parse_form_value::<TicketType>(item)?),
+                "title" => title = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                "description" => description = Some(parse_form_value::<NonEmptyString>(item)?.0),
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            title: title.ok_or_else(|| missing("title"))?,
+            description: description.ok_or_else(|| missing("description"))?,
+            medium: medium.into_medium(ty.ok_or_else(|| missing("ty"))?)?,
+        })
+    }
+}
+
+/// Endpoint to edit the title, description and medium of a ticket.
+#[post("/<id>/content", data = "<data>")]
+pub fn post_edit_content(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    data: Form<EditContent>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let data = data.into_inner();
+
+    match service.edit_content(
+        EditTicketContent {
+            id: id.0,
+            title: data.title,
+            description: data.description,
+            medium: data.medium,
+        },
+        user.0.id,
+        user.0.role,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketUpdated,
+        ),
+        Err(e) => {
+            error!("error during ticket content update: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketUpdate,
+            )
+        }
+    }
+}
+
 /// Form data for the comment form.
 #[derive(FromForm)]
 pub struct NewComment {
     comment: NonEmptyString,
+    internal: bool,
 }
 
 /// Endpoint to create new ticket comments.
@@ -268,23 +724,60 @@ pub struct NewComment {
 pub fn post_add_comment(
     user: StudentUser<'_>,
     id: PositiveId,
-    data: Form<NewComment>,
+    data: UploadForm<NewComment>,
     conn: DbConn,
     config: State<'_, Config>,
 ) -> Flash<Redirect> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    match service.add_comment(id.0, user.0.id, data.0.comment.0) {
-        Ok(()) => Flash::success(
+    // Only users that can see the ticket take part in its discussion.
+    if !service
+        .can_open(id.0, user.0.id, user.0.role)
+        .unwrap_or(false)
+    {
+        return Flash::error(
             Redirect::to(uri!("/tickets", edit: id)),
-            MessageCode::CommentCreated,
-        ),
+            MessageCode::FailedCommentCreation,
+        );
+    }
+
+    let UploadForm { form, files } = data;
+
+    // Check the attachments first, so an invalid file doesn't leave a comment without them.
+    let files = match validate_attachments(&conn, &config, files) {
+        Ok(files) => files,
+        Err(e) => {
+            error!("error during attachment validation: {:?}", e);
+            return Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedAttachmentUpload,
+            );
+        }
+    };
+
+    match service.add_comment(id.0, user.0.id, user.0.role, form.comment.0, form.internal) {
+        Ok(comment_id) => {
+            match add_attachments(&conn, &config, id.0, Some(comment_id), user.0.id, files) {
+                Ok(()) => Flash::success(
+                    Redirect::to(uri!("/tickets", edit: id)),
+                    MessageCode::CommentCreated,
+                ),
+                Err(e) => {
+                    error!("error during attachment upload: {:?}", e);
+                    Flash::error(
+                        Redirect::to(uri!("/tickets", edit: id)),
+                        MessageCode::FailedAttachmentUpload,
+                    )
+                }
+            }
+        }
         Err(e) => {
             error!("error during comment creation: {:?}", e);
             Flash::error(
@@ -295,128 +788,1383 @@ pub fn post_add_comment(
     }
 }
 
-/// Endpoint to forward a ticket to its course's author.
-#[get("/<id>/forward", rank = 2)]
-pub fn forward(
-    _user: TutorUser<'_>,
+/// Form data for the Markdown preview.
+#[derive(FromForm)]
+pub struct Preview {
+    text: String,
+}
+
+/// Render Markdown formatted text the same way as descriptions and comments are shown, for the
+/// preview within forms.
+#[post("/preview", data = "<data>")]
+pub fn preview(_user: StudentUser<'_>, data: Form<Preview>) -> content::Html<String> {
+    content::Html(markdown::to_html(&data.text))
+}
+
+/// Form data for the comment edit form.
+#[derive(FromForm)]
+pub struct EditComment {
+    message: NonEmptyString,
+}
+
+/// Endpoint to change the message of a comment, which writers can do for a short while after
+/// writing it.
+#[post("/<id>/comments/<comment>/edit", data = "<data>")]
+pub fn post_edit_comment(
+    user: StudentUser<'_>,
     id: PositiveId,
+    comment: PositiveId,
+    data: Form<EditComment>,
     conn: DbConn,
     config: State<'_, Config>,
 ) -> Flash<Redirect> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    match service.forward(id.0) {
+    match service.edit_comment(
+        id.0,
+        comment.0,
+        user.0.id,
+        data.0.message.0,
+        config.tickets.comment_edit_window,
+    ) {
         Ok(()) => Flash::success(
             Redirect::to(uri!("/tickets", edit: id)),
-            MessageCode::TicketUpdated,
+            MessageCode::CommentUpdated,
         ),
         Err(e) => {
-            error!("error during ticket forwarding: {:?}", e);
+            error!("error during comment update: {:?}", e);
             Flash::error(
                 Redirect::to(uri!("/tickets", edit: id)),
-                MessageCode::FailedTicketUpdate,
+                MessageCode::FailedCommentUpdate,
             )
         }
     }
 }
 
-/// Endpoint to change a ticket's status.
-#[get("/<id>/status/<status>")]
-pub fn change_status(
-    _user: TutorUser<'_>,
+/// Endpoint to delete the message of a comment, keeping the original in the comment's history.
+#[post("/<id>/comments/<comment>/delete")]
+pub fn post_delete_comment(
+    user: AdminUser<'_>,
     id: PositiveId,
-    status: Status,
+    comment: PositiveId,
     conn: DbConn,
     config: State<'_, Config>,
 ) -> Flash<Redirect> {
     let service = services::ticket_service(
         repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
         repositories::course_repo(&conn),
         repositories::user_repo(&conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    match service.change_status(id.0, status) {
+    match service.delete_comment(id.0, comment.0, user.0.id, user.0.role) {
         Ok(()) => Flash::success(
             Redirect::to(uri!("/tickets", edit: id)),
-            MessageCode::TicketUpdated,
+            MessageCode::CommentDeleted,
         ),
         Err(e) => {
-            error!("error during ticket status change: {:?}", e);
+            error!("error during comment deletion: {:?}", e);
             Flash::error(
                 Redirect::to(uri!("/tickets", edit: id)),
-                MessageCode::FailedTicketUpdate,
+                MessageCode::FailedCommentDeletion,
             )
         }
     }
 }
 
-/// Form data for the ticket search form.
-#[derive(FromForm)]
-pub struct SearchOptions {
-    title: Option<String>,
-    course: Option<PositiveId>,
-    category: Option<Category>,
-    priority: Option<Priority>,
-    status: Option<Status>,
+/// Content of an attachment, sent together with its original file name.
+pub struct AttachmentFile {
+    attachment: Attachment,
+    data: Vec<u8>,
 }
 
-/// Ticket search page for all registered users.
-#[get("/search?<data..>")]
-pub fn search(
-    user: StudentUser<'_>,
-    data: Form<SearchOptions>,
-    conn: DbConn,
-    config: State<'_, Config>,
-) -> Result<templates::SearchTickets, ServerError> {
+impl<'r> Responder<'r> for AttachmentFile {
+    fn respond_to(self, _: &Request<'_>) -> response::Result<'r> {
+        // Only images are shown directly in the browser, everything else is downloaded.
+        let disposition = if self.attachment.is_image() {
+            "inline"
+        } else {
+            "attachment"
+        };
+        let file_name = form_urlencoded::byte_serialize(self.attachment.file_name.as_bytes())
+            .collect::<String>()
+            .replace('+', "%20");
+
+        Response::build()
+            .header(
+                ContentType::parse_flexible(&self.attachment.mime_type)
+                    .unwrap_or(ContentType::Binary),
+            )
+            .raw_header(
+                "Content-Disposition",
+                format!("{}; filename*=UTF-8''{}", disposition, file_name),
+            )
+            .raw_header("X-Content-Type-Options", "nosniff")
+            .sized_body(Cursor::new(self.data))
+            .ok()
+    }
+}
+
+/// Response of the [`attachment`] and [`attachment_thumbnail`] endpoints.
+#[derive(rocket::Responder)]
+pub enum AttachmentResponse {
+    File(AttachmentFile),
+    Forbidden(templates::Error403),
+}
+
+/// Load an attachment of a ticket, if the user is allowed to open the ticket.
+fn load_attachment(
+    user: &StudentUser<'_>,
+    id: Id,
+    attachment_id: Id,
+    thumbnail: bool,
+    conn: &DbConn,
+    config: &Config,
+) -> Result<AttachmentResponse> {
     let service = services::ticket_service(
-        repositories::ticket_repo(&conn),
-        repositories::course_repo(&conn),
-        repositories::user_repo(&conn),
+        repositories::ticket_repo(conn),
+        repositories::workflow_repo(conn),
+        repositories::course_repo(conn),
+        repositories::user_repo(conn),
         email::new_smtp_sender(&config.smtp),
         email::new_mail_renderer(&config.host),
     );
 
-    let mut search = TicketSearch {
-        title: data.0.title,
-        course_id: data.0.course.map(|c| c.0),
-        category: data.0.category,
-        priority: data.0.priority,
-        status: data.0.status,
-    };
+    if !service.can_open(id, user.0.id, user.0.role)? {
+        return Ok(AttachmentResponse::Forbidden(templates::Error403));
+    }
 
-    let tickets = service.search(user.0.role, &mut search)?;
+    let attachment_service = services::attachment_service(
+        repositories::attachment_repo(conn),
+        storage::new_disk_store(DIRS.attachment_dir()),
+        &config.attachments,
+    );
 
-    let courses = service.list_course_names()?;
+    let mut attachment = attachment_service.get(attachment_id, id)?;
 
-    Ok(templates::SearchTickets {
-        role: user.0.role,
-        user_id: user.0.id,
-        tickets,
-        courses,
-        search,
-    })
-}
+    // Attachments of internal comments are as hidden as the comments themselves.
+    if let Some(comment_id) = attachment.comment_id {
+        if user.0.role > Role::Tutor && service.get_comment(comment_id)?.internal {
+            return Ok(AttachmentResponse::Forbidden(templates::Error403));
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use pretty_assertions::assert_eq;
-    use rocket::http::Status;
-    use rocket::uri;
+    let data = if thumbnail {
+        attachment.mime_type = "image/png".to_owned();
+        attachment_service.load_thumbnail(attachment_id)?
+    } else {
+        attachment_service.load(attachment_id)?
+    };
 
-    use crate::tests::{check_form, prepare_logged_in_client};
+    Ok(AttachmentResponse::File(AttachmentFile {
+        attachment,
+        data,
+    }))
+}
 
-    #[test]
-    fn invalid_post_new_ticket() {
-        let client = prepare_logged_in_client("admin", "admin");
-        let uri = uri!("/tickets", super::post_new).to_string();
+/// Download an attachment of a ticket.
+#[get("/<id>/attachments/<attachment>")]
+pub fn attachment(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    attachment: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<AttachmentResponse, ServerError> {
+    load_attachment(&user, id.0, attachment.0, false, &conn, &config).map_err(Into::into)
+}
+
+/// Show the thumbnail of an image attachment.
+#[get("/<id>/attachments/<attachment>/thumbnail")]
+pub fn attachment_thumbnail(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    attachment: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<AttachmentResponse, ServerError> {
+    load_attachment(&user, id.0, attachment.0, true, &conn, &config).map_err(Into::into)
+}
+
+/// Endpoint to forward a ticket to its course's author.
+#[get("/<id>/forward", rank = 2)]
+pub fn forward(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.forward(id.0, user.0.id) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketUpdated,
+        ),
+        Err(e) => {
+            error!("error during ticket forwarding: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketUpdate,
+            )
+        }
+    }
+}
+
+/// Form data for the ticket return form.
+#[derive(FromForm)]
+pub struct ReturnTicket {
+    comment: NonEmptyString,
+}
+
+/// Endpoint to return a forwarded ticket to its course's tutor.
+#[post("/<id>/return", data = "<data>")]
+pub fn post_return(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<ReturnTicket>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.return_to_tutor(id.0, user.0.id, user.0.role, data.into_inner().comment.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketReturned,
+        ),
+        Err(e) => {
+            error!("error during ticket return: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketReturn,
+            )
+        }
+    }
+}
+
+/// Form data to pick the new ticket type on the ticket move page.
+#[derive(FromForm, UriDisplayQuery)]
+pub struct MoveOptions {
+    ty: Option<TicketType>,
+}
+
+/// Page to move a ticket into another course or ticket type. The new type defaults to the current
+/// one, so only the course changes unless another type is picked.
+#[get("/<id>/move?<data..>", rank = 2)]
+pub fn move_ticket(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<MoveOptions>,
+    conn: DbConn,
+    config: State<'_, Config>,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::MoveTicket, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+    let ticket = service.get_with_rels(id.0, user.0.id, user.0.role)?;
+    let courses = service.list_course_names()?;
+
+    Ok(templates::MoveTicket {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        ty: data.ty.unwrap_or(ticket.ticket.type_),
+        ticket,
+        courses,
+    })
+}
+
+/// Form data for the ticket move form. The medium fields are only sent if the new ticket type uses
+/// a different kind of medium.
+pub struct MoveData {
+    course: Id,
+    ty: TicketType,
+    medium: Option<NewMedium>,
+}
+
+impl<'f> FromForm<'f> for MoveData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut course = None;
+        let mut ty = None;
+        let mut medium = MediumFields::default();
+
+        for item in it {
+            if medium.parse(item)? {
+                continue;
+            }
+
+            match item.key.as_str() {
+                "course" => course = Some(parse_form_value::<PositiveId>(item)?.0),
+                "ty" => ty = Some(parse_form_value(item)?),
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        let ty = ty.ok_or_else(|| missing("ty"))?;
+
+        Ok(Self {
+            course: course.ok_or_else(|| missing("course"))?,
+            ty,
+            medium: if medium.is_empty() {
+                None
+            } else {
+                Some(medium.into_medium(ty)?)
+            },
+        })
+    }
+}
+
+/// Endpoint to move a ticket into another course or ticket type.
+#[post("/<id>/move", data = "<data>")]
+pub fn post_move(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<MoveData>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let data = data.into_inner();
+    let ty = data.ty;
+
+    match service.move_ticket(
+        MoveTicket {
+            id: id.0,
+            course_id: data.course,
+            type_: data.ty,
+            medium: data.medium,
+        },
+        user.0.id,
+        user.0.role,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketMoved,
+        ),
+        Err(e) => {
+            error!("error during ticket move: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!(
+                    "/tickets",
+                    move_ticket: id,
+                    MoveOptions { ty: Some(ty) }
+                )),
+                MessageCode::FailedTicketMove,
+            )
+        }
+    }
+}
+
+/// Form data for the ticket assignment form.
+#[derive(FromForm)]
+pub struct AssignTicket {
+    assignee: PositiveId,
+}
+
+/// Endpoint to assign a ticket to a tutor, author or admin.
+#[post("/<id>/assign", data = "<data>")]
+pub fn post_assign(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<AssignTicket>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.assign(id.0, data.0.assignee.0, user.0.id) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketAssigned,
+        ),
+        Err(e) => {
+            error!("error during ticket assignment: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketAssignment,
+            )
+        }
+    }
+}
+
+/// Form data for the ticket label form. Labels are selected with a `<select multiple>` element,
+/// which sends one form field per selected value.
+pub struct TicketLabels {
+    labels: Vec<Id>,
+}
+
+impl<'f> FromForm<'f> for TicketLabels {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut labels = Vec::new();
+
+        for item in it {
+            match item.key.as_str() {
+                "labels" => labels.push(parse_form_value::<PositiveId>(item)?.0),
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        Ok(Self { labels })
+    }
+}
+
+/// Endpoint to replace the labels of a ticket.
+#[post("/<id>/labels", data = "<data>")]
+pub fn post_labels(
+    _user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<TicketLabels>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.set_labels(id.0, data.0.labels) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketLabelsUpdated,
+        ),
+        Err(e) => {
+            error!("error during ticket label update: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketLabelUpdate,
+            )
+        }
+    }
+}
+
+/// Endpoint to assign a ticket to the current user.
+#[get("/<id>/assign/me")]
+pub fn assign_me(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.assign(id.0, user.0.id, user.0.id) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketAssigned,
+        ),
+        Err(e) => {
+            error!("error during ticket assignment: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketAssignment,
+            )
+        }
+    }
+}
+
+/// Queue of all unfinished tickets that aren't assigned to anyone yet, optionally filtered by
+/// course.
+#[get("/unassigned?<course>")]
+pub fn unassigned(
+    user: TutorUser<'_>,
+    course: Option<PositiveId>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<templates::UnassignedTickets, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let course_id = course.map(|c| c.0);
+    let tickets = service.list_unassigned(course_id)?;
+    let courses = service.list_course_names()?;
+    let overdue = service.list_overdue(&tickets, &config.tickets.sla);
+
+    Ok(templates::UnassignedTickets {
+        role: user.0.role,
+        tickets,
+        courses,
+        course_id,
+        overdue,
+    })
+}
+
+/// Endpoint to change a ticket's status.
+#[get("/<id>/status/<status>")]
+pub fn change_status(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    status: Status,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.change_status(id.0, status, user.0.id, user.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketUpdated,
+        ),
+        Err(e) => {
+            error!("error during ticket status change: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketUpdate,
+            )
+        }
+    }
+}
+
+/// Form data for the reopen form.
+#[derive(FromForm)]
+pub struct Reopen {
+    reason: NonEmptyString,
+}
+
+/// Endpoint for staff members to reopen a closed ticket.
+#[post("/<id>/reopen", data = "<data>")]
+pub fn post_reopen(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<Reopen>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.reopen(id.0, user.0.id, user.0.role, data.0.reason.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketReopened,
+        ),
+        Err(e) => {
+            error!("error during ticket reopening: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketReopen,
+            )
+        }
+    }
+}
+
+/// Endpoint for the creator of a closed ticket to ask for it to be reopened.
+#[post("/<id>/reopen-request", data = "<data>")]
+pub fn post_reopen_request(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    data: Form<Reopen>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.request_reopen(
+        id.0,
+        user.0.id,
+        user.0.role,
+        data.0.reason.0,
+        config.tickets.reopen_window,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::ReopenRequested,
+        ),
+        Err(e) => {
+            error!("error during reopen request: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedReopenRequest,
+            )
+        }
+    }
+}
+
+/// Endpoint to accept the pending reopen request of a ticket.
+#[post("/<id>/reopen-request/accept")]
+pub fn post_accept_reopen(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.accept_reopen(id.0, user.0.id, user.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketReopened,
+        ),
+        Err(e) => {
+            error!("error during reopen request acceptance: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketReopen,
+            )
+        }
+    }
+}
+
+/// Endpoint to refuse the pending reopen request of a ticket.
+#[post("/<id>/reopen-request/refuse", data = "<data>")]
+pub fn post_refuse_reopen(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<Reopen>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.refuse_reopen(id.0, user.0.id, user.0.role, data.0.reason.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::ReopenRefused,
+        ),
+        Err(e) => {
+            error!("error during reopen request refusal: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedReopenRefusal,
+            )
+        }
+    }
+}
+
+/// Form data for the duplicate form.
+#[derive(FromForm)]
+pub struct MarkDuplicate {
+    primary: PositiveId,
+}
+
+/// Endpoint to close a ticket as duplicate of another ticket and merge it into that one.
+#[post("/<id>/duplicate", data = "<data>")]
+pub fn post_duplicate(
+    user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<MarkDuplicate>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.mark_duplicate(id.0, data.primary.0, user.0.id, user.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: PositiveNum(data.primary.0))),
+            MessageCode::TicketMerged,
+        ),
+        Err(e) => {
+            error!("error during ticket merge: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketMerge,
+            )
+        }
+    }
+}
+
+/// Query data for the similar tickets lookup. The location fields are optional, as they might
+/// not be filled out yet, and are ignored if invalid.
+#[derive(FromForm)]
+pub struct SimilarOptions {
+    course: PositiveId,
+    ty: TicketType,
+    title: String,
+    page: Option<PositiveNum<u16>>,
+    line: Option<PositiveNum<u16>>,
+    hour: Option<Hour>,
+    minute: Option<Minute>,
+    second: Option<Second>,
+    url: Option<ValidUrl>,
+    question: Option<PositiveNum<u16>>,
+}
+
+/// A single entry of the [`similar`] endpoint's response.
+#[derive(Serialize)]
+struct SimilarEntry {
+    id: Id,
+    title: String,
+    status: String,
+    score: f64,
+}
+
+/// Find open tickets that are similar to a ticket that is currently being created, returned as
+/// JSON list.
+#[get("/similar?<data..>")]
+pub fn similar(
+    _user: StudentUser<'_>,
+    data: Form<SimilarOptions>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<content::Json<String>, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let data = data.into_inner();
+    let location = match data.ty.medium() {
+        MediumType::Text => data.page.map(|page| NewMedium::Text {
+            locations: vec![Span {
+                start: TextPosition {
+                    page: page.0,
+                    line: data.line.map_or(1, |l| l.0),
+                },
+                end: None,
+            }],
+        }),
+        MediumType::Recording => match (data.hour, data.minute, data.second) {
+            (Some(hour), Some(minute), Some(second)) => Some(NewMedium::Recording {
+                locations: vec![Span {
+                    start: NaiveTime::from_hms(hour.0.into(), minute.0.into(), second.0.into()),
+                    end: None,
+                }],
+            }),
+            _ => None,
+        },
+        MediumType::Interactive => data
+            .url
+            .map(|url| NewMedium::Interactive { urls: vec![url.0] }),
+        MediumType::Questionaire => data.question.map(|question| NewMedium::Questionaire {
+            locations: vec![QuestionLocation {
+                questions: Span {
+                    start: question.0,
+                    end: None,
+                },
+                answer: String::new(),
+            }],
+        }),
+    };
+
+    let similar = service
+        .find_similar(&SimilarSearch {
+            course_id: data.course.0,
+            type_: data.ty,
+            title: data.title,
+            location,
+        })?
+        .into_iter()
+        .map(|t| SimilarEntry {
+            id: t.ticket.ticket.id,
+            title: t.ticket.ticket.title,
+            status: t.ticket.status.name,
+            score: t.score,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(content::Json(
+        serde_json::to_string(&similar).map_err(anyhow::Error::from)?,
+    ))
+}
+
+/// Endpoint to join an existing ticket, for users that have the same issue.
+#[post("/<id>/me-too")]
+pub fn post_me_too(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    // Joining a ticket doesn't grant access to its details, so users go back to the overview.
+    match service.me_too(
+        id.0,
+        user.0.id,
+        user.0.role,
+        &config.tickets.vote_thresholds,
+    ) {
+        Ok(()) => Flash::success(Redirect::to(uri!(super::index)), MessageCode::TicketJoined),
+        Err(e) => {
+            error!("error during joining a ticket: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!(super::index)),
+                MessageCode::FailedTicketJoin,
+            )
+        }
+    }
+}
+
+/// Endpoint to start watching a ticket and get notified about its changes.
+#[post("/<id>/watch")]
+pub fn post_watch(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.watch(id.0, user.0.id, user.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketWatched,
+        ),
+        Err(e) => {
+            error!("error during watching a ticket: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketWatch,
+            )
+        }
+    }
+}
+
+/// Endpoint to stop watching a ticket.
+#[post("/<id>/unwatch")]
+pub fn post_unwatch(
+    user: StudentUser<'_>,
+    id: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.unwatch(id.0, user.0.id) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::TicketUnwatched,
+        ),
+        Err(e) => {
+            error!("error during unwatching a ticket: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedTicketUnwatch,
+            )
+        }
+    }
+}
+
+/// Form data for the link creation form.
+#[derive(FromForm)]
+pub struct AddLink {
+    kind: LinkType,
+    target: PositiveId,
+}
+
+/// Endpoint to link a ticket to another ticket.
+#[post("/<id>/links", data = "<data>")]
+pub fn post_link(
+    _user: TutorUser<'_>,
+    id: PositiveId,
+    data: Form<AddLink>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.add_link(id.0, data.target.0, data.kind) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::LinkCreated,
+        ),
+        Err(e) => {
+            error!("error during link creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedLinkCreation,
+            )
+        }
+    }
+}
+
+/// Endpoint to remove a link between two tickets.
+#[post("/<id>/links/<link>/delete")]
+pub fn post_delete_link(
+    _user: TutorUser<'_>,
+    id: PositiveId,
+    link: PositiveId,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Flash<Redirect> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    match service.remove_link(id.0, link.0) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/tickets", edit: id)),
+            MessageCode::LinkDeleted,
+        ),
+        Err(e) => {
+            error!("error during link deletion: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/tickets", edit: id)),
+                MessageCode::FailedLinkDeletion,
+            )
+        }
+    }
+}
+
+/// A single entry of the [`lookup`] endpoint's response.
+#[derive(Serialize)]
+struct LookupEntry {
+    id: Id,
+    title: String,
+}
+
+/// Look up tickets by ID or title for the link form's autocompletion, returned as JSON list.
+#[get("/lookup?<term>")]
+pub fn lookup(
+    _user: TutorUser<'_>,
+    term: String,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<content::Json<String>, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let found = service
+        .lookup(&term)?
+        .into_iter()
+        .map(|(id, title)| LookupEntry { id, title })
+        .collect::<Vec<_>>();
+
+    Ok(content::Json(
+        serde_json::to_string(&found).map_err(anyhow::Error::from)?,
+    ))
+}
+
+/// A single entry of the [`mentions`] endpoint's response.
+#[derive(Serialize)]
+struct MentionEntry {
+    username: String,
+    name: String,
+}
+
+/// Look up users by username or name for the comment form's mention autocompletion, returned as
+/// JSON list.
+#[get("/mentions?<term>")]
+pub fn mentions(
+    user: StudentUser<'_>,
+    term: String,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<content::Json<String>, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let found = service
+        .search_mentionable(&term, user.0.role)?
+        .into_iter()
+        .map(|(username, name)| MentionEntry { username, name })
+        .collect::<Vec<_>>();
+
+    Ok(content::Json(
+        serde_json::to_string(&found).map_err(anyhow::Error::from)?,
+    ))
+}
+
+/// Form data for the ticket search form. Labels are selected with a `<select multiple>` element,
+/// which sends one form field per selected value. Invalid values are ignored, the same way as for
+/// any other optional field.
+pub struct SearchOptions {
+    title: Option<String>,
+    program: Option<PositiveId>,
+    course: Option<PositiveId>,
+    category: Option<Category>,
+    priority: Option<Priority>,
+    status: Option<Status>,
+    labels: Vec<Id>,
+    order: Option<TicketOrder>,
+}
+
+impl<'f> FromForm<'f> for SearchOptions {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut options = Self {
+            title: None,
+            program: None,
+            course: None,
+            category: None,
+            priority: None,
+            status: None,
+            labels: Vec::new(),
+            order: None,
+        };
+
+        for item in it {
+            match item.key.as_str() {
+                "title" => options.title = parse_form_value(item)?,
+                "program" => options.program = parse_form_value(item)?,
+                "course" => options.course = parse_form_value(item)?,
+                "category" => options.category = parse_form_value(item)?,
+                "priority" => options.priority = parse_form_value(item)?,
+                "status" => options.status = parse_form_value(item)?,
+                "labels" => options
+                    .labels
+                    .extend(parse_form_value::<Option<PositiveId>>(item)?.map(|l| l.0)),
+                "order" => options.order = parse_form_value(item)?,
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Ticket search page for all registered users.
+#[get("/search?<data..>")]
+pub fn search(
+    user: StudentUser<'_>,
+    data: Form<SearchOptions>,
+    origin: &Origin<'_>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<templates::SearchTickets, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let mut search = TicketSearch {
+        title: data.0.title,
+        program_id: data.0.program.map(|p| p.0),
+        course_id: data.0.course.map(|c| c.0),
+        category: data.0.category,
+        priority: data.0.priority,
+        status: data.0.status,
+        active_only: false,
+        labels: data.0.labels,
+        order: data.0.order,
+    };
+
+    let tickets = service.search(user.0.role, &mut search)?;
+    let overdue = service.list_overdue(&tickets, &config.tickets.sla);
+    let media = service.list_media(&tickets)?;
+
+    let courses = service.list_course_names()?;
+    let statuses = service.workflow()?.statuses;
+    let assignees = if user.0.role < Role::Student {
+        service.list_assignees()?
+    } else {
+        Vec::new()
+    };
+    let labels = services::label_service(
+        repositories::label_repo(&conn),
+        repositories::course_repo(&conn),
+    )
+    .list()?;
+    let programs = services::program_service(
+        repositories::program_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+    )
+    .list_names()?;
+
+    Ok(templates::SearchTickets {
+        role: user.0.role,
+        user_id: user.0.id,
+        tickets,
+        courses,
+        programs,
+        statuses,
+        assignees,
+        labels,
+        search,
+        query: origin.query().unwrap_or_default().to_owned(),
+        overdue,
+        media,
+    })
+}
+
+/// The chosen action of the bulk action form, together with its value.
+enum BulkKind {
+    Status(Status),
+    Priority(Priority),
+    Forward,
+    Assign(Id),
+    Label(Id),
+}
+
+/// Form data for the bulk action form on the ticket search page. Tickets are selected with
+/// checkboxes, which send one form field per checked ticket. Only the value that belongs to the
+/// chosen action is required.
+pub struct BulkData {
+    tickets: Vec<Id>,
+    kind: BulkKind,
+    query: String,
+}
+
+impl<'f> FromForm<'f> for BulkData {
+    type Error = FormParseError<'f>;
+
+    fn from_form(it: &mut FormItems<'f>, strict: bool) -> Result<Self, Self::Error> {
+        let mut tickets = Vec::new();
+        let mut action = None;
+        let mut status = None;
+        let mut priority = None;
+        let mut assignee = None;
+        let mut label = None;
+        let mut query = String::new();
+
+        for item in it {
+            match item.key.as_str() {
+                "tickets" => tickets.push(parse_form_value::<PositiveId>(item)?.0),
+                "action" => action = Some((item.key, item.value)),
+                "status" => status = parse_form_value(item)?,
+                "priority" => priority = parse_form_value(item)?,
+                "assignee" => assignee = parse_form_value::<Option<PositiveId>>(item)?,
+                "label" => label = parse_form_value::<Option<PositiveId>>(item)?,
+                "query" => query = parse_form_value(item)?,
+                _ if strict => return Err(FormParseError::Unknown(item.key, item.value)),
+                _ => {}
+            }
+        }
+
+        if tickets.is_empty() {
+            return Err(missing("tickets"));
+        }
+
+        let (key, value) = action.ok_or_else(|| missing("action"))?;
+        let kind = match value.as_str() {
+            "status" => BulkKind::Status(status.ok_or_else(|| missing("status"))?),
+            "priority" => BulkKind::Priority(priority.ok_or_else(|| missing("priority"))?),
+            "forward" => BulkKind::Forward,
+            "assign" => BulkKind::Assign(assignee.ok_or_else(|| missing("assignee"))?.0),
+            "label" => BulkKind::Label(label.ok_or_else(|| missing("label"))?.0),
+            _ => return Err(FormParseError::BadValue(key, value)),
+        };
+
+        Ok(Self {
+            tickets,
+            kind,
+            query,
+        })
+    }
+}
+
+/// Endpoint to apply an action to all selected tickets of the search page at once. Instead of a
+/// redirect, it shows which tickets were changed and why the others failed.
+#[post("/bulk", data = "<data>")]
+pub fn post_bulk(
+    user: TutorUser<'_>,
+    data: Form<BulkData>,
+    conn: DbConn,
+    config: State<'_, Config>,
+) -> Result<templates::BulkTickets, ServerError> {
+    let service = services::ticket_service(
+        repositories::ticket_repo(&conn),
+        repositories::workflow_repo(&conn),
+        repositories::course_repo(&conn),
+        repositories::user_repo(&conn),
+        email::new_smtp_sender(&config.smtp),
+        email::new_mail_renderer(&config.host),
+    );
+
+    let BulkData {
+        tickets,
+        kind,
+        query,
+    } = data.0;
+
+    let action = match kind {
+        BulkKind::Status(status) => BulkAction::Status(status),
+        BulkKind::Priority(priority) => BulkAction::Priority(priority),
+        BulkKind::Forward => BulkAction::Forward,
+        BulkKind::Assign(assignee_id) => BulkAction::Assign(assignee_id),
+        BulkKind::Label(label_id) => BulkAction::AddLabel(
+            services::label_service(
+                repositories::label_repo(&conn),
+                repositories::course_repo(&conn),
+            )
+            .list()?
+            .into_iter()
+            .find(|l| l.id == label_id)
+            .context("Label doesn't exist")?,
+        ),
+    };
+
+    let outcome = service.bulk(&tickets, &action, user.0.id, user.0.role)?;
+
+    Ok(templates::BulkTickets {
+        role: user.0.role,
+        outcome,
+        query,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::{ContentType, Status};
+    use rocket::uri;
+
+    use super::MAX_LOCATIONS;
+    use crate::routes::{auth, courses, PositiveNum, MAX_FORM_FIELDS};
+    use crate::tests::{check_form, login, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_new_ticket() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_new).to_string();
 
         let data_list = &[
             "ty=&category=content&title=a&description=a&course=1&page=1&line=1",
@@ -433,6 +2181,321 @@ mod tests {
             "ty=interactive-book&category=content&title=a&description=a&course=1&url=",
             "ty=practice-exam&category=content&title=a&description=a&course=1&question=0&answer=a",
             "ty=practice-exam&category=content&title=a&description=a&course=1&question=1&answer=",
+            "ty=course-book&category=content&title=a&description=a&course=1&page=1&line=1&field-a=a",
+            "ty=course-book&category=content&title=a&description=a&course=1&page=2&line=1&end_page=1&end_line=9",
+            "ty=course-book&category=content&title=a&description=a&course=1&page=1&line=5&end_line=4",
+            "ty=vodcast&category=content&title=a&description=a&course=1&hour=0&minute=1&second=0&end_hour=0&end_minute=0&end_second=59",
+            "ty=practice-exam&category=content&title=a&description=a&course=1&question=3&end_question=2&answer=a",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_assign() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_assign: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "assignee=").status()
+        );
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "assignee=0").status()
+        );
+    }
+
+    #[test]
+    fn invalid_assign_me_id() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::assign_me: PositiveNum(0)).to_string();
+
+        assert_eq!(Status::NotFound, client.get(uri).dispatch().status());
+    }
+
+    #[test]
+    fn invalid_attachment_id() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::attachment: PositiveNum(1), PositiveNum(0)).to_string();
+
+        assert_eq!(Status::NotFound, client.get(uri).dispatch().status());
+    }
+
+    #[test]
+    fn me_too_foreign_ticket() {
+        let client = prepare_logged_in_client("admin", "admin");
+
+        // Tickets of inactive courses can't be found by students anymore.
+        let res = client
+            .get(uri!("/courses", courses::enable: PositiveNum(2), false).to_string())
+            .dispatch();
+        assert_eq!(Status::SeeOther, res.status());
+
+        client.post(uri!(auth::post_logout).to_string()).dispatch();
+        login(&client, "max.mustermann", "Mustermann");
+
+        let res = client
+            .post(uri!("/tickets", super::post_me_too: PositiveNum(2)).to_string())
+            .dispatch();
+        assert_eq!(Status::SeeOther, res.status());
+        assert_eq!(Some("/"), res.headers().get_one("Location"));
+        assert!(res
+            .headers()
+            .get("Set-Cookie")
+            .any(|cookie| cookie.contains("failed-ticket-join")));
+    }
+
+    #[test]
+    fn too_many_locations() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_new).to_string();
+
+        let data = (0..=MAX_LOCATIONS).fold(
+            "ty=course-book&category=content&title=a&description=a&course=1".to_owned(),
+            |data, i| format!("{}&page={}&line=1", data, i + 1),
+        );
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, &data).status()
+        );
+    }
+
+    #[test]
+    fn too_many_comment_fields() {
+        let client = prepare_logged_in_client("max.mustermann", "Mustermann");
+        let uri = uri!("/tickets", super::post_add_comment: PositiveNum(1)).to_string();
+
+        let mut body = (0..=MAX_FORM_FIELDS)
+            .map(|i| {
+                format!(
+                    "--boundary\r\nContent-Disposition: form-data; name=\"f{}\"\r\n\r\na\r\n",
+                    i
+                )
+            })
+            .collect::<String>();
+        body.push_str("--boundary--\r\n");
+
+        let res = client
+            .post(uri)
+            .header(ContentType::with_params(
+                "multipart",
+                "form-data",
+                ("boundary", "boundary"),
+            ))
+            .body(body)
+            .dispatch();
+
+        assert_eq!(Status::PayloadTooLarge, res.status());
+    }
+
+    #[test]
+    fn invalid_post_edit_content() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_edit_content: PositiveNum(1)).to_string();
+
+        let data_list = &[
+            "ty=&title=a&description=a&page=1&line=1",
+            "ty=course-book&title=&description=a&page=1&line=1",
+            "ty=course-book&title=a&description=&page=1&line=1",
+            "ty=course-book&title=a&description=a&page=0&line=1",
+            "ty=course-book&title=a&description=a&page=1",
+            "ty=course-book&title=a&description=a&page=1&line=1&page=2",
+            "ty=course-book&title=a&description=a&page=2&line=1&end_page=1&end_line=1",
+            "ty=course-book&title=a&description=a&page=1&line=1&end_page=2&end_line=",
+            "ty=vodcast&title=a&description=a&hour=24&minute=0&second=0",
+            "ty=vodcast&title=a&description=a&hour=0&minute=1&second=0&end_hour=0&end_minute=0&end_second=0",
+            "ty=vodcast&title=a&description=a&hour=0&minute=1&second=0&end_hour=0&end_minute=2&end_second=",
+            "ty=interactive-book&title=a&description=a&url=",
+            "ty=practice-exam&title=a&description=a&question=1&answer=",
+            "ty=practice-exam&title=a&description=a&question=3&end_question=2&answer=a",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_return() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_return: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "").status()
+        );
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "comment=").status()
+        );
+    }
+
+    #[test]
+    fn invalid_post_move() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_move: PositiveNum(1)).to_string();
+
+        let data_list = &[
+            "ty=course-book",
+            "course=0&ty=course-book",
+            "course=1&ty=",
+            "course=1",
+            "course=1&ty=course-book&page=1",
+            "course=1&ty=vodcast&page=1&line=1",
+            "course=1&ty=interactive-book&url=",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_duplicate() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_duplicate: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "primary=").status()
+        );
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "primary=0").status()
+        );
+    }
+
+    #[test]
+    fn invalid_preview() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::preview).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "other=a").status()
+        );
+    }
+
+    #[test]
+    fn invalid_post_edit_comment() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri =
+            uri!("/tickets", super::post_edit_comment: PositiveNum(1), PositiveNum(1)).to_string();
+
+        for data in &["message=", "other=a"] {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_comment_id() {
+        let client = prepare_logged_in_client("admin", "admin");
+
+        for uri in &[
+            uri!("/tickets", super::post_delete_comment: PositiveNum(0), PositiveNum(1))
+                .to_string(),
+            uri!("/tickets", super::post_delete_comment: PositiveNum(1), PositiveNum(0))
+                .to_string(),
+        ] {
+            assert_eq!(
+                Status::NotFound,
+                client.post(uri).dispatch().status(),
+                "uri = {}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_watch_id() {
+        let client = prepare_logged_in_client("admin", "admin");
+
+        for uri in &[
+            uri!("/tickets", super::post_watch: PositiveNum(0)).to_string(),
+            uri!("/tickets", super::post_unwatch: PositiveNum(0)).to_string(),
+        ] {
+            assert_eq!(
+                Status::NotFound,
+                client.post(uri).dispatch().status(),
+                "uri = {}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_link() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_link: PositiveNum(1)).to_string();
+
+        for data in &[
+            "kind=&target=2",
+            "kind=unknown&target=2",
+            "kind=blocks&target=0",
+        ] {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_labels() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_labels: PositiveNum(1)).to_string();
+
+        for data in &["labels=0", "labels=a", "labels=1&labels=", "label=1"] {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_bulk() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_bulk).to_string();
+
+        let data_list = &[
+            "action=forward",
+            "tickets=0&action=forward",
+            "tickets=a&action=forward",
+            "tickets=1",
+            "tickets=1&action=close",
+            "tickets=1&action=status",
+            "tickets=1&action=priority&priority=urgent",
+            "tickets=1&action=assign&assignee=0",
+            "tickets=1&action=label",
         ];
 
         for data in data_list {
@@ -444,4 +2507,69 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn invalid_similar() {
+        let client = prepare_logged_in_client("admin", "admin");
+
+        for query in &[
+            "course=0&ty=course-book&title=a",
+            "course=1&ty=&title=a",
+            "course=1&ty=course-book",
+        ] {
+            assert_eq!(
+                Status::NotFound,
+                client
+                    .get(format!("/tickets/similar?{}", query))
+                    .dispatch()
+                    .status(),
+                "query = {}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn valid_similar() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let mut res = client
+            .get("/tickets/similar?course=1&ty=course-book&title=Fehler&page=1&line=")
+            .dispatch();
+
+        assert_eq!(Status::Ok, res.status());
+        assert!(res.body_string().unwrap_or_default().starts_with('['));
+    }
+
+    #[test]
+    fn invalid_post_reopen() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_reopen: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "reason=").status()
+        );
+    }
+
+    #[test]
+    fn invalid_post_reopen_request() {
+        let client = prepare_logged_in_client("max.mustermann", "Mustermann");
+        let uri = uri!("/tickets", super::post_reopen_request: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "reason=").status()
+        );
+    }
+
+    #[test]
+    fn invalid_post_refuse_reopen() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/tickets", super::post_refuse_reopen: PositiveNum(1)).to_string();
+
+        assert_eq!(
+            Status::UnprocessableEntity,
+            check_form(&client, &uri, "reason=").status()
+        );
+    }
 }
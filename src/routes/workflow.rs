@@ -0,0 +1,251 @@
+//! Workflow related routes, to manage ticket statuses and the transitions between them.
+
+use anyhow::Result;
+use log::error;
+use rocket::request::{FlashMessage, Form, FromForm};
+use rocket::response::{Flash, Redirect};
+use rocket::{get, post, uri};
+
+use super::{NonEmptyString, ServerError};
+use crate::db::connection::DbConn;
+use crate::db::repositories;
+use crate::models::{Role, Status, StatusColor};
+use crate::roles::AdminUser;
+use crate::services::{self, WorkflowService};
+use crate::templates::{self, MessageCode};
+
+/// Workflow management page for administrators.
+#[get("/")]
+pub fn list(
+    user: AdminUser<'_>,
+    conn: DbConn,
+    flash: Option<FlashMessage<'_, '_>>,
+) -> Result<templates::EditWorkflow, ServerError> {
+    let service = services::workflow_service(repositories::workflow_repo(&conn));
+    let workflow = service.get()?;
+
+    Ok(templates::EditWorkflow {
+        role: user.0.role,
+        flash: flash.map(|f| {
+            (
+                f.name().to_owned(),
+                f.msg().parse().unwrap_or(MessageCode::Unknown),
+            )
+        }),
+        workflow,
+    })
+}
+
+/// Form data from the status creation form.
+#[derive(FromForm)]
+pub struct NewStatus {
+    key: Status,
+    name: NonEmptyString,
+    color: StatusColor,
+    icon: NonEmptyString,
+    terminal: bool,
+}
+
+/// New status POST endpoint to handle status creation.
+#[post("/statuses", data = "<data>")]
+pub fn post_new_status(
+    _user: AdminUser<'_>,
+    data: Form<NewStatus>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::workflow_service(repositories::workflow_repo(&conn));
+
+    match service.create_status(
+        data.0.key,
+        data.0.name.0,
+        data.0.color,
+        data.0.icon.0,
+        data.0.terminal,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/workflow", list)),
+            MessageCode::StatusCreated,
+        ),
+        Err(e) => {
+            error!("error during status creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/workflow", list)),
+                MessageCode::FailedStatusCreation,
+            )
+        }
+    }
+}
+
+/// Form data from the status editing form.
+#[derive(FromForm)]
+pub struct EditStatus {
+    name: NonEmptyString,
+    color: StatusColor,
+    icon: NonEmptyString,
+    terminal: bool,
+}
+
+/// Edit status POST endpoint to handle status editing.
+#[post("/statuses/<key>", data = "<data>")]
+pub fn post_edit_status(
+    _user: AdminUser<'_>,
+    key: Status,
+    data: Form<EditStatus>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::workflow_service(repositories::workflow_repo(&conn));
+
+    match service.update_status(
+        key,
+        data.0.name.0,
+        data.0.color,
+        data.0.icon.0,
+        data.0.terminal,
+    ) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/workflow", list)),
+            MessageCode::StatusUpdated,
+        ),
+        Err(e) => {
+            error!("error during status update: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/workflow", list)),
+                MessageCode::FailedStatusUpdate,
+            )
+        }
+    }
+}
+
+/// Form data from the transition creation and removal forms.
+#[derive(FromForm)]
+pub struct TransitionData {
+    from: Status,
+    to: Status,
+    role: Role,
+}
+
+/// New transition POST endpoint to allow a role to change between two statuses.
+#[post("/transitions", data = "<data>")]
+pub fn post_new_transition(
+    _user: AdminUser<'_>,
+    data: Form<TransitionData>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::workflow_service(repositories::workflow_repo(&conn));
+
+    match service.add_transition(data.0.from, data.0.to, data.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/workflow", list)),
+            MessageCode::TransitionCreated,
+        ),
+        Err(e) => {
+            error!("error during transition creation: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/workflow", list)),
+                MessageCode::FailedTransitionCreation,
+            )
+        }
+    }
+}
+
+/// Delete transition POST endpoint to remove a previously allowed status change.
+#[post("/transitions/delete", data = "<data>")]
+pub fn post_delete_transition(
+    _user: AdminUser<'_>,
+    data: Form<TransitionData>,
+    conn: DbConn,
+) -> Flash<Redirect> {
+    let service = services::workflow_service(repositories::workflow_repo(&conn));
+
+    match service.remove_transition(data.0.from, data.0.to, data.0.role) {
+        Ok(()) => Flash::success(
+            Redirect::to(uri!("/workflow", list)),
+            MessageCode::TransitionDeleted,
+        ),
+        Err(e) => {
+            error!("error during transition removal: {:?}", e);
+            Flash::error(
+                Redirect::to(uri!("/workflow", list)),
+                MessageCode::FailedTransitionDeletion,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rocket::http::Status;
+    use rocket::uri;
+
+    use crate::models::Status as TicketStatus;
+    use crate::tests::{check_form, prepare_logged_in_client};
+
+    #[test]
+    fn invalid_post_new_status() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/workflow", super::post_new_status).to_string();
+
+        let data_list = &[
+            "key=&name=a&color=info&icon=fa-a",
+            "key=A&name=a&color=info&icon=fa-a",
+            "key=a_b&name=a&color=info&icon=fa-a",
+            "key=a&name=&color=info&icon=fa-a",
+            "key=a&name=a&color=pink&icon=fa-a",
+            "key=a&name=a&color=info&icon=",
+            "key=a&name=a&color=info&icon=fa-a&terminal=maybe",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_edit_status() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/workflow", super::post_edit_status: TicketStatus::OPEN).to_string();
+
+        let data_list = &[
+            "name=&color=info&icon=fa-a",
+            "name=a&color=&icon=fa-a",
+            "name=a&color=info&icon=",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_post_new_transition() {
+        let client = prepare_logged_in_client("admin", "admin");
+        let uri = uri!("/workflow", super::post_new_transition).to_string();
+
+        let data_list = &[
+            "from=&to=open&role=tutor",
+            "from=open&to=&role=tutor",
+            "from=open&to=in-progress&role=",
+            "from=open&to=in-progress&role=nobody",
+        ];
+
+        for data in data_list {
+            assert_eq!(
+                Status::UnprocessableEntity,
+                check_form(&client, &uri, data).status(),
+                "data = {}",
+                data
+            );
+        }
+    }
+}
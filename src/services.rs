@@ -1,20 +1,42 @@
 //! Services of the application which contain the business logic.
 
+use std::io::Cursor;
 use std::iter;
 
-use anyhow::{ensure, Result};
-use chrono::Utc;
+use anyhow::{ensure, Context, Result};
+use chrono::{Duration, Utc};
+use fnv::{FnvHashMap, FnvHashSet};
+use image::ImageOutputFormat;
+use log::error;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
+use similar::{ChangeTag, TextDiff};
 
-use crate::db::repositories::{CourseRepository, TicketRepository, UserRepository};
-use crate::email::{CommentDetails, Mail, MailRenderer, MailSender, StatusDetails};
+use crate::config::{AttachmentConfig, SlaConfig};
+use crate::db::repositories::{
+    AttachmentRepository, CourseRepository, LabelRepository, ProgramRepository,
+    PublicReportRepository, RuleRepository, TemplateRepository, TicketRepository, UserRepository,
+    WorkflowRepository,
+};
+use crate::email::{
+    AssignmentDetails, CommentDetails, Mail, MailRenderer, MailSender, OverdueDetails,
+    StatusDetails,
+};
 use crate::hashing::Hasher;
+use crate::markdown;
 use crate::models::{
-    Category, Course, CourseWithNames, EditCourse, EditTicket, EditUser, Id, NewComment, NewCourse,
-    NewMedium, NewTicket, NewUser, Priority, Role, Status, Ticket, TicketSearch, TicketWithNames,
-    TicketWithRels, User,
+    Attachment, BulkAction, BulkError, BulkOutcome, Category, Comment, CommentRevisionWithNames,
+    Course, CourseTree, CourseWithNames, DiffKind, DiffPart, DueDates, EditCourse, EditProgram,
+    EditTicket, EditTicketContent, EditUser, EventField, Faculty, FacultyNode, Id, Label,
+    LabelWithNames, LinkType, Medium, MediumType, MoveTicket, NewAttachment, NewComment,
+    NewCommentRevision, NewCourse, NewLabel, NewMedium, NewProgram, NewPublicReport, NewRule,
+    NewTicket, NewTicketEvent, NewTicketLink, NewTicketRevision, NewUser, Priority, ProgramNode,
+    ProgramWithRels, PublicReport, PublicReportWithNames, RevisionDiff, Role, Rule, RuleOutcome,
+    RuleWithNames, SimilarSearch, SimilarTicket, Status, StatusColor, Ticket, TicketSearch,
+    TicketTemplate, TicketType, TicketWithNames, TicketWithRels, TimelineEntry, Transition, Upload,
+    User, ValidUpload, Workflow, WorkflowStatus,
 };
+use crate::storage::FileStore;
 
 /// The login service manages the user login. Logout is directly handled in the
 /// [`post_logout`](crate::routes::auth::post_logout) route because that logic is part of the
@@ -187,8 +209,15 @@ pub trait CourseService {
     fn create(&self, code: String, title: String, author_id: Id, tutor_id: Id) -> Result<()>;
     /// Enable or disable a course.
     fn enable(&self, id: Id, enable: bool) -> Result<()>;
-    /// Update the information of a course.
-    fn update(&self, id: Id, title: String, author_id: Id, tutor_id: Id) -> Result<()>;
+    /// Update the information of a course, including whether it accepts public reports.
+    fn update(
+        &self,
+        id: Id,
+        title: String,
+        author_id: Id,
+        tutor_id: Id,
+        public_reports: bool,
+    ) -> Result<()>;
 }
 
 /// Main implementation of [`CourseService`].
@@ -226,12 +255,20 @@ impl<UR: UserRepository, CR: CourseRepository> CourseService for CourseServiceIm
         self.course_repo.enable(id, enable).map_err(Into::into)
     }
 
-    fn update(&self, id: Id, title: String, author_id: Id, tutor_id: Id) -> Result<()> {
+    fn update(
+        &self,
+        id: Id,
+        title: String,
+        author_id: Id,
+        tutor_id: Id,
+        public_reports: bool,
+    ) -> Result<()> {
         self.course_repo.update(EditCourse {
             id,
             title,
             author_id,
             tutor_id,
+            public_reports,
         })
     }
 }
@@ -247,255 +284,3574 @@ pub fn course_service(
     }
 }
 
-/// The ticket service manages tickets of the system, like listing existing or adding new ones.
-pub trait TicketService {
-    /// List all tickets.
-    fn list(&self) -> Result<Vec<TicketWithNames>>;
-    /// List all tickets that were created by the given user.
-    fn list_created(&self, user_id: Id) -> Result<Vec<TicketWithNames>>;
-    /// List all tickets that are currently assigned to the given user.
-    fn list_assigned(&self, user_id: Id, role: Role) -> Result<Vec<TicketWithNames>>;
-    /// List all courses with ID and name.
-    fn list_course_names(&self) -> Result<Vec<(Id, String)>>;
-    /// Get a single ticket by its ID.
-    fn get(&self, id: Id) -> Result<TicketWithNames>;
-    /// Get a single ticket together with all relations. If the opening user is a tutor or author
-    /// and the ticket is still in [`Status::Open`] it will be changed to [`Status::InProgress`].
-    fn get_with_rels(&self, id: Id, user_id: Id, role: Role) -> Result<TicketWithRels>;
-    /// Create a new ticket in the system.
-    fn create(&self, ticket: NewTicket, medium: NewMedium) -> Result<Id>;
-    /// Add a new comment to a ticket.
-    fn add_comment(&self, id: Id, writer_id: Id, message: String) -> Result<()>;
-    /// Update the details of a ticket.
-    fn update(&self, id: Id, priority: Priority) -> Result<()>;
-    /// Forward a ticket to its course's author.
-    fn forward(&self, id: Id) -> Result<()>;
-    /// Change the current status of the ticket.
-    fn change_status(&self, id: Id, status: Status) -> Result<()>;
-    /// Search for tickets with different criteria.
-    fn search(&self, role: Role, search: &mut TicketSearch) -> Result<Vec<TicketWithNames>>;
-    /// Check whether the user can open a specific ticket.
-    fn can_open(&self, id: Id, user_id: Id, role: Role) -> Result<bool>;
+/// A list of courses and staff members with only their ID and name.
+///
+/// The first tuple element contains the courses and the second one the staff members.
+type VecCoursesStaff = (Vec<(i32, String)>, Vec<(i32, String)>);
+
+/// The program service manages faculties and study programs, which form a hierarchy above the
+/// courses.
+pub trait ProgramService {
+    /// Build the full hierarchy of faculties, programs and courses.
+    fn tree(&self) -> Result<CourseTree>;
+    /// Build the hierarchy only for the programs that the given user manages.
+    fn managed_tree(&self, user_id: Id) -> Result<CourseTree>;
+    /// List all faculties.
+    fn list_faculties(&self) -> Result<Vec<Faculty>>;
+    /// List all programs with ID and code.
+    fn list_names(&self) -> Result<Vec<(Id, String)>>;
+    /// Check whether the given user manages at least one program.
+    fn is_manager(&self, user_id: Id) -> Result<bool>;
+    /// List all courses and staff members with ID and name.
+    fn list_courses_staff(&self) -> Result<VecCoursesStaff>;
+    /// Get a single program by its ID.
+    fn get(&self, id: Id) -> Result<ProgramWithRels>;
+    /// Create a new faculty in the system.
+    fn create_faculty(&self, name: String) -> Result<()>;
+    /// Create a new program in the system.
+    fn create(
+        &self,
+        faculty_id: Id,
+        code: String,
+        title: String,
+        course_ids: Vec<Id>,
+        manager_ids: Vec<Id>,
+    ) -> Result<()>;
+    /// Update the information of a program.
+    fn update(
+        &self,
+        id: Id,
+        faculty_id: Id,
+        title: String,
+        course_ids: Vec<Id>,
+        manager_ids: Vec<Id>,
+    ) -> Result<()>;
 }
 
-/// Main implementation of [`TicketService`].
-struct TicketServiceImpl<TR, CR, UR, MS, MR>
+/// Main implementation of [`ProgramService`].
+struct ProgramServiceImpl<PR: ProgramRepository, CR: CourseRepository, UR: UserRepository> {
+    program_repo: PR,
+    course_repo: CR,
+    user_repo: UR,
+}
+
+impl<PR, CR, UR> ProgramServiceImpl<PR, CR, UR>
 where
-    TR: TicketRepository,
+    PR: ProgramRepository,
     CR: CourseRepository,
     UR: UserRepository,
-    MS: MailSender,
-    MR: MailRenderer,
 {
-    ticket_repo: TR,
-    course_repo: CR,
-    user_repo: UR,
-    mail_sender: MS,
-    mail_renderer: MR,
+    /// Build the course hierarchy. If `managed` is given, only these programs are included and
+    /// faculties without any of them are left out.
+    fn build_tree(&self, managed: Option<&[Id]>) -> Result<CourseTree> {
+        let courses = self.course_repo.list_with_names()?;
+        let assignments = self.program_repo.list_course_assignments()?;
+
+        let mut manager_names = FnvHashMap::<Id, Vec<String>>::default();
+        for (program_id, name) in self.program_repo.list_manager_names()? {
+            manager_names.entry(program_id).or_default().push(name);
+        }
+
+        let mut programs = FnvHashMap::<Id, Vec<ProgramNode>>::default();
+        for program in self.program_repo.list()? {
+            if managed.map_or(false, |ids| !ids.contains(&program.id)) {
+                continue;
+            }
+
+            let program_courses = courses
+                .iter()
+                .filter(|c| assignments.contains(&(c.course.id, program.id)))
+                .cloned()
+                .collect();
+
+            programs
+                .entry(program.faculty_id)
+                .or_default()
+                .push(ProgramNode {
+                    manager_names: manager_names.remove(&program.id).unwrap_or_default(),
+                    program,
+                    courses: program_courses,
+                });
+        }
+
+        let faculties = self
+            .program_repo
+            .list_faculties()?
+            .into_iter()
+            .filter_map(|faculty| {
+                let programs = programs.remove(&faculty.id).unwrap_or_default();
+
+                if managed.is_some() && programs.is_empty() {
+                    None
+                } else {
+                    Some(FacultyNode { faculty, programs })
+                }
+            })
+            .collect();
+
+        let unassigned = if managed.is_some() {
+            Vec::new()
+        } else {
+            courses
+                .into_iter()
+                .filter(|c| !assignments.iter().any(|(id, _)| *id == c.course.id))
+                .collect()
+        };
+
+        Ok(CourseTree {
+            faculties,
+            unassigned,
+        })
+    }
 }
 
-impl<TR, CR, UR, MS, MR> TicketServiceImpl<TR, CR, UR, MS, MR>
+impl<PR, CR, UR> ProgramService for ProgramServiceImpl<PR, CR, UR>
 where
-    TR: TicketRepository,
+    PR: ProgramRepository,
     CR: CourseRepository,
     UR: UserRepository,
-    MS: MailSender,
-    MR: MailRenderer,
 {
-    /// Decide the priority of a ticket based on its category.
-    fn map_priority(category: Category) -> Priority {
-        match category {
-            Category::Editorial => Priority::Medium,
-            Category::Content => Priority::High,
-            Category::Improvement | Category::Addition => Priority::Low,
-        }
+    fn tree(&self) -> Result<CourseTree> {
+        self.build_tree(None)
     }
 
-    /// Send an email about a ticket status change.
-    fn send_status_update(
+    fn managed_tree(&self, user_id: Id) -> Result<CourseTree> {
+        let managed = self.program_repo.list_managed_ids(user_id)?;
+
+        self.build_tree(Some(&managed))
+    }
+
+    fn list_faculties(&self) -> Result<Vec<Faculty>> {
+        self.program_repo.list_faculties()
+    }
+
+    fn list_names(&self) -> Result<Vec<(Id, String)>> {
+        self.program_repo.list_names()
+    }
+
+    fn is_manager(&self, user_id: Id) -> Result<bool> {
+        self.program_repo
+            .list_managed_ids(user_id)
+            .map(|ids| !ids.is_empty())
+    }
+
+    fn list_courses_staff(&self) -> Result<VecCoursesStaff> {
+        Ok((
+            self.course_repo.list_names()?,
+            self.user_repo.list_staff_names()?,
+        ))
+    }
+
+    fn get(&self, id: Id) -> Result<ProgramWithRels> {
+        self.program_repo.get_with_rels(id)
+    }
+
+    fn create_faculty(&self, name: String) -> Result<()> {
+        self.program_repo.create_faculty(name)
+    }
+
+    fn create(
         &self,
-        ticket: &Ticket,
-        creator: User,
-        old: Status,
-        new: Status,
+        faculty_id: Id,
+        code: String,
+        title: String,
+        course_ids: Vec<Id>,
+        manager_ids: Vec<Id>,
     ) -> Result<()> {
-        let (subject, message) = self.mail_renderer.status_change(
-            &creator.name,
-            StatusDetails {
-                ticket_title: &ticket.title,
-                ticket_id: ticket.id,
-                old_status: old,
-                new_status: new,
-            },
-        );
+        self.program_repo.create(NewProgram {
+            faculty_id,
+            code,
+            title,
+            course_ids,
+            manager_ids,
+        })
+    }
 
-        self.mail_sender.send(Mail {
-            from: ("amelio@dnaka91.rocks", "Amelio"),
-            to: (
-                &format!("{}@iubh-fernstudium.de", creator.username),
-                &creator.name,
-            ),
-            subject,
-            message: &message,
+    fn update(
+        &self,
+        id: Id,
+        faculty_id: Id,
+        title: String,
+        course_ids: Vec<Id>,
+        manager_ids: Vec<Id>,
+    ) -> Result<()> {
+        self.program_repo.update(EditProgram {
+            id,
+            faculty_id,
+            title,
+            course_ids,
+            manager_ids,
         })
     }
+}
 
-    /// Send an email about a new comment for a ticket.
-    fn send_comment_update(
+/// Create a new program service.
+pub fn program_service(
+    program_repo: impl ProgramRepository,
+    course_repo: impl CourseRepository,
+    user_repo: impl UserRepository,
+) -> impl ProgramService {
+    ProgramServiceImpl {
+        program_repo,
+        course_repo,
+        user_repo,
+    }
+}
+
+/// The workflow service manages the statuses of tickets and the transitions between them.
+pub trait WorkflowService {
+    /// Get the full workflow with all statuses and transitions.
+    fn get(&self) -> Result<Workflow>;
+    /// Create a new status in the workflow.
+    fn create_status(
         &self,
-        ticket: &Ticket,
-        creator: User,
-        writer: User,
-        comment: &str,
+        key: Status,
+        name: String,
+        color: StatusColor,
+        icon: String,
+        terminal: bool,
+    ) -> Result<()>;
+    /// Update the display details of a status.
+    fn update_status(
+        &self,
+        key: Status,
+        name: String,
+        color: StatusColor,
+        icon: String,
+        terminal: bool,
+    ) -> Result<()>;
+    /// Allow the given role to change tickets from one status to another.
+    fn add_transition(&self, from: Status, to: Status, role: Role) -> Result<()>;
+    /// Remove a previously allowed transition.
+    fn remove_transition(&self, from: Status, to: Status, role: Role) -> Result<()>;
+}
+
+/// Main implementation of [`WorkflowService`].
+struct WorkflowServiceImpl<WR: WorkflowRepository> {
+    workflow_repo: WR,
+}
+
+impl<WR: WorkflowRepository> WorkflowService for WorkflowServiceImpl<WR> {
+    fn get(&self) -> Result<Workflow> {
+        self.workflow_repo.get()
+    }
+
+    fn create_status(
+        &self,
+        key: Status,
+        name: String,
+        color: StatusColor,
+        icon: String,
+        terminal: bool,
     ) -> Result<()> {
-        let (subject, message) = self.mail_renderer.new_comment(
-            &creator.name,
-            CommentDetails {
-                ticket_title: &ticket.title,
-                ticket_id: ticket.id,
-                comment,
-                writer_name: &writer.name,
-            },
+        self.workflow_repo.create_status(WorkflowStatus {
+            key,
+            name,
+            color,
+            icon,
+            terminal,
+        })
+    }
+
+    fn update_status(
+        &self,
+        key: Status,
+        name: String,
+        color: StatusColor,
+        icon: String,
+        terminal: bool,
+    ) -> Result<()> {
+        // New tickets must always be editable.
+        ensure!(
+            !(terminal && key == Status::OPEN),
+            "The initial status can't be terminal"
         );
 
-        self.mail_sender.send(Mail {
-            from: ("amelio@dnaka91.rocks", "Amelio"),
-            to: (
-                &format!("{}@iubh-fernstudium.de", creator.username),
-                &creator.name,
-            ),
-            subject,
-            message: &message,
+        self.workflow_repo.update_status(WorkflowStatus {
+            key,
+            name,
+            color,
+            icon,
+            terminal,
         })
     }
+
+    fn add_transition(&self, from: Status, to: Status, role: Role) -> Result<()> {
+        ensure!(from != to, "Transition must change the status");
+        ensure!(
+            role <= Role::Tutor,
+            "Only staff members can change statuses"
+        );
+
+        let workflow = self.workflow_repo.get()?;
+        ensure!(
+            workflow.status(&from).is_some() && workflow.status(&to).is_some(),
+            "Unknown status in transition"
+        );
+        ensure!(
+            !workflow.can_change(&from, &to, role),
+            "Transition already exists"
+        );
+
+        self.workflow_repo
+            .add_transition(Transition { from, to, role })
+    }
+
+    fn remove_transition(&self, from: Status, to: Status, role: Role) -> Result<()> {
+        self.workflow_repo
+            .remove_transition(Transition { from, to, role })
+    }
+}
+
+/// Create a new workflow service.
+pub fn workflow_service(workflow_repo: impl WorkflowRepository) -> impl WorkflowService {
+    WorkflowServiceImpl { workflow_repo }
+}
+
+/// The rule service manages the rules that decide the priority and routing of new tickets.
+pub trait RuleService {
+    /// List all rules, in the order they're evaluated.
+    fn list(&self) -> Result<Vec<Rule>>;
+    /// List all rules together with their course and assignee names.
+    fn list_with_names(&self) -> Result<Vec<RuleWithNames>>;
+    /// List all courses and staff members with ID and name.
+    fn list_courses_staff(&self) -> Result<VecCoursesStaff>;
+    /// Create a new rule in the system.
+    fn create(&self, rule: NewRule) -> Result<()>;
+    /// Delete an existing rule.
+    fn delete(&self, id: Id) -> Result<()>;
+    /// Evaluate all rules for a sample ticket, without creating it.
+    fn test(&self, ticket: &NewTicket) -> Result<RuleOutcome>;
+}
+
+/// Main implementation of [`RuleService`].
+struct RuleServiceImpl<RR: RuleRepository, CR: CourseRepository, UR: UserRepository> {
+    rule_repo: RR,
+    course_repo: CR,
+    user_repo: UR,
 }
 
-impl<TR, CR, UR, MS, MR> TicketService for TicketServiceImpl<TR, CR, UR, MS, MR>
+impl<RR, CR, UR> RuleService for RuleServiceImpl<RR, CR, UR>
 where
-    TR: TicketRepository,
+    RR: RuleRepository,
     CR: CourseRepository,
     UR: UserRepository,
-    MS: MailSender,
-    MR: MailRenderer,
 {
-    fn list(&self) -> Result<Vec<TicketWithNames>> {
-        self.ticket_repo.list_with_names()
+    fn list(&self) -> Result<Vec<Rule>> {
+        self.rule_repo.list()
     }
 
-    fn list_created(&self, user_id: Id) -> Result<Vec<TicketWithNames>> {
-        self.ticket_repo.list_by_creator_id(user_id)
+    fn list_with_names(&self) -> Result<Vec<RuleWithNames>> {
+        self.rule_repo.list_with_names()
     }
 
-    fn list_assigned(&self, user_id: Id, role: Role) -> Result<Vec<TicketWithNames>> {
-        if role <= Role::Tutor {
-            self.ticket_repo.list_by_assignee_id(user_id)
-        } else {
-            Ok(Vec::new())
+    fn list_courses_staff(&self) -> Result<VecCoursesStaff> {
+        Ok((
+            self.course_repo.list_names()?,
+            self.user_repo.list_staff_names()?,
+        ))
+    }
+
+    fn create(&self, rule: NewRule) -> Result<()> {
+        ensure!(
+            rule.priority.is_some()
+                || rule.forward
+                || rule.assignee_id.is_some()
+                || !rule.label_ids.is_empty(),
+            "Rule must have at least one action"
+        );
+
+        if let Some(assignee_id) = rule.assignee_id {
+            let assignee = self.user_repo.find(assignee_id)?;
+            ensure!(
+                assignee.role <= Role::Tutor,
+                "User can't be assigned to tickets"
+            );
         }
+
+        self.rule_repo.create(rule)
     }
 
-    fn list_course_names(&self) -> Result<Vec<(Id, String)>> {
-        self.course_repo.list_names()
+    fn delete(&self, id: Id) -> Result<()> {
+        self.rule_repo.delete(id)
     }
 
-    fn get(&self, id: Id) -> Result<TicketWithNames> {
-        self.ticket_repo.get_with_names(id)
+    fn test(&self, ticket: &NewTicket) -> Result<RuleOutcome> {
+        let rules = self.rule_repo.list()?;
+
+        Ok(RuleOutcome::evaluate(&rules, ticket))
     }
+}
 
-    fn get_with_rels(&self, id: Id, user_id: Id, role: Role) -> Result<TicketWithRels> {
-        // If we open a ticket as tutor or author, update the status first.
-        let activated = if role == Role::Tutor || role == Role::Author {
-            self.ticket_repo.activate_ticket(id, user_id)?
-        } else {
-            false
-        };
+/// Create a new rule service.
+pub fn rule_service(
+    rule_repo: impl RuleRepository,
+    course_repo: impl CourseRepository,
+    user_repo: impl UserRepository,
+) -> impl RuleService {
+    RuleServiceImpl {
+        rule_repo,
+        course_repo,
+        user_repo,
+    }
+}
 
-        let ticket = self.ticket_repo.get_with_rels(id)?;
+/// The label service manages the labels that staff members can assign to tickets.
+pub trait LabelService {
+    /// List all labels.
+    fn list(&self) -> Result<Vec<Label>>;
+    /// List all labels together with the names of their courses.
+    fn list_with_names(&self) -> Result<Vec<LabelWithNames>>;
+    /// List all labels that can be assigned to the tickets of a course.
+    fn list_for_course(&self, course_id: Id) -> Result<Vec<Label>>;
+    /// List all courses with ID and name.
+    fn list_courses(&self) -> Result<Vec<(Id, String)>>;
+    /// Create a new label, either globally or for a single course.
+    fn create(&self, label: NewLabel) -> Result<()>;
+    /// Delete an existing label.
+    fn delete(&self, id: Id) -> Result<()>;
+}
 
-        if activated {
-            let creator = self.user_repo.find(ticket.ticket.creator_id)?;
-            self.send_status_update(&ticket.ticket, creator, Status::Open, ticket.ticket.status)?;
-        }
+/// Main implementation of [`LabelService`].
+struct LabelServiceImpl<LR: LabelRepository, CR: CourseRepository> {
+    label_repo: LR,
+    course_repo: CR,
+}
 
-        Ok(ticket)
+impl<LR, CR> LabelService for LabelServiceImpl<LR, CR>
+where
+    LR: LabelRepository,
+    CR: CourseRepository,
+{
+    fn list(&self) -> Result<Vec<Label>> {
+        self.label_repo.list()
     }
 
-    fn create(&self, ticket: NewTicket, medium: NewMedium) -> Result<Id> {
-        let priority = Self::map_priority(ticket.category);
-
-        self.ticket_repo.create(ticket, priority, medium)
+    fn list_with_names(&self) -> Result<Vec<LabelWithNames>> {
+        self.label_repo.list_with_names()
     }
 
-    fn add_comment(&self, id: Id, writer_id: Id, message: String) -> Result<()> {
-        self.ticket_repo.add_comment(NewComment {
-            ticket_id: id,
-            creator_id: writer_id,
-            timestamp: Utc::now(),
-            message: message.clone(),
-        })?;
+    fn list_for_course(&self, course_id: Id) -> Result<Vec<Label>> {
+        self.label_repo.list_for_course(course_id)
+    }
 
-        let creator = self.user_repo.find_ticket_creator(id)?;
+    fn list_courses(&self) -> Result<Vec<(Id, String)>> {
+        self.course_repo.list_names()
+    }
 
-        // We don't want emails for our own comments
-        if creator.id == writer_id {
-            return Ok(());
+    fn create(&self, label: NewLabel) -> Result<()> {
+        if let Some(course_id) = label.course_id {
+            self.course_repo.get(course_id)?;
         }
 
-        let writer = self.user_repo.find(writer_id)?;
-        let ticket = self.ticket_repo.get(id)?;
+        self.label_repo.create(label)
+    }
+
+    fn delete(&self, id: Id) -> Result<()> {
+        self.label_repo.delete(id)
+    }
+}
 
-        self.send_comment_update(&ticket, creator, writer, &message)
+/// Create a new label service.
+pub fn label_service(
+    label_repo: impl LabelRepository,
+    course_repo: impl CourseRepository,
+) -> impl LabelService {
+    LabelServiceImpl {
+        label_repo,
+        course_repo,
     }
+}
 
-    fn update(&self, id: Id, priority: Priority) -> Result<()> {
-        self.ticket_repo.update(EditTicket { id, priority })
+/// The template service manages the intake templates that guide users when creating tickets.
+pub trait TemplateService {
+    /// List the templates of all ticket types, using an empty one for types without a template.
+    fn list(&self) -> Result<Vec<TicketTemplate>>;
+    /// Get the template of a single ticket type, or an empty one if there is none.
+    fn get(&self, ty: TicketType) -> Result<TicketTemplate>;
+    /// Replace the template of a ticket type.
+    fn update(&self, template: TicketTemplate) -> Result<()>;
+}
+
+/// Main implementation of [`TemplateService`].
+struct TemplateServiceImpl<TR: TemplateRepository> {
+    template_repo: TR,
+}
+
+impl<TR: TemplateRepository> TemplateService for TemplateServiceImpl<TR> {
+    fn list(&self) -> Result<Vec<TicketTemplate>> {
+        let mut templates = self.template_repo.list()?;
+
+        Ok(TicketType::ALL
+            .iter()
+            .map(|&ty| {
+                templates
+                    .iter()
+                    .position(|t| t.type_ == ty)
+                    .map_or_else(|| TicketTemplate::empty(ty), |i| templates.swap_remove(i))
+            })
+            .collect())
     }
 
-    fn forward(&self, id: Id) -> Result<()> {
-        self.ticket_repo.forward(id)
+    fn get(&self, ty: TicketType) -> Result<TicketTemplate> {
+        Ok(self
+            .template_repo
+            .find(ty)?
+            .unwrap_or_else(|| TicketTemplate::empty(ty)))
     }
 
-    fn change_status(&self, id: Id, status: Status) -> Result<()> {
-        let ticket = self.ticket_repo.get(id)?;
-        ensure!(ticket.status.can_change(status), "Status cannot be changed");
+    fn update(&self, template: TicketTemplate) -> Result<()> {
+        self.template_repo.save(template)
+    }
+}
 
-        self.ticket_repo.set_status(id, status)?;
+/// Create a new template service.
+pub fn template_service(template_repo: impl TemplateRepository) -> impl TemplateService {
+    TemplateServiceImpl { template_repo }
+}
+
+/// The public report service manages reports from people without an account, that are moderated
+/// by the course's staff before they become tickets.
+pub trait PublicReportService {
+    /// Get a course that accepts public reports, failing for inactive courses and courses without
+    /// a public report form.
+    fn get_course(&self, course_id: Id) -> Result<Course>;
+    /// Store a new report, unless its sender already sent `limit` reports within the last hour.
+    /// Returns whether the report was stored.
+    fn submit(&self, report: NewPublicReport, limit: u32) -> Result<bool>;
+    /// List all pending reports that the given user can moderate.
+    fn list_pending(&self, user_id: Id, role: Role) -> Result<Vec<PublicReportWithNames>>;
+    /// Get a single pending report for moderation by the given user.
+    fn get_pending(&self, id: Id, user_id: Id, role: Role) -> Result<PublicReport>;
+    /// Approve a pending report, turning it into a ticket with the given function. The ticket is
+    /// only kept if the report is still pending afterwards, so each report becomes at most one
+    /// ticket.
+    fn approve(
+        &self,
+        id: Id,
+        user_id: Id,
+        role: Role,
+        create_ticket: &mut dyn FnMut(PublicReport) -> Result<Id>,
+    ) -> Result<()>;
+    /// Reject a pending report, so it never becomes a ticket.
+    fn reject(&self, id: Id, user_id: Id, role: Role) -> Result<()>;
+}
 
-        let creator = self.user_repo.find_ticket_creator(id)?;
-        self.send_status_update(&ticket, creator, ticket.status, status)
+/// Main implementation of [`PublicReportService`].
+struct PublicReportServiceImpl<PR: PublicReportRepository, CR: CourseRepository> {
+    report_repo: PR,
+    course_repo: CR,
+}
+
+impl<PR: PublicReportRepository, CR: CourseRepository> PublicReportServiceImpl<PR, CR> {
+    /// Check whether the user moderates the reports of a course. Administrators can moderate all
+    /// reports, while others need to be the course's tutor.
+    fn can_moderate(&self, course_id: Id, user_id: Id, role: Role) -> Result<bool> {
+        Ok(role == Role::Admin || self.course_repo.get(course_id)?.tutor_id == user_id)
     }
+}
 
-    fn search(&self, role: Role, mut search: &mut TicketSearch) -> Result<Vec<TicketWithNames>> {
-        if role >= Role::Student {
-            search.priority = None;
-        }
+impl<PR: PublicReportRepository, CR: CourseRepository> PublicReportService
+    for PublicReportServiceImpl<PR, CR>
+{
+    fn get_course(&self, course_id: Id) -> Result<Course> {
+        let course = self.course_repo.get(course_id)?;
+        ensure!(
+            course.active && course.public_reports,
+            "Course with ID {} doesn't accept public reports",
+            course_id
+        );
 
-        self.ticket_repo.search(search)
+        Ok(course)
     }
 
-    fn can_open(&self, id: Id, user_id: Id, role: Role) -> Result<bool> {
-        // Everyone above a student can always see any ticket details
-        if role < Role::Student {
-            return Ok(true);
+    fn submit(&self, mut report: NewPublicReport, limit: u32) -> Result<bool> {
+        self.get_course(report.course_id)?;
+        report.medium.sort();
+
+        let now = Utc::now();
+        let ip_address = report.ip_address.clone();
+        let mut report = Some(report);
+        let mut stored = false;
+
+        // Counting and inserting in one transaction keeps concurrent requests from the same
+        // address from all passing the limit check before any of them is stored.
+        self.report_repo.transaction(&mut || {
+            let recent = self
+                .report_repo
+                .count_since(&ip_address, now - Duration::hours(1))?;
+
+            if recent < limit.into() {
+                if let Some(report) = report.take() {
+                    self.report_repo.create(report, now)?;
+                    stored = true;
+                }
+            }
+
+            Ok(())
+        })?;
+
+        Ok(stored)
+    }
+
+    fn list_pending(&self, user_id: Id, role: Role) -> Result<Vec<PublicReportWithNames>> {
+        let mut reports = self.report_repo.list_pending()?;
+
+        if role != Role::Admin {
+            let courses = self
+                .course_repo
+                .list_with_names()?
+                .into_iter()
+                .filter(|c| c.course.tutor_id == user_id)
+                .map(|c| c.course.id)
+                .collect::<FnvHashSet<_>>();
+
+            reports.retain(|r| courses.contains(&r.report.course_id));
         }
 
-        self.ticket_repo.is_creator(id, user_id)
+        Ok(reports)
     }
-}
 
-/// Create a new ticket service.
-pub fn ticket_service(
-    ticket_repo: impl TicketRepository,
-    course_repo: impl CourseRepository,
-    user_repo: impl UserRepository,
+    fn get_pending(&self, id: Id, user_id: Id, role: Role) -> Result<PublicReport> {
+        let report = self.report_repo.get_pending(id)?;
+        ensure!(
+            self.can_moderate(report.course_id, user_id, role)?,
+            "User {} can't moderate public report {}",
+            user_id,
+            id
+        );
+
+        Ok(report)
+    }
+
+    fn approve(
+        &self,
+        id: Id,
+        user_id: Id,
+        role: Role,
+        create_ticket: &mut dyn FnMut(PublicReport) -> Result<Id>,
+    ) -> Result<()> {
+        self.report_repo.transaction(&mut || {
+            let report = self.get_pending(id, user_id, role)?;
+            let ticket_id = create_ticket(report)?;
+
+            self.report_repo.approve(id, ticket_id)
+        })
+    }
+
+    fn reject(&self, id: Id, user_id: Id, role: Role) -> Result<()> {
+        self.get_pending(id, user_id, role)?;
+        self.report_repo.reject(id)
+    }
+}
+
+/// Create a new public report service.
+pub fn public_report_service(
+    report_repo: impl PublicReportRepository,
+    course_repo: impl CourseRepository,
+) -> impl PublicReportService {
+    PublicReportServiceImpl {
+        report_repo,
+        course_repo,
+    }
+}
+
+/// The ticket service manages tickets of the system, like listing existing or adding new ones.
+pub trait TicketService {
+    /// List all tickets.
+    fn list(&self) -> Result<Vec<TicketWithNames>>;
+    /// List all tickets that were created by the given user.
+    fn list_created(&self, user_id: Id) -> Result<Vec<TicketWithNames>>;
+    /// List all tickets that are currently assigned to the given user.
+    fn list_assigned(&self, user_id: Id, role: Role) -> Result<Vec<TicketWithNames>>;
+    /// List all unfinished tickets that aren't assigned to anyone, optionally for a single course.
+    fn list_unassigned(&self, course_id: Option<Id>) -> Result<Vec<TicketWithNames>>;
+    /// List all courses with ID and name.
+    fn list_course_names(&self) -> Result<Vec<(Id, String)>>;
+    /// List all users with ID and name that tickets can be assigned to.
+    fn list_assignees(&self) -> Result<Vec<(Id, String)>>;
+    /// Get a single ticket by its ID.
+    fn get(&self, id: Id) -> Result<TicketWithNames>;
+    /// Get a single ticket together with all relations. If the opening user is the ticket's
+    /// assignee and the ticket is still in [`Status::OPEN`] it will be changed to
+    /// [`Status::IN_PROGRESS`], if the workflow allows it for the user's role.
+    fn get_with_rels(&self, id: Id, user_id: Id, role: Role) -> Result<TicketWithRels>;
+    /// Create a new ticket in the system. All `rules` that match the ticket fire, setting its
+    /// priority, forwarding it to the course's author, assigning it to a specific user or adding
+    /// labels.
+    fn create(&self, ticket: NewTicket, medium: NewMedium, rules: &[Rule]) -> Result<Id>;
+    /// Add a new comment to a ticket, returning the new comment's ID. Only users that can open
+    /// the ticket can comment on it, while internal comments can only be written by staff members
+    /// and are hidden from everyone else.
+    ///
+    /// Users mentioned as `@username` are notified, if they can see the ticket. Mentioned staff
+    /// members additionally start watching the ticket to follow the discussion.
+    fn add_comment(
+        &self,
+        id: Id,
+        writer_id: Id,
+        role: Role,
+        message: String,
+        internal: bool,
+    ) -> Result<Id>;
+    /// Get a single comment.
+    fn get_comment(&self, comment_id: Id) -> Result<Comment>;
+    /// Change the message of a comment. Only the writer can do so, and only within `window`
+    /// minutes after writing it.
+    fn edit_comment(
+        &self,
+        id: Id,
+        comment_id: Id,
+        editor_id: Id,
+        message: String,
+        window: u32,
+    ) -> Result<()>;
+    /// Delete the message of a comment, which can only be done by admins. The comment itself
+    /// stays in the timeline and the original message is kept in its history.
+    fn delete_comment(&self, id: Id, comment_id: Id, user_id: Id, role: Role) -> Result<()>;
+    /// List the IDs of all comments of the ticket that the user can still edit.
+    fn list_editable_comments(&self, ticket: &TicketWithRels, user_id: Id, window: u32) -> Vec<Id>;
+    /// List the previous messages of all comments of a ticket, oldest first.
+    fn list_comment_revisions(&self, id: Id) -> Result<Vec<CommentRevisionWithNames>>;
+    /// List username and name of all existing users that are mentioned in the comments of a
+    /// ticket.
+    fn list_mentioned_users(&self, ticket: &TicketWithRels) -> Result<Vec<(String, String)>>;
+    /// Search for users that can be mentioned in comments by a part of their username or name.
+    /// Students can only find staff members.
+    fn search_mentionable(&self, term: &str, role: Role) -> Result<Vec<(String, String)>>;
+    /// List the IDs of all given tickets that missed any of their due dates.
+    fn list_overdue(&self, tickets: &[TicketWithNames], sla: &SlaConfig) -> Vec<Id>;
+    /// Load the media of the given tickets, to show their locations in a list of tickets.
+    fn list_media(&self, tickets: &[TicketWithNames]) -> Result<FnvHashMap<Id, Medium>>;
+    /// Escalate all tickets that missed any of their due dates. At first the assignee is notified,
+    /// and if the ticket is still overdue after the configured delay, the course's author and all
+    /// admins are notified as well. Tickets without assignee skip the first step.
+    fn escalate_overdue(&self, sla: &SlaConfig) -> Result<()>;
+    /// Check whether the user can edit the content of a ticket. Creators can do so while the
+    /// ticket is still open, staff members as long as it isn't closed.
+    fn can_edit(&self, id: Id, user_id: Id, role: Role) -> Result<bool>;
+    /// Edit the title, description and medium of a ticket, keeping the previous version as
+    /// revision.
+    fn edit_content(&self, edit: EditTicketContent, editor_id: Id, role: Role) -> Result<()>;
+    /// Move a ticket into another course or ticket type, as administrator or as tutor or author of
+    /// the ticket's current course. A new medium must be given if the medium type changes. Tickets
+    /// moved to another course are assigned to the course's tutor.
+    fn move_ticket(&self, ticket: MoveTicket, user_id: Id, role: Role) -> Result<()>;
+    /// List the changes of all edits of a ticket, newest first.
+    fn list_revisions(&self, ticket: &TicketWithRels) -> Result<Vec<RevisionDiff>>;
+    /// Update the details of a ticket.
+    fn update(&self, id: Id, priority: Priority, editor_id: Id) -> Result<()>;
+    /// Forward a ticket to its course's author, who becomes the new assignee.
+    fn forward(&self, id: Id, forwarder_id: Id) -> Result<()>;
+    /// Return a forwarded ticket to its course's tutor, who becomes the assignee again. The
+    /// author's comment is added as internal comment and sent to the tutor.
+    fn return_to_tutor(&self, id: Id, user_id: Id, role: Role, comment: String) -> Result<()>;
+    /// Assign a ticket to a tutor, author or admin.
+    fn assign(&self, id: Id, assignee_id: Id, user_id: Id) -> Result<()>;
+    /// Replace the labels of a ticket. Only global labels and the ones of the ticket's course can
+    /// be assigned.
+    fn set_labels(&self, id: Id, label_ids: Vec<Id>) -> Result<()>;
+    /// Change the current status of the ticket, if the workflow allows it for the given role.
+    fn change_status(&self, id: Id, status: Status, user_id: Id, role: Role) -> Result<()>;
+    /// Apply the same action to several tickets. Each ticket is checked and changed on its own,
+    /// so a failing ticket doesn't affect the others.
+    fn bulk(&self, ids: &[Id], action: &BulkAction, user_id: Id, role: Role)
+        -> Result<BulkOutcome>;
+    /// Close a ticket as duplicate of another one and merge it into that primary ticket, if the
+    /// workflow allows it for the given role.
+    fn mark_duplicate(&self, id: Id, primary_id: Id, user_id: Id, role: Role) -> Result<()>;
+    /// Get the current workflow with all statuses and transitions.
+    fn workflow(&self) -> Result<Workflow>;
+    /// Check whether the user can reopen a closed ticket. Staff members can always reopen tickets,
+    /// while creators can only request a reopen within `window` days after the ticket was closed
+    /// and as long as no other request is pending.
+    fn can_reopen(&self, id: Id, user_id: Id, role: Role, window: u32) -> Result<bool>;
+    /// Reopen a closed ticket as staff member, with the reason added as a new comment. A pending
+    /// reopen request is resolved by this as well.
+    fn reopen(&self, id: Id, user_id: Id, role: Role, reason: String) -> Result<()>;
+    /// Ask for a closed ticket to be reopened by a staff member, as its creator within `window`
+    /// days after it was closed. The reason is added as a new comment.
+    fn request_reopen(
+        &self,
+        id: Id,
+        user_id: Id,
+        role: Role,
+        reason: String,
+        window: u32,
+    ) -> Result<()>;
+    /// Check whether a reopen request is pending for the ticket.
+    fn has_reopen_request(&self, id: Id) -> Result<bool>;
+    /// Accept the pending reopen request of a ticket and reopen it.
+    fn accept_reopen(&self, id: Id, user_id: Id, role: Role) -> Result<()>;
+    /// Refuse the pending reopen request of a ticket, with the reason added as a new comment.
+    fn refuse_reopen(&self, id: Id, user_id: Id, role: Role, reason: String) -> Result<()>;
+    /// Search for tickets with different criteria. Students only find tickets of active courses.
+    fn search(&self, role: Role, search: &mut TicketSearch) -> Result<Vec<TicketWithNames>>;
+    /// Find open tickets that are similar to a ticket that is about to be created, ranked by
+    /// their similarity. Only the most similar tickets are returned.
+    fn find_similar(&self, search: &SimilarSearch) -> Result<Vec<SimilarTicket>>;
+    /// Link a ticket to another one.
+    fn add_link(&self, id: Id, target_id: Id, kind: LinkType) -> Result<()>;
+    /// Remove a link between two tickets.
+    fn remove_link(&self, id: Id, link_id: Id) -> Result<()>;
+    /// Look up tickets by their ID or a part of their title, for quick selection of a ticket.
+    fn lookup(&self, term: &str) -> Result<Vec<(Id, String)>>;
+    /// Join an existing ticket because the user has the same issue, getting notified about any
+    /// status changes from now on. The user's vote counts towards the ticket's priority, which is
+    /// raised one level for each of the reached `thresholds`. Only tickets the user can open or
+    /// find in the search can be joined, and joining doesn't grant access to the ticket's details.
+    fn me_too(&self, id: Id, user_id: Id, role: Role, thresholds: &[u32]) -> Result<()>;
+    /// Check whether the user already voted for a ticket.
+    fn has_voted(&self, id: Id, user_id: Id) -> Result<bool>;
+    /// Start watching a ticket, getting notified about any status changes and comments.
+    fn watch(&self, id: Id, user_id: Id, role: Role) -> Result<()>;
+    /// Stop watching a ticket.
+    fn unwatch(&self, id: Id, user_id: Id) -> Result<()>;
+    /// Check whether the user is watching a ticket.
+    fn is_watching(&self, id: Id, user_id: Id) -> Result<bool>;
+    /// Check whether the user can open a specific ticket.
+    fn can_open(&self, id: Id, user_id: Id, role: Role) -> Result<bool>;
+}
+
+/// Main implementation of [`TicketService`].
+struct TicketServiceImpl<TR, WR, CR, UR, MS, MR>
+where
+    TR: TicketRepository,
+    WR: WorkflowRepository,
+    CR: CourseRepository,
+    UR: UserRepository,
+    MS: MailSender,
+    MR: MailRenderer,
+{
+    ticket_repo: TR,
+    workflow_repo: WR,
+    course_repo: CR,
+    user_repo: UR,
+    mail_sender: MS,
+    mail_renderer: MR,
+}
+
+impl<TR, WR, CR, UR, MS, MR> TicketServiceImpl<TR, WR, CR, UR, MS, MR>
+where
+    TR: TicketRepository,
+    WR: WorkflowRepository,
+    CR: CourseRepository,
+    UR: UserRepository,
+    MS: MailSender,
+    MR: MailRenderer,
+{
+    /// Record a single change of a ticket in its history.
+    fn record_event(
+        &self,
+        ticket_id: Id,
+        actor_id: Id,
+        field: EventField,
+        old_value: Option<String>,
+        new_value: Option<String>,
+    ) -> Result<()> {
+        self.ticket_repo.add_event(NewTicketEvent {
+            ticket_id,
+            actor_id,
+            timestamp: Utc::now(),
+            field,
+            old_value,
+            new_value,
+        })
+    }
+
+    /// Rate how close the location of an existing ticket is to the location of a new one, between
+    /// `0.0` for unrelated locations and `1.0` for the same location. With several locations, the
+    /// closest pair counts, and ranges that overlap are treated like the same location.
+    fn location_score(location: &NewMedium, medium: &Medium) -> f64 {
+        fn best<A, B>(a: &[A], b: &[B], score: impl Fn(&A, &B) -> f64) -> f64 {
+            a.iter()
+                .flat_map(|a| b.iter().map(|b| score(a, b)))
+                .fold(0.0, f64::max)
+        }
+
+        match (location, medium) {
+            (NewMedium::Text { locations }, Medium::Text { locations: l, .. }) => {
+                best(locations, l, |a, b| {
+                    let (a, b) = a.closest(b);
+                    match a.page.abs_diff(b.page) {
+                        0 => 1.0 - f64::from(a.line.abs_diff(b.line).min(20)) / 40.0,
+                        1 => 0.4,
+                        2 => 0.2,
+                        _ => 0.0,
+                    }
+                })
+            }
+            (NewMedium::Recording { locations }, Medium::Recording { locations: l, .. }) => {
+                best(locations, l, |a, b| {
+                    let (a, b) = a.closest(b);
+                    let diff = (a - b).num_seconds().abs().min(120);
+                    1.0 - f64::from(u8::try_from(diff).unwrap_or(120)) / 120.0
+                })
+            }
+            (NewMedium::Interactive { urls }, Medium::Interactive { urls: u, .. }) => {
+                best(urls, u, |url, u| {
+                    if url == u {
+                        1.0
+                    } else if url.host() == u.host() && url.path() == u.path() {
+                        0.8
+                    } else if url.host() == u.host() {
+                        0.2
+                    } else {
+                        0.0
+                    }
+                })
+            }
+            (NewMedium::Questionaire { locations }, Medium::Questionaire { locations: l, .. }) => {
+                best(locations, l, |a, b| {
+                    let (a, b) = a.questions.closest(&b.questions);
+                    if a == b {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Decide whether the content of a ticket can be edited by the given user.
+    fn edit_allowed(ticket: &Ticket, status: &WorkflowStatus, user_id: Id, role: Role) -> bool {
+        if role <= Role::Tutor {
+            return !status.terminal;
+        }
+
+        ticket.creator_id == user_id && status.key == Status::OPEN
+    }
+
+    /// Decide whether a comment can still be edited by the given user.
+    fn comment_edit_allowed(comment: &Comment, user_id: Id, window: u32) -> bool {
+        comment.creator_id == user_id
+            && !comment.deleted
+            && Utc::now() - comment.timestamp <= Duration::minutes(window.into())
+    }
+
+    /// Decide whether a ticket can be reopened by the given user, either directly by staff members
+    /// or through a request by its creator. Tickets without a closing time can't be requested to
+    /// be reopened, as they were closed before these times were recorded.
+    fn reopen_allowed(
+        ticket: &Ticket,
+        terminal: bool,
+        user_id: Id,
+        role: Role,
+        window: u32,
+    ) -> bool {
+        if !terminal {
+            return false;
+        }
+
+        if role <= Role::Tutor {
+            return true;
+        }
+
+        ticket.creator_id == user_id
+            && ticket.closed_at.map_or(false, |closed_at| {
+                Utc::now() - closed_at <= Duration::days(window.into())
+            })
+    }
+
+    /// Let the watchers of a reopened ticket know that it's open again.
+    fn send_reopened(&self, ticket: &Ticket, actor_id: Id, old_status: &str) -> Result<()> {
+        let workflow = self.workflow_repo.get()?;
+        let open = workflow
+            .status(&Status::OPEN)
+            .map_or_else(|| Status::OPEN.as_ref(), |s| &s.name);
+
+        self.send_status_updates(ticket, actor_id, old_status, open)
+    }
+
+    /// Send an email about a ticket status change to all its watchers, except the user who
+    /// changed it.
+    fn send_status_updates(
+        &self,
+        ticket: &Ticket,
+        actor_id: Id,
+        old: &str,
+        new: &str,
+    ) -> Result<()> {
+        for watcher in self.user_repo.list_ticket_watchers(ticket.id)? {
+            if watcher.id != actor_id {
+                self.send_status_update(ticket, watcher, old, new)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send an email about a ticket status change.
+    fn send_status_update(
+        &self,
+        ticket: &Ticket,
+        recipient: User,
+        old: &str,
+        new: &str,
+    ) -> Result<()> {
+        let (subject, message) = self.mail_renderer.status_change(
+            &recipient.name,
+            StatusDetails {
+                ticket_title: &ticket.title,
+                ticket_id: ticket.id,
+                old_status: old,
+                new_status: new,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", recipient.username),
+                &recipient.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    /// Send an email to the new assignee of a ticket.
+    fn send_assignment(&self, ticket: &Ticket, assignee: User, assigner_name: &str) -> Result<()> {
+        let (subject, message) = self.mail_renderer.assignment(
+            &assignee.name,
+            AssignmentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: ticket.id,
+                assigner_name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", assignee.username),
+                &assignee.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    /// Send an email about a new comment for a ticket.
+    fn send_comment_update(
+        &self,
+        ticket: &Ticket,
+        recipient: User,
+        writer: &User,
+        comment: &str,
+    ) -> Result<()> {
+        let (subject, message) = self.mail_renderer.new_comment(
+            &recipient.name,
+            CommentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: ticket.id,
+                comment,
+                writer_name: &writer.name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", recipient.username),
+                &recipient.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    /// List the users that are notified about overdue tickets in the last escalation step, which
+    /// are the course's author and all admins.
+    fn list_escalation_managers(&self, ticket: &Ticket) -> Result<Vec<User>> {
+        let author_id = self.course_repo.get(ticket.course_id)?.author_id;
+
+        Ok(self
+            .user_repo
+            .list()?
+            .into_iter()
+            .filter(|u| u.active && (u.id == author_id || u.role == Role::Admin))
+            .collect())
+    }
+
+    /// Send an email about an overdue ticket to one of the responsible users.
+    fn send_overdue(
+        &self,
+        ticket: &Ticket,
+        recipient: User,
+        assignee_name: Option<&str>,
+    ) -> Result<()> {
+        let (subject, message) = self.mail_renderer.overdue(
+            &recipient.name,
+            OverdueDetails {
+                ticket_title: &ticket.title,
+                ticket_id: ticket.id,
+                assignee_name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", recipient.username),
+                &recipient.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    /// Send an email about a mention in a new comment to the mentioned user.
+    fn send_mention(
+        &self,
+        ticket: &Ticket,
+        recipient: User,
+        writer: &User,
+        comment: &str,
+    ) -> Result<()> {
+        let (subject, message) = self.mail_renderer.mention(
+            &recipient.name,
+            CommentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: ticket.id,
+                comment,
+                writer_name: &writer.name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", recipient.username),
+                &recipient.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+}
+
+impl<TR, WR, CR, UR, MS, MR> TicketService for TicketServiceImpl<TR, WR, CR, UR, MS, MR>
+where
+    TR: TicketRepository,
+    WR: WorkflowRepository,
+    CR: CourseRepository,
+    UR: UserRepository,
+    MS: MailSender,
+    MR: MailRenderer,
+{
+    fn list(&self) -> Result<Vec<TicketWithNames>> {
+        self.ticket_repo.list_with_names()
+    }
+
+    fn list_created(&self, user_id: Id) -> Result<Vec<TicketWithNames>> {
+        self.ticket_repo.list_by_creator_id(user_id)
+    }
+
+    fn list_assigned(&self, user_id: Id, role: Role) -> Result<Vec<TicketWithNames>> {
+        if role <= Role::Tutor {
+            self.ticket_repo.list_by_assignee_id(user_id)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn list_unassigned(&self, course_id: Option<Id>) -> Result<Vec<TicketWithNames>> {
+        self.ticket_repo.list_unassigned(course_id)
+    }
+
+    fn list_course_names(&self) -> Result<Vec<(Id, String)>> {
+        self.course_repo.list_names()
+    }
+
+    fn list_assignees(&self) -> Result<Vec<(Id, String)>> {
+        self.user_repo.list_staff_names()
+    }
+
+    fn get(&self, id: Id) -> Result<TicketWithNames> {
+        self.ticket_repo.get_with_names(id)
+    }
+
+    fn get_with_rels(&self, id: Id, user_id: Id, role: Role) -> Result<TicketWithRels> {
+        let workflow = self.workflow_repo.get()?;
+
+        // If we open a ticket as its assignee, update the status first.
+        let activated = if workflow.can_change(&Status::OPEN, &Status::IN_PROGRESS, role) {
+            self.ticket_repo.activate_ticket(id, user_id)?
+        } else {
+            false
+        };
+
+        if activated {
+            self.record_event(
+                id,
+                user_id,
+                EventField::Status,
+                Some(Status::OPEN.to_string()),
+                Some(Status::IN_PROGRESS.to_string()),
+            )?;
+        }
+
+        let mut ticket = self.ticket_repo.get_with_rels(id)?;
+
+        if role > Role::Tutor {
+            ticket.hide_internal();
+        }
+
+        if activated {
+            let old = workflow
+                .status(&Status::OPEN)
+                .map_or_else(|| Status::OPEN.as_ref(), |s| &s.name);
+            self.send_status_updates(&ticket.ticket, user_id, old, &ticket.status.name)?;
+        }
+
+        Ok(ticket)
+    }
+
+    fn create(&self, ticket: NewTicket, mut medium: NewMedium, rules: &[Rule]) -> Result<Id> {
+        medium.sort();
+
+        let outcome = RuleOutcome::evaluate(rules, &ticket);
+        let priority = outcome
+            .priority
+            .unwrap_or_else(|| map_priority(ticket.category, 0, &[]));
+        let course_id = ticket.course_id;
+        let creator_id = ticket.creator_id;
+
+        let id = self
+            .ticket_repo
+            .create(ticket, priority, Utc::now(), medium)?;
+        self.ticket_repo.add_watcher(id, creator_id)?;
+
+        // New tickets go to the course's tutor, unless the tutor was deactivated in the meantime,
+        // which leaves the ticket in the course's unassigned queue.
+        let course = self.course_repo.get(course_id)?;
+        let tutor = self.user_repo.find(course.tutor_id).ok();
+
+        if let Some(tutor) = tutor.filter(|tutor| tutor.active) {
+            self.ticket_repo.assign(id, tutor.id)?;
+            self.ticket_repo.add_watcher(id, tutor.id)?;
+        }
+
+        let fired = rules
+            .iter()
+            .filter(|rule| outcome.fired(rule.id))
+            .collect::<Vec<_>>();
+
+        for rule in &fired {
+            self.record_event(
+                id,
+                creator_id,
+                EventField::Rule,
+                None,
+                Some(rule.name.clone()),
+            )?;
+        }
+
+        if !outcome.label_ids.is_empty() {
+            self.ticket_repo.set_labels(id, &outcome.label_ids)?;
+        }
+
+        let mut assignment = None;
+
+        if let Some(rule) = fired.iter().find(|rule| rule.forward) {
+            assignment = Some((self.ticket_repo.forward(id)?, rule));
+        }
+
+        if let Some(rule) = fired.iter().find(|rule| rule.assignee_id.is_some()) {
+            // Rules may point to users that were deactivated or changed their role in the
+            // meantime, which must not prevent the ticket from being created.
+            let assignee = rule
+                .assignee_id
+                .and_then(|user_id| self.user_repo.find(user_id).ok())
+                .filter(|user| user.active && user.role <= Role::Tutor);
+
+            if let Some(assignee) = assignee {
+                self.ticket_repo.assign(id, assignee.id)?;
+                assignment = Some((assignee.id, rule));
+            }
+        }
+
+        if let Some((assignee_id, rule)) = assignment {
+            self.ticket_repo.add_watcher(id, assignee_id)?;
+
+            if assignee_id != creator_id {
+                let ticket = self.ticket_repo.get(id)?;
+                let assignee = self.user_repo.find(assignee_id)?;
+
+                self.send_assignment(&ticket, assignee, &format!("der Regel \"{}\"", rule.name))?;
+            }
+        }
+
+        Ok(id)
+    }
+
+    fn add_comment(
+        &self,
+        id: Id,
+        writer_id: Id,
+        role: Role,
+        message: String,
+        internal: bool,
+    ) -> Result<Id> {
+        ensure!(
+            !internal || role <= Role::Tutor,
+            "Only staff members can write internal comments"
+        );
+        ensure!(
+            self.can_open(id, writer_id, role)?,
+            "Ticket can't be commented on"
+        );
+
+        let now = Utc::now();
+        let comment_id = self.ticket_repo.add_comment(NewComment {
+            ticket_id: id,
+            creator_id: writer_id,
+            timestamp: now,
+            message: message.clone(),
+            source_ticket_id: None,
+            internal,
+        })?;
+
+        // Anybody taking part in the discussion wants to know about replies.
+        self.ticket_repo.add_watcher(id, writer_id)?;
+
+        let writer = self.user_repo.find(writer_id)?;
+        let ticket = self.ticket_repo.get(id)?;
+        let mut notified = FnvHashSet::default();
+
+        // Only visible answers of staff members count as response to the ticket.
+        if !internal && role <= Role::Tutor && writer_id != ticket.creator_id {
+            self.ticket_repo.set_first_response(id, now)?;
+        }
+
+        for user in self
+            .user_repo
+            .list_by_usernames(&markdown::mentions(&message))?
+        {
+            // Students are only notified about tickets they can already see, and never about
+            // internal comments.
+            let staff = user.role <= Role::Tutor;
+            if user.id == writer_id
+                || (!staff && (internal || !self.can_open(id, user.id, user.role)?))
+            {
+                continue;
+            }
+
+            if staff {
+                self.ticket_repo.add_watcher(id, user.id)?;
+            }
+
+            notified.insert(user.id);
+            self.send_mention(&ticket, user, &writer, &message)?;
+        }
+
+        // We don't want emails for our own comments, watchers that only voted for the ticket can't
+        // read its discussion and internal comments only go to staff members.
+        for watcher in self.user_repo.list_ticket_watchers(id)? {
+            if watcher.id != writer_id
+                && self.can_open(id, watcher.id, watcher.role)?
+                && !notified.contains(&watcher.id)
+                && (!internal || watcher.role <= Role::Tutor)
+            {
+                self.send_comment_update(&ticket, watcher, &writer, &message)?;
+            }
+        }
+
+        Ok(comment_id)
+    }
+
+    fn get_comment(&self, comment_id: Id) -> Result<Comment> {
+        self.ticket_repo.get_comment(comment_id)
+    }
+
+    fn edit_comment(
+        &self,
+        id: Id,
+        comment_id: Id,
+        editor_id: Id,
+        message: String,
+        window: u32,
+    ) -> Result<()> {
+        let comment = self.ticket_repo.get_comment(comment_id)?;
+        ensure!(
+            comment.ticket_id == id,
+            "Comment doesn't belong to the ticket"
+        );
+        ensure!(
+            Self::comment_edit_allowed(&comment, editor_id, window),
+            "Comment can't be edited"
+        );
+
+        if message == comment.message {
+            return Ok(());
+        }
+
+        self.ticket_repo.edit_comment(
+            comment_id,
+            message,
+            NewCommentRevision {
+                comment_id,
+                editor_id,
+                timestamp: Utc::now(),
+                message: comment.message,
+            },
+        )
+    }
+
+    fn delete_comment(&self, id: Id, comment_id: Id, user_id: Id, role: Role) -> Result<()> {
+        ensure!(role == Role::Admin, "Only admins can delete comments");
+
+        let comment = self.ticket_repo.get_comment(comment_id)?;
+        ensure!(
+            comment.ticket_id == id,
+            "Comment doesn't belong to the ticket"
+        );
+        ensure!(!comment.deleted, "Comment was already deleted");
+
+        self.ticket_repo.delete_comment(
+            comment_id,
+            NewCommentRevision {
+                comment_id,
+                editor_id: user_id,
+                timestamp: Utc::now(),
+                message: comment.message,
+            },
+        )
+    }
+
+    fn list_editable_comments(&self, ticket: &TicketWithRels, user_id: Id, window: u32) -> Vec<Id> {
+        ticket
+            .timeline
+            .iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Comment(c)
+                    if Self::comment_edit_allowed(&c.comment, user_id, window) =>
+                {
+                    Some(c.comment.id)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn list_comment_revisions(&self, id: Id) -> Result<Vec<CommentRevisionWithNames>> {
+        self.ticket_repo.list_comment_revisions(id)
+    }
+
+    fn list_mentioned_users(&self, ticket: &TicketWithRels) -> Result<Vec<(String, String)>> {
+        let mut usernames = ticket
+            .timeline
+            .iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Comment(c) if !c.comment.deleted => {
+                    Some(markdown::mentions(&c.comment.message))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        usernames.sort_unstable();
+        usernames.dedup();
+
+        Ok(self
+            .user_repo
+            .list_by_usernames(&usernames)?
+            .into_iter()
+            .map(|u| (u.username, u.name))
+            .collect())
+    }
+
+    fn list_media(&self, tickets: &[TicketWithNames]) -> Result<FnvHashMap<Id, Medium>> {
+        self.ticket_repo.list_media(tickets)
+    }
+
+    fn list_overdue(&self, tickets: &[TicketWithNames], sla: &SlaConfig) -> Vec<Id> {
+        tickets
+            .iter()
+            .filter(|t| due_dates(&t.ticket, t.status.terminal, sla).is_overdue())
+            .map(|t| t.ticket.id)
+            .collect()
+    }
+
+    fn escalate_overdue(&self, sla: &SlaConfig) -> Result<()> {
+        let now = Utc::now();
+        let delay = Duration::hours(sla.escalation_delay.into());
+
+        for TicketWithNames {
+            ticket,
+            status,
+            assignee_name,
+            ..
+        } in self.ticket_repo.list_with_names()?
+        {
+            if !due_dates(&ticket, status.terminal, sla).is_overdue() {
+                continue;
+            }
+
+            let recipients = match (ticket.escalation_level, ticket.assignee_id) {
+                (0, Some(assignee_id)) => vec![self.user_repo.find(assignee_id)?],
+                (0, None) => self.list_escalation_managers(&ticket)?,
+                (1, _)
+                    if ticket
+                        .escalated_at
+                        .map_or(true, |escalated_at| now - escalated_at >= delay) =>
+                {
+                    self.list_escalation_managers(&ticket)?
+                }
+                _ => continue,
+            };
+
+            // Tickets without assignee go straight to the last level.
+            let level = if ticket.assignee_id.is_some() {
+                ticket.escalation_level + 1
+            } else {
+                2
+            };
+
+            self.ticket_repo.set_escalation(ticket.id, level, now)?;
+
+            for recipient in recipients {
+                self.send_overdue(&ticket, recipient, assignee_name.as_deref())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn search_mentionable(&self, term: &str, role: Role) -> Result<Vec<(String, String)>> {
+        let term = term.trim().trim_start_matches('@');
+        if term.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let roles: &[Role] = if role <= Role::Tutor {
+            &[Role::Admin, Role::Author, Role::Tutor, Role::Student]
+        } else {
+            &[Role::Admin, Role::Author, Role::Tutor]
+        };
+
+        self.user_repo.search_names(term, roles)
+    }
+
+    fn can_edit(&self, id: Id, user_id: Id, role: Role) -> Result<bool> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+
+        Ok(Self::edit_allowed(&ticket, &status, user_id, role))
+    }
+
+    fn edit_content(&self, mut edit: EditTicketContent, editor_id: Id, role: Role) -> Result<()> {
+        edit.medium.sort();
+
+        let TicketWithRels {
+            ticket,
+            status,
+            medium,
+            ..
+        } = self.ticket_repo.get_with_rels(edit.id)?;
+        ensure!(
+            Self::edit_allowed(&ticket, &status, editor_id, role),
+            "Ticket can't be edited"
+        );
+        ensure!(
+            edit.medium.medium_type() == ticket.type_.medium(),
+            "Medium doesn't match the ticket type"
+        );
+
+        let location = medium.to_string();
+        if edit.title == ticket.title
+            && edit.description == ticket.description
+            && edit.medium.to_string() == location
+        {
+            return Ok(());
+        }
+
+        let revision = NewTicketRevision {
+            ticket_id: ticket.id,
+            editor_id,
+            timestamp: Utc::now(),
+            title: ticket.title,
+            description: ticket.description,
+            location,
+        };
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo
+                .update_content(edit.clone(), revision.clone())?;
+            self.record_event(ticket.id, editor_id, EventField::Edited, None, None)
+        })
+    }
+
+    fn move_ticket(&self, mut ticket: MoveTicket, user_id: Id, role: Role) -> Result<()> {
+        let TicketWithRels {
+            ticket: old,
+            status,
+            medium,
+            ..
+        } = self.ticket_repo.get_with_rels(ticket.id)?;
+        ensure!(!status.terminal, "Ticket can't be moved anymore");
+
+        let source = self.course_repo.get(old.course_id)?;
+        ensure!(
+            role == Role::Admin || source.tutor_id == user_id || source.author_id == user_id,
+            "User {} can't move tickets of course {}",
+            user_id,
+            source.id
+        );
+
+        let course_changed = old.course_id != ticket.course_id;
+        let type_changed = old.type_ != ticket.type_;
+        if !course_changed && !type_changed {
+            return Ok(());
+        }
+
+        let course = self.course_repo.get(ticket.course_id)?;
+        ensure!(
+            !course_changed || course.active,
+            "Tickets can't be moved into inactive courses"
+        );
+
+        let old_medium = old.type_.medium();
+        check_moved_medium(&mut ticket.medium, old_medium, ticket.type_.medium())?;
+
+        // The old location is kept as revision, as it can't be restored from the new medium
+        let revision = ticket.medium.as_ref().map(|_| NewTicketRevision {
+            ticket_id: old.id,
+            editor_id: user_id,
+            timestamp: Utc::now(),
+            title: old.title.clone(),
+            description: old.description.clone(),
+            location: medium.to_string(),
+        });
+
+        // The new course's tutor takes over, unless the ticket is already with them. Like new
+        // tickets, it goes to the course's unassigned queue if the tutor was deactivated.
+        let tutor = course_changed
+            .then(|| self.user_repo.find(course.tutor_id).ok())
+            .flatten()
+            .filter(|tutor| tutor.active);
+        let new_assignee = if course_changed {
+            tutor.as_ref().map(|tutor| tutor.id)
+        } else {
+            old.assignee_id
+        };
+
+        let (id, type_) = (ticket.id, ticket.type_);
+        let mut changes = Some((ticket, revision));
+
+        self.ticket_repo.transaction(&mut || {
+            if let Some((ticket, revision)) = changes.take() {
+                self.ticket_repo.move_ticket(ticket, old_medium, revision)?;
+            }
+
+            if course_changed {
+                self.record_event(
+                    id,
+                    user_id,
+                    EventField::Course,
+                    Some(old.course_id.to_string()),
+                    Some(course.id.to_string()),
+                )?;
+            }
+
+            if type_changed {
+                self.record_event(
+                    id,
+                    user_id,
+                    EventField::Type,
+                    Some(old.type_.to_string()),
+                    Some(type_.to_string()),
+                )?;
+            }
+
+            if new_assignee != old.assignee_id {
+                if let Some(assignee_id) = new_assignee {
+                    self.ticket_repo.assign(id, assignee_id)?;
+                    self.ticket_repo.add_watcher(id, assignee_id)?;
+                } else {
+                    self.ticket_repo.unassign(id)?;
+                }
+
+                self.record_event(
+                    id,
+                    user_id,
+                    EventField::Assignee,
+                    old.assignee_id.map(|id| id.to_string()),
+                    new_assignee.map(|id| id.to_string()),
+                )?;
+            }
+
+            Ok(())
+        })?;
+
+        let tutor = match tutor {
+            Some(tutor) if new_assignee != old.assignee_id && tutor.id != user_id => tutor,
+            _ => return Ok(()),
+        };
+
+        let ticket = self.ticket_repo.get(id)?;
+        let mover = self.user_repo.find(user_id)?;
+        self.send_assignment(&ticket, tutor, &mover.name)
+    }
+
+    fn list_revisions(&self, ticket: &TicketWithRels) -> Result<Vec<RevisionDiff>> {
+        let revisions = self.ticket_repo.list_revisions(ticket.ticket.id)?;
+        let current_location = ticket.medium.to_string();
+
+        // Each revision is compared to the version that replaced it, which is either the next
+        // revision or the current content of the ticket.
+        let newer = revisions
+            .iter()
+            .skip(1)
+            .map(|r| {
+                (
+                    r.revision.title.as_str(),
+                    r.revision.description.as_str(),
+                    r.revision.location.as_str(),
+                )
+            })
+            .chain(iter::once((
+                ticket.ticket.title.as_str(),
+                ticket.ticket.description.as_str(),
+                current_location.as_str(),
+            )));
+
+        let mut diffs = revisions
+            .iter()
+            .zip(newer)
+            .map(|(old, (title, description, location))| RevisionDiff {
+                editor_name: old.editor_name.clone(),
+                timestamp: old.revision.timestamp,
+                title: diff_words(&old.revision.title, title),
+                description: diff_words(&old.revision.description, description),
+                location: diff_words(&old.revision.location, location),
+            })
+            .collect::<Vec<_>>();
+
+        diffs.reverse();
+        Ok(diffs)
+    }
+
+    fn update(&self, id: Id, priority: Priority, editor_id: Id) -> Result<()> {
+        let ticket = self.ticket_repo.get(id)?;
+
+        self.ticket_repo.update(EditTicket { id, priority })?;
+
+        if ticket.priority != priority {
+            self.record_event(
+                id,
+                editor_id,
+                EventField::Priority,
+                Some(ticket.priority.to_string()),
+                Some(priority.to_string()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn forward(&self, id: Id, forwarder_id: Id) -> Result<()> {
+        let old_assignee_id = self.ticket_repo.get(id)?.assignee_id;
+        let author_id = self.ticket_repo.forward(id)?;
+        self.ticket_repo.add_watcher(id, author_id)?;
+
+        self.record_event(
+            id,
+            forwarder_id,
+            EventField::Forwarded,
+            old_assignee_id.map(|id| id.to_string()),
+            Some(author_id.to_string()),
+        )?;
+
+        if author_id == forwarder_id {
+            return Ok(());
+        }
+
+        let ticket = self.ticket_repo.get(id)?;
+        let author = self.user_repo.find(author_id)?;
+        let forwarder = self.user_repo.find(forwarder_id)?;
+
+        self.send_assignment(&ticket, author, &forwarder.name)
+    }
+
+    fn return_to_tutor(&self, id: Id, user_id: Id, role: Role, comment: String) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        ensure!(
+            !status.terminal && ticket.forwarded,
+            "Ticket can't be returned"
+        );
+        ensure!(
+            role == Role::Admin || ticket.assignee_id == Some(user_id),
+            "Only the ticket's assignee can return it"
+        );
+
+        let mut tutor_id = 0;
+
+        self.ticket_repo.transaction(&mut || {
+            tutor_id = self.ticket_repo.return_to_tutor(id)?;
+            self.ticket_repo.add_watcher(id, tutor_id)?;
+
+            self.record_event(
+                id,
+                user_id,
+                EventField::Returned,
+                ticket.assignee_id.map(|id| id.to_string()),
+                Some(tutor_id.to_string()),
+            )?;
+            self.ticket_repo.add_comment(NewComment {
+                ticket_id: id,
+                creator_id: user_id,
+                timestamp: Utc::now(),
+                message: comment.clone(),
+                source_ticket_id: None,
+                internal: true,
+            })?;
+            Ok(())
+        })?;
+
+        if tutor_id == user_id {
+            return Ok(());
+        }
+
+        let tutor = self.user_repo.find(tutor_id)?;
+        let author = self.user_repo.find(user_id)?;
+        let (subject, message) = self.mail_renderer.returned(
+            &tutor.name,
+            CommentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: id,
+                comment: &comment,
+                writer_name: &author.name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", tutor.username),
+                &tutor.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    fn assign(&self, id: Id, assignee_id: Id, user_id: Id) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        ensure!(!status.terminal, "Ticket can't be assigned anymore");
+
+        let assignee = self.user_repo.find(assignee_id)?;
+        ensure!(
+            assignee.active && assignee.role <= Role::Tutor,
+            "User can't be assigned to tickets"
+        );
+
+        self.ticket_repo.assign(id, assignee_id)?;
+        self.ticket_repo.add_watcher(id, assignee_id)?;
+
+        if ticket.assignee_id != Some(assignee_id) {
+            self.record_event(
+                id,
+                user_id,
+                EventField::Assignee,
+                ticket.assignee_id.map(|id| id.to_string()),
+                Some(assignee_id.to_string()),
+            )?;
+        }
+
+        // No need to notify users that assigned themselves
+        if assignee_id == user_id {
+            return Ok(());
+        }
+
+        let assigned_by = self.user_repo.find(user_id)?;
+        self.send_assignment(&ticket, assignee, &assigned_by.name)
+    }
+
+    fn set_labels(&self, id: Id, mut label_ids: Vec<Id>) -> Result<()> {
+        label_ids.sort_unstable();
+        label_ids.dedup();
+
+        self.ticket_repo.set_labels(id, &label_ids)
+    }
+
+    fn change_status(&self, id: Id, status: Status, user_id: Id, role: Role) -> Result<()> {
+        let workflow = self.workflow_repo.get()?;
+        let TicketWithNames {
+            ticket,
+            status: old,
+            ..
+        } = self.ticket_repo.get_with_names(id)?;
+        let new = workflow.status(&status).context("Status doesn't exist")?;
+
+        ensure!(
+            workflow.can_change(&old.key, &new.key, role),
+            "Status cannot be changed"
+        );
+
+        let closed_at = if new.terminal { Some(Utc::now()) } else { None };
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo.set_status(id, status.clone(), closed_at)?;
+            if !new.terminal {
+                self.ticket_repo.remove_reopen_request(id)?;
+            }
+            self.record_event(
+                id,
+                user_id,
+                EventField::Status,
+                Some(old.key.to_string()),
+                Some(new.key.to_string()),
+            )
+        })?;
+
+        self.send_status_updates(&ticket, user_id, &old.name, &new.name)
+    }
+
+    fn bulk(
+        &self,
+        ids: &[Id],
+        action: &BulkAction,
+        user_id: Id,
+        role: Role,
+    ) -> Result<BulkOutcome> {
+        let workflow = self.workflow_repo.get()?;
+        let assignable = match action {
+            BulkAction::Assign(assignee_id) => {
+                let assignee = self.user_repo.find(*assignee_id)?;
+                assignee.active && assignee.role <= Role::Tutor
+            }
+            _ => true,
+        };
+
+        let mut outcome = BulkOutcome::default();
+
+        for &id in ids {
+            let TicketWithNames {
+                ticket,
+                status,
+                labels,
+                ..
+            } = if let Ok(ticket) = self.ticket_repo.get_with_names(id) {
+                ticket
+            } else {
+                outcome.failed.push((id, BulkError::NotFound));
+                continue;
+            };
+
+            let check = match action {
+                BulkAction::Status(new) if !workflow.can_change(&status.key, new, role) => {
+                    Err(BulkError::Transition)
+                }
+                BulkAction::Priority(_) | BulkAction::Forward | BulkAction::Assign(_)
+                    if status.terminal =>
+                {
+                    Err(BulkError::Closed)
+                }
+                BulkAction::Forward if ticket.forwarded => Err(BulkError::Forwarded),
+                BulkAction::Assign(_) if !assignable => Err(BulkError::Assignee),
+                BulkAction::AddLabel(label) if !label.is_available(ticket.course_id) => {
+                    Err(BulkError::Label)
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(e) = check {
+                outcome.failed.push((id, e));
+                continue;
+            }
+
+            let res = self.ticket_repo.transaction(&mut || match action {
+                BulkAction::Status(new) => self.change_status(id, new.clone(), user_id, role),
+                BulkAction::Priority(priority) => self.update(id, *priority, user_id),
+                BulkAction::Forward => self.forward(id, user_id),
+                BulkAction::Assign(assignee_id) => self.assign(id, *assignee_id, user_id),
+                BulkAction::AddLabel(label) => self.set_labels(
+                    id,
+                    labels
+                        .iter()
+                        .map(|l| l.id)
+                        .chain(iter::once(label.id))
+                        .collect(),
+                ),
+            });
+
+            match res {
+                Ok(()) => outcome.changed.push(id),
+                Err(e) => {
+                    error!("error during bulk action on ticket {}: {:?}", id, e);
+                    outcome.failed.push((id, BulkError::Internal));
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    fn mark_duplicate(&self, id: Id, primary_id: Id, user_id: Id, role: Role) -> Result<()> {
+        ensure!(id != primary_id, "Ticket can't be a duplicate of itself");
+
+        let workflow = self.workflow_repo.get()?;
+        let TicketWithNames {
+            ticket,
+            status: old,
+            ..
+        } = self.ticket_repo.get_with_names(id)?;
+        ensure!(!old.terminal, "Closed tickets can't be marked as duplicate");
+        ensure!(
+            workflow.can_change(&old.key, &Status::DUPLICATE, role),
+            "Status cannot be changed"
+        );
+
+        // Always merge into the ticket at the top, to avoid chains of duplicates.
+        let primary = self.ticket_repo.get(primary_id)?;
+        ensure!(
+            primary.duplicate_of.is_none(),
+            "Ticket {} is a duplicate itself",
+            primary_id
+        );
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo
+                .mark_duplicate(id, primary_id, Utc::now())?;
+            self.record_event(
+                id,
+                user_id,
+                EventField::Status,
+                Some(old.key.to_string()),
+                Some(Status::DUPLICATE.to_string()),
+            )
+        })?;
+
+        let new = workflow
+            .status(&Status::DUPLICATE)
+            .map_or_else(|| Status::DUPLICATE.as_ref(), |s| &s.name);
+
+        // The watchers of the duplicate were moved over to the primary ticket while merging, so
+        // its watchers now cover both tickets.
+        for watcher in self.user_repo.list_ticket_watchers(primary_id)? {
+            if watcher.id != user_id {
+                self.send_status_update(&ticket, watcher, &old.name, new)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn workflow(&self) -> Result<Workflow> {
+        self.workflow_repo.get()
+    }
+
+    fn can_reopen(&self, id: Id, user_id: Id, role: Role, window: u32) -> Result<bool> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+
+        Ok(
+            Self::reopen_allowed(&ticket, status.terminal, user_id, role, window)
+                && (role <= Role::Tutor || self.ticket_repo.reopen_requester(id)?.is_none()),
+        )
+    }
+
+    fn reopen(&self, id: Id, user_id: Id, role: Role, reason: String) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        ensure!(
+            role <= Role::Tutor && status.terminal,
+            "Ticket can't be reopened"
+        );
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo.reopen(id)?;
+            self.ticket_repo.remove_reopen_request(id)?;
+            self.record_event(
+                id,
+                user_id,
+                EventField::Reopened,
+                Some(status.key.to_string()),
+                Some(Status::OPEN.to_string()),
+            )?;
+            self.ticket_repo.add_comment(NewComment {
+                ticket_id: id,
+                creator_id: user_id,
+                timestamp: Utc::now(),
+                message: reason.clone(),
+                source_ticket_id: None,
+                internal: false,
+            })?;
+
+            Ok(())
+        })?;
+
+        self.send_reopened(&ticket, user_id, &status.name)
+    }
+
+    fn request_reopen(
+        &self,
+        id: Id,
+        user_id: Id,
+        role: Role,
+        reason: String,
+        window: u32,
+    ) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        ensure!(
+            role > Role::Tutor
+                && Self::reopen_allowed(&ticket, status.terminal, user_id, role, window),
+            "Ticket can't be requested to be reopened"
+        );
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo
+                .add_reopen_request(id, user_id, Utc::now())?;
+            self.record_event(id, user_id, EventField::ReopenRequested, None, None)?;
+            self.ticket_repo.add_comment(NewComment {
+                ticket_id: id,
+                creator_id: user_id,
+                timestamp: Utc::now(),
+                message: reason.clone(),
+                source_ticket_id: None,
+                internal: false,
+            })?;
+
+            Ok(())
+        })?;
+
+        // Unassigned tickets have nobody looking after them yet, so the course's tutor decides.
+        let recipient_id = match ticket.assignee_id {
+            Some(assignee_id) => assignee_id,
+            None => self.course_repo.get(ticket.course_id)?.tutor_id,
+        };
+        let recipient = self.user_repo.find(recipient_id)?;
+        let requester = self.user_repo.find(user_id)?;
+        let (subject, message) = self.mail_renderer.reopen_request(
+            &recipient.name,
+            CommentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: id,
+                comment: &reason,
+                writer_name: &requester.name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", recipient.username),
+                &recipient.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    fn has_reopen_request(&self, id: Id) -> Result<bool> {
+        Ok(self.ticket_repo.reopen_requester(id)?.is_some())
+    }
+
+    fn accept_reopen(&self, id: Id, user_id: Id, role: Role) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        ensure!(
+            role <= Role::Tutor && status.terminal,
+            "Ticket can't be reopened"
+        );
+        ensure!(
+            self.ticket_repo.reopen_requester(id)?.is_some(),
+            "No reopen request pending for ticket {}",
+            id
+        );
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo.reopen(id)?;
+            self.ticket_repo.remove_reopen_request(id)?;
+            self.record_event(
+                id,
+                user_id,
+                EventField::Reopened,
+                Some(status.key.to_string()),
+                Some(Status::OPEN.to_string()),
+            )
+        })?;
+
+        self.send_reopened(&ticket, user_id, &status.name)
+    }
+
+    fn refuse_reopen(&self, id: Id, user_id: Id, role: Role, reason: String) -> Result<()> {
+        ensure!(
+            role <= Role::Tutor,
+            "Only staff members can refuse reopen requests"
+        );
+        let ticket = self.ticket_repo.get(id)?;
+        let requester_id = self
+            .ticket_repo
+            .reopen_requester(id)?
+            .with_context(|| format!("No reopen request pending for ticket {}", id))?;
+
+        self.ticket_repo.transaction(&mut || {
+            self.ticket_repo.remove_reopen_request(id)?;
+            self.record_event(id, user_id, EventField::ReopenRefused, None, None)?;
+            self.ticket_repo.add_comment(NewComment {
+                ticket_id: id,
+                creator_id: user_id,
+                timestamp: Utc::now(),
+                message: reason.clone(),
+                source_ticket_id: None,
+                internal: false,
+            })?;
+
+            Ok(())
+        })?;
+
+        let requester = self.user_repo.find(requester_id)?;
+        let refuser = self.user_repo.find(user_id)?;
+        let (subject, message) = self.mail_renderer.reopen_refused(
+            &requester.name,
+            CommentDetails {
+                ticket_title: &ticket.title,
+                ticket_id: id,
+                comment: &reason,
+                writer_name: &refuser.name,
+            },
+        );
+
+        self.mail_sender.send(Mail {
+            from: ("amelio@dnaka91.rocks", "Amelio"),
+            to: (
+                &format!("{}@iubh-fernstudium.de", requester.username),
+                &requester.name,
+            ),
+            subject,
+            message: &message,
+        })
+    }
+
+    fn search(&self, role: Role, mut search: &mut TicketSearch) -> Result<Vec<TicketWithNames>> {
+        if role >= Role::Student {
+            search.priority = None;
+            search.active_only = true;
+        }
+
+        self.ticket_repo.search(search)
+    }
+
+    fn find_similar(&self, search: &SimilarSearch) -> Result<Vec<SimilarTicket>> {
+        // New tickets can only be created for active courses.
+        if !self.course_repo.get(search.course_id)?.active {
+            return Ok(Vec::new());
+        }
+
+        let title = words(&search.title);
+
+        let mut similar = self
+            .ticket_repo
+            .list_open_with_media(search.course_id, search.type_)?
+            .into_iter()
+            .map(|(ticket, medium)| {
+                let text = text_score(&title, &words(&ticket.ticket.title));
+                let score = match &search.location {
+                    Some(location) => 0.6 * Self::location_score(location, &medium) + 0.4 * text,
+                    None => text,
+                };
+
+                SimilarTicket { ticket, score }
+            })
+            .filter(|t| t.score >= 0.25)
+            .collect::<Vec<_>>();
+
+        similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+        similar.truncate(5);
+
+        Ok(similar)
+    }
+
+    fn add_link(&self, id: Id, target_id: Id, kind: LinkType) -> Result<()> {
+        ensure!(id != target_id, "Ticket can't be linked to itself");
+
+        // Make sure both tickets exist before linking them.
+        self.ticket_repo.get(id)?;
+        self.ticket_repo.get(target_id)?;
+
+        self.ticket_repo.add_link(NewTicketLink {
+            source_id: id,
+            target_id,
+            kind,
+        })
+    }
+
+    fn remove_link(&self, id: Id, link_id: Id) -> Result<()> {
+        self.ticket_repo.remove_link(id, link_id)
+    }
+
+    fn lookup(&self, term: &str) -> Result<Vec<(Id, String)>> {
+        let term = term.trim().trim_start_matches('#');
+        if term.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let by_id = term
+            .parse::<Id>()
+            .ok()
+            .and_then(|id| self.ticket_repo.get(id).ok())
+            .map(|t| (t.id, t.title));
+
+        let by_title = self
+            .ticket_repo
+            .search(&TicketSearch {
+                title: Some(term.to_owned()),
+                program_id: None,
+                course_id: None,
+                category: None,
+                priority: None,
+                status: None,
+                active_only: false,
+                labels: Vec::new(),
+                order: None,
+            })?
+            .into_iter()
+            .map(|t| (t.ticket.id, t.ticket.title));
+
+        let mut found = by_id.into_iter().chain(by_title).collect::<Vec<_>>();
+        found.dedup_by_key(|(id, _)| *id);
+        found.truncate(10);
+
+        Ok(found)
+    }
+
+    fn me_too(&self, id: Id, user_id: Id, role: Role, thresholds: &[u32]) -> Result<()> {
+        let TicketWithNames { ticket, status, .. } = self.ticket_repo.get_with_names(id)?;
+        // Students find other tickets through the search or the similar tickets, which both only
+        // offer the tickets of active courses to join.
+        ensure!(
+            self.can_open(id, user_id, role)? || self.course_repo.get(ticket.course_id)?.active,
+            "Ticket can't be joined"
+        );
+        ensure!(!status.terminal, "Closed tickets can't be joined");
+        ensure!(
+            ticket.duplicate_of.is_none(),
+            "Duplicates can't be joined, only their primary ticket"
+        );
+
+        if ticket.creator_id == user_id {
+            return Ok(());
+        }
+
+        self.ticket_repo.add_watcher(id, user_id)?;
+
+        if let Some(votes) = self.ticket_repo.add_vote(id, user_id)? {
+            // Votes only ever raise the priority, so a manually set higher priority is kept.
+            let priority = map_priority(ticket.category, votes, thresholds);
+            if priority < ticket.priority {
+                self.ticket_repo.update(EditTicket { id, priority })?;
+                self.record_event(
+                    id,
+                    user_id,
+                    EventField::Priority,
+                    Some(ticket.priority.to_string()),
+                    Some(priority.to_string()),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_voted(&self, id: Id, user_id: Id) -> Result<bool> {
+        self.ticket_repo.has_voted(id, user_id)
+    }
+
+    fn watch(&self, id: Id, user_id: Id, role: Role) -> Result<()> {
+        ensure!(self.can_open(id, user_id, role)?, "Ticket can't be watched");
+
+        self.ticket_repo.add_watcher(id, user_id)
+    }
+
+    fn unwatch(&self, id: Id, user_id: Id) -> Result<()> {
+        self.ticket_repo.remove_watcher(id, user_id)
+    }
+
+    fn is_watching(&self, id: Id, user_id: Id) -> Result<bool> {
+        self.ticket_repo.is_watcher(id, user_id)
+    }
+
+    fn can_open(&self, id: Id, user_id: Id, role: Role) -> Result<bool> {
+        // Everyone above a student can always see any ticket details
+        if role < Role::Student {
+            return Ok(true);
+        }
+
+        Ok(self.ticket_repo.is_creator(id, user_id)?
+            || self.ticket_repo.is_program_manager(id, user_id)?)
+    }
+}
+
+/// Decide the priority of a ticket based on its category, raised by one level for each of the
+/// vote `thresholds` that were reached.
+/// Check the medium of a moved ticket. It only needs to be re-entered if the new ticket type uses
+/// a different medium, otherwise the current one is kept.
+fn check_moved_medium(
+    medium: &mut Option<NewMedium>,
+    old_type: MediumType,
+    new_type: MediumType,
+) -> Result<()> {
+    if old_type == new_type {
+        *medium = None;
+        return Ok(());
+    }
+
+    let medium = medium
+        .as_mut()
+        .context("Medium must be re-entered for the new ticket type")?;
+    ensure!(
+        medium.medium_type() == new_type,
+        "Medium doesn't match the ticket type"
+    );
+    medium.sort();
+    Ok(())
+}
+
+fn map_priority(category: Category, votes: u32, thresholds: &[u32]) -> Priority {
+    let priority = match category {
+        Category::Editorial => Priority::Medium,
+        Category::Content => Priority::High,
+        Category::Improvement | Category::Addition => Priority::Low,
+    };
+
+    thresholds
+        .iter()
+        .filter(|&&threshold| threshold > 0 && votes >= threshold)
+        .fold(priority, |priority, _| priority.raised())
+}
+
+/// Calculate the due dates of a ticket, based on the service level targets for its current
+/// priority. Terminal tickets don't have any due dates anymore.
+pub fn due_dates(ticket: &Ticket, terminal: bool, sla: &SlaConfig) -> DueDates {
+    if terminal {
+        return DueDates {
+            response: None,
+            resolution: None,
+        };
+    }
+
+    let target = sla.target(ticket.priority);
+
+    DueDates {
+        response: ticket
+            .first_response_at
+            .is_none()
+            .then(|| ticket.created_at + Duration::hours(target.first_response.into())),
+        resolution: Some(ticket.created_at + Duration::hours(target.resolution.into())),
+    }
+}
+
+/// Split a text into its distinct lowercase words, ignoring very short ones like articles.
+fn words(text: &str) -> FnvHashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Rate the similarity of two sets of words by the share of common words, between `0.0` for no
+/// common words and `1.0` for the same words.
+#[allow(clippy::cast_precision_loss)]
+fn text_score(a: &FnvHashSet<String>, b: &FnvHashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Compare two texts word by word, merging consecutive words with the same kind of change.
+fn diff_words(old: &str, new: &str) -> Vec<DiffPart> {
+    let diff = TextDiff::from_words(old, new);
+    let mut parts = Vec::<DiffPart>::new();
+
+    for change in diff.iter_all_changes() {
+        let kind = match change.tag() {
+            ChangeTag::Equal => DiffKind::Equal,
+            ChangeTag::Insert => DiffKind::Insert,
+            ChangeTag::Delete => DiffKind::Delete,
+        };
+
+        match parts.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(change.value()),
+            _ => parts.push(DiffPart {
+                kind,
+                text: change.value().to_owned(),
+            }),
+        }
+    }
+
+    parts
+}
+
+/// Create a new ticket service.
+pub fn ticket_service(
+    ticket_repo: impl TicketRepository,
+    workflow_repo: impl WorkflowRepository,
+    course_repo: impl CourseRepository,
+    user_repo: impl UserRepository,
     mail_sender: impl MailSender,
     mail_renderer: impl MailRenderer,
 ) -> impl TicketService {
     TicketServiceImpl {
         ticket_repo,
+        workflow_repo,
         course_repo,
         user_repo,
         mail_sender,
         mail_renderer,
     }
 }
+
+/// The attachment service manages files that are attached to tickets or their comments.
+pub trait AttachmentService {
+    /// Validate uploaded files, so they can be checked before the ticket or comment they belong to
+    /// is created. A single invalid file rejects the whole upload.
+    fn validate(&self, files: Vec<Upload>) -> Result<Vec<ValidUpload>>;
+    /// Store validated files as attachments of a ticket, optionally belonging to one of its
+    /// comments. Thumbnails are created for all images.
+    fn add(
+        &self,
+        ticket_id: Id,
+        comment_id: Option<Id>,
+        creator_id: Id,
+        files: Vec<ValidUpload>,
+    ) -> Result<()>;
+    /// Get the metadata of a single attachment that belongs to the given ticket.
+    fn get(&self, id: Id, ticket_id: Id) -> Result<Attachment>;
+    /// Load the content of an attachment.
+    fn load(&self, id: Id) -> Result<Vec<u8>>;
+    /// Load the thumbnail of an image attachment.
+    fn load_thumbnail(&self, id: Id) -> Result<Vec<u8>>;
+}
+
+/// Main implementation of [`AttachmentService`].
+struct AttachmentServiceImpl<'a, AR: AttachmentRepository, FS: FileStore> {
+    attachment_repo: AR,
+    file_store: FS,
+    config: &'a AttachmentConfig,
+}
+
+impl<'a, AR: AttachmentRepository, FS: FileStore> AttachmentServiceImpl<'a, AR, FS> {
+    /// Save a single validated file, removing its database entry again if storing fails.
+    fn save(&self, attachment: NewAttachment, data: &[u8]) -> Result<()> {
+        let is_image = attachment.mime_type.starts_with("image/");
+        let id = self.attachment_repo.create(attachment)?;
+
+        let res = self.file_store.save(id, data).and_then(|_| {
+            if is_image {
+                let mut thumbnail = Cursor::new(Vec::new());
+                image::load_from_memory(data)?
+                    .thumbnail(320, 240)
+                    .write_to(&mut thumbnail, ImageOutputFormat::Png)?;
+                self.file_store.save_thumbnail(id, thumbnail.get_ref())?;
+            }
+
+            Ok(())
+        });
+
+        if res.is_err() {
+            self.file_store.remove(id)?;
+            self.attachment_repo.delete(id)?;
+        }
+
+        res
+    }
+}
+
+impl<'a, AR: AttachmentRepository, FS: FileStore> AttachmentService
+    for AttachmentServiceImpl<'a, AR, FS>
+{
+    fn validate(&self, files: Vec<Upload>) -> Result<Vec<ValidUpload>> {
+        ensure!(
+            files.len() <= self.config.max_files,
+            "Too many files, only {} allowed",
+            self.config.max_files
+        );
+
+        files
+            .into_iter()
+            .map(|file| {
+                ensure!(
+                    file.data.len() as u64 <= self.config.max_size,
+                    "File {} is too large",
+                    file.file_name
+                );
+
+                // Never trust the file extension or the content type sent by the browser.
+                let mime_type = infer::get(&file.data)
+                    .map(|t| t.mime_type())
+                    .context("Unknown file type")?;
+                ensure!(
+                    self.config.allowed_types.iter().any(|t| t == mime_type),
+                    "File type {} not allowed",
+                    mime_type
+                );
+
+                Ok(ValidUpload {
+                    file_name: sanitize_file_name(&file.file_name),
+                    mime_type: mime_type.to_owned(),
+                    data: file.data,
+                })
+            })
+            .collect()
+    }
+
+    fn add(
+        &self,
+        ticket_id: Id,
+        comment_id: Option<Id>,
+        creator_id: Id,
+        files: Vec<ValidUpload>,
+    ) -> Result<()> {
+        for file in files {
+            self.save(
+                NewAttachment {
+                    ticket_id,
+                    comment_id,
+                    creator_id,
+                    file_name: file.file_name,
+                    mime_type: file.mime_type,
+                    size: file.data.len() as u64,
+                    timestamp: Utc::now(),
+                },
+                &file.data,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, id: Id, ticket_id: Id) -> Result<Attachment> {
+        let attachment = self.attachment_repo.get(id)?;
+        ensure!(
+            attachment.ticket_id == ticket_id,
+            "Attachment {} doesn't belong to ticket {}",
+            id,
+            ticket_id
+        );
+
+        Ok(attachment)
+    }
+
+    fn load(&self, id: Id) -> Result<Vec<u8>> {
+        self.file_store.load(id)
+    }
+
+    fn load_thumbnail(&self, id: Id) -> Result<Vec<u8>> {
+        self.file_store.load_thumbnail(id)
+    }
+}
+
+/// Reduce a file name as sent by the browser to its last path segment and remove any characters
+/// that could cause trouble when sending it back in a header.
+fn sanitize_file_name(name: &str) -> String {
+    let name = name
+        .rsplit(|c| c == '/' || c == '\\')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(200)
+        .collect::<String>();
+    let name = name.trim();
+
+    if name.is_empty() {
+        "datei".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Create a new attachment service.
+pub fn attachment_service<'a>(
+    attachment_repo: impl AttachmentRepository + 'a,
+    file_store: impl FileStore + 'a,
+    config: &'a AttachmentConfig,
+) -> impl AttachmentService + 'a {
+    AttachmentServiceImpl {
+        attachment_repo,
+        file_store,
+        config,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use diesel::SqliteConnection;
+    use pretty_assertions::assert_eq;
+
+    use super::{PublicReportService, TicketService};
+    use crate::config::{SlaConfig, SlaTarget};
+    use crate::db::repositories::{
+        self, CourseRepository, PublicReportRepository, TicketRepository, UserRepository,
+    };
+    use crate::db::tests::{connection, insert_course, insert_user};
+    use crate::email::tests::RecordingSender;
+    use crate::email::{self};
+    use crate::models::{
+        Category, EditCourse, EditTicket, EditTicketContent, EventField, Id, Medium, MoveTicket,
+        NewMedium, NewPublicReport, NewTicket, Priority, PublicReport, Role, Span, Status,
+        TextPosition, TicketField, TicketType, TimelineEntry,
+    };
+
+    fn ticket_service(conn: &SqliteConnection, sender: RecordingSender) -> impl TicketService + '_ {
+        super::ticket_service(
+            repositories::ticket_repo(conn),
+            repositories::workflow_repo(conn),
+            repositories::course_repo(conn),
+            repositories::user_repo(conn),
+            sender,
+            email::new_mail_renderer("http://localhost:8080"),
+        )
+    }
+
+    fn new_ticket(course_id: Id, creator_id: Id) -> NewTicket {
+        NewTicket {
+            type_: TicketType::CourseBook,
+            title: "Falsche Formel".to_owned(),
+            description: "Die Formel auf Seite 3 ist falsch.".to_owned(),
+            category: Category::Content,
+            course_id,
+            creator_id,
+            fields: Vec::new(),
+        }
+    }
+
+    fn text_medium() -> NewMedium {
+        NewMedium::Text {
+            locations: vec![Span::new(TextPosition { page: 3, line: 1 }, None).unwrap()],
+        }
+    }
+
+    fn report_course(conn: &SqliteConnection) -> Id {
+        let author = insert_user(conn, "author", Role::Author);
+        let tutor = insert_user(conn, "tutor", Role::Tutor);
+        let course = insert_course(conn, "C1", author, tutor);
+
+        repositories::course_repo(conn)
+            .update(EditCourse {
+                id: course,
+                title: "C1".to_owned(),
+                author_id: author,
+                tutor_id: tutor,
+                public_reports: true,
+            })
+            .unwrap();
+
+        course
+    }
+
+    fn new_report(course_id: Id) -> NewPublicReport {
+        NewPublicReport {
+            course_id,
+            type_: TicketType::CourseBook,
+            category: Category::Content,
+            title: "Falsche Formel".to_owned(),
+            description: "Die Formel auf Seite 3 ist falsch.".to_owned(),
+            medium: text_medium(),
+            fields: vec![TicketField {
+                label: "Auflage".to_owned(),
+                value: "2".to_owned(),
+            }],
+            contact_email: Some("jane@example.com".to_owned()),
+            ip_address: "127.0.0.1".to_owned(),
+        }
+    }
+
+    #[test]
+    fn create_sorts_locations() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let medium = NewMedium::Text {
+            locations: vec![
+                Span::new(TextPosition { page: 7, line: 1 }, None).unwrap(),
+                Span::new(
+                    TextPosition { page: 2, line: 3 },
+                    Some(TextPosition { page: 2, line: 9 }),
+                )
+                .unwrap(),
+            ],
+        };
+        let id = service
+            .create(new_ticket(course, author), medium, &[])
+            .unwrap();
+
+        let locations = match repositories::ticket_repo(&conn)
+            .get_with_rels(id)
+            .unwrap()
+            .medium
+        {
+            Medium::Text { locations, .. } => locations,
+            _ => panic!("ticket should have a text medium"),
+        };
+        assert_eq!(
+            vec![((2, 3), Some((2, 9))), ((7, 1), None)],
+            locations
+                .iter()
+                .map(|l| (
+                    (l.start.page, l.start.line),
+                    l.end.map(|end| (end.page, end.line))
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn move_ticket_needs_course_staff() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let other = insert_user(&conn, "other", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let target = insert_course(&conn, "C2", author, other);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        let move_to = |course_id| MoveTicket {
+            id,
+            course_id,
+            type_: TicketType::CourseBook,
+            medium: None,
+        };
+
+        // The target course's tutor isn't responsible for the ticket yet.
+        service
+            .move_ticket(move_to(target), other, Role::Tutor)
+            .unwrap_err();
+        assert_eq!(course, repo.get(id).unwrap().course_id);
+
+        service
+            .move_ticket(move_to(target), tutor, Role::Tutor)
+            .unwrap();
+
+        let ticket = repo.get(id).unwrap();
+        assert_eq!(target, ticket.course_id);
+        assert_eq!(Some(other), ticket.assignee_id);
+        assert_eq!(
+            vec!["other@iubh-fernstudium.de".to_owned()],
+            sender.recipients()
+        );
+
+        let events = repo
+            .get_with_rels(id)
+            .unwrap()
+            .timeline
+            .into_iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Event(e) => Some(e.event.field),
+                TimelineEntry::Comment(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(events == [EventField::Course, EventField::Assignee]);
+    }
+
+    #[test]
+    fn move_ticket_to_inactive_tutor_unassigns() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let other = insert_user(&conn, "other", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let target = insert_course(&conn, "C2", author, other);
+        repositories::user_repo(&conn).enable(other, false).unwrap();
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        assert_eq!(Some(tutor), repo.get(id).unwrap().assignee_id);
+
+        service
+            .move_ticket(
+                MoveTicket {
+                    id,
+                    course_id: target,
+                    type_: TicketType::CourseBook,
+                    medium: None,
+                },
+                tutor,
+                Role::Tutor,
+            )
+            .unwrap();
+
+        let ticket = repo.get(id).unwrap();
+        assert_eq!(target, ticket.course_id);
+        assert_eq!(None, ticket.assignee_id);
+        assert!(sender.recipients().is_empty());
+
+        let unassigned = service.list_unassigned(Some(target)).unwrap();
+        assert_eq!(
+            vec![id],
+            unassigned.iter().map(|t| t.ticket.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn return_to_tutor_reassigns_and_notifies() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        service.forward(id, tutor).unwrap();
+
+        // Only the author the ticket was forwarded to can hand it back.
+        service
+            .return_to_tutor(id, student, Role::Student, "Erledigt".to_owned())
+            .unwrap_err();
+
+        service
+            .return_to_tutor(id, author, Role::Author, "Erledigt".to_owned())
+            .unwrap();
+
+        let ticket = repo.get(id).unwrap();
+        assert!(!ticket.forwarded);
+        assert_eq!(Some(tutor), ticket.assignee_id);
+        assert_eq!(
+            vec![
+                "author@iubh-fernstudium.de".to_owned(),
+                "tutor@iubh-fernstudium.de".to_owned()
+            ],
+            sender.recipients()
+        );
+
+        let timeline = repo.get_with_rels(id).unwrap().timeline;
+        assert!(timeline.iter().any(|entry| matches!(
+            entry,
+            TimelineEntry::Event(e) if e.event.field == EventField::Returned
+        )));
+        assert!(timeline.iter().any(|entry| matches!(
+            entry,
+            TimelineEntry::Comment(c) if c.comment.internal && c.comment.message == "Erledigt"
+        )));
+    }
+
+    #[test]
+    fn create_assigns_course_tutor() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+
+        let ticket = repositories::ticket_repo(&conn).get(id).unwrap();
+        assert_eq!(Some(tutor), ticket.assignee_id);
+
+        let assigned = service.list_assigned(tutor, Role::Tutor).unwrap();
+        assert_eq!(
+            vec![id],
+            assigned.iter().map(|t| t.ticket.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn create_leaves_ticket_unassigned_for_inactive_tutor() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        repositories::user_repo(&conn).enable(tutor, false).unwrap();
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+
+        let ticket = repositories::ticket_repo(&conn).get(id).unwrap();
+        assert_eq!(None, ticket.assignee_id);
+
+        let unassigned = service.list_unassigned(Some(course)).unwrap();
+        assert_eq!(
+            vec![id],
+            unassigned.iter().map(|t| t.ticket.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn assign_notifies_new_assignee() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        service.assign(id, author, tutor).unwrap();
+
+        let ticket = repositories::ticket_repo(&conn).get(id).unwrap();
+        assert_eq!(Some(author), ticket.assignee_id);
+        assert_eq!(vec!["author@iubh-fernstudium.de"], sender.recipients());
+    }
+
+    #[test]
+    fn reopen_request_needs_staff_decision() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        repo.set_status(id, Status::COMPLETED, Some(Utc::now()))
+            .unwrap();
+
+        service
+            .reopen(id, student, Role::Student, "Immer noch falsch".to_owned())
+            .unwrap_err();
+
+        service
+            .request_reopen(
+                id,
+                student,
+                Role::Student,
+                "Immer noch falsch".to_owned(),
+                14,
+            )
+            .unwrap();
+        assert!(service.has_reopen_request(id).unwrap());
+        assert!(!service.can_reopen(id, student, Role::Student, 14).unwrap());
+        assert!(repo.get(id).unwrap().status == Status::COMPLETED);
+        assert_eq!(vec!["tutor@iubh-fernstudium.de"], sender.recipients());
+
+        service.accept_reopen(id, tutor, Role::Tutor).unwrap();
+        assert!(!service.has_reopen_request(id).unwrap());
+
+        let ticket = repo.get(id).unwrap();
+        assert!(ticket.status == Status::OPEN);
+        assert_eq!(1, ticket.reopen_count);
+
+        let events = repo
+            .get_with_rels(id)
+            .unwrap()
+            .timeline
+            .into_iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Event(e) => Some(e.event.field),
+                TimelineEntry::Comment(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(events == [EventField::ReopenRequested, EventField::Reopened]);
+    }
+
+    #[test]
+    fn refused_reopen_request_keeps_ticket_closed() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        repo.set_status(id, Status::COMPLETED, Some(Utc::now()))
+            .unwrap();
+        service
+            .request_reopen(
+                id,
+                student,
+                Role::Student,
+                "Immer noch falsch".to_owned(),
+                14,
+            )
+            .unwrap();
+
+        service
+            .refuse_reopen(id, tutor, Role::Tutor, "Ist korrekt".to_owned())
+            .unwrap();
+        assert!(!service.has_reopen_request(id).unwrap());
+        assert!(repo.get(id).unwrap().status == Status::COMPLETED);
+        assert_eq!(
+            vec!["tutor@iubh-fernstudium.de", "student@iubh-fernstudium.de"],
+            sender.recipients()
+        );
+    }
+
+    #[test]
+    fn reopen_request_needs_closing_time() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        repositories::ticket_repo(&conn)
+            .set_status(id, Status::COMPLETED, None)
+            .unwrap();
+
+        assert!(!service.can_reopen(id, student, Role::Student, 14).unwrap());
+        service
+            .request_reopen(
+                id,
+                student,
+                Role::Student,
+                "Immer noch falsch".to_owned(),
+                14,
+            )
+            .unwrap_err();
+        assert!(service.can_reopen(id, tutor, Role::Tutor, 14).unwrap());
+    }
+
+    #[test]
+    fn mark_duplicate_notifies_both_tickets() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let first = insert_user(&conn, "first", Role::Student);
+        let second = insert_user(&conn, "second", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+
+        let primary = service
+            .create(new_ticket(course, first), text_medium(), &[])
+            .unwrap();
+        let duplicate = service
+            .create(new_ticket(course, second), text_medium(), &[])
+            .unwrap();
+
+        service
+            .mark_duplicate(duplicate, primary, second, Role::Student)
+            .unwrap_err();
+
+        service
+            .mark_duplicate(duplicate, primary, tutor, Role::Tutor)
+            .unwrap();
+
+        let ticket = repositories::ticket_repo(&conn).get(duplicate).unwrap();
+        assert!(ticket.status == Status::DUPLICATE);
+        assert_eq!(Some(primary), ticket.duplicate_of);
+
+        let mut recipients = sender.recipients();
+        recipients.sort();
+        assert_eq!(
+            vec![
+                "first@iubh-fernstudium.de".to_owned(),
+                "second@iubh-fernstudium.de".to_owned(),
+            ],
+            recipients
+        );
+    }
+
+    #[test]
+    fn me_too_grants_no_access() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let first = insert_user(&conn, "first", Role::Student);
+        let second = insert_user(&conn, "second", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let id = service
+            .create(new_ticket(course, first), text_medium(), &[])
+            .unwrap();
+
+        repositories::course_repo(&conn)
+            .enable(course, false)
+            .unwrap();
+        service.me_too(id, second, Role::Student, &[]).unwrap_err();
+
+        repositories::course_repo(&conn)
+            .enable(course, true)
+            .unwrap();
+        service.me_too(id, second, Role::Student, &[]).unwrap();
+        assert!(!service.can_open(id, second, Role::Student).unwrap());
+    }
+
+    #[test]
+    fn map_priority_thresholds() {
+        let thresholds = [3, 5, 10];
+
+        assert!(super::map_priority(Category::Improvement, 2, &thresholds) == Priority::Low);
+        assert!(super::map_priority(Category::Improvement, 3, &thresholds) == Priority::Medium);
+        assert!(super::map_priority(Category::Improvement, 7, &thresholds) == Priority::High);
+        assert!(super::map_priority(Category::Improvement, 10, &thresholds) == Priority::Critical);
+        assert!(super::map_priority(Category::Content, 10, &thresholds) == Priority::Critical);
+        assert!(super::map_priority(Category::Editorial, 10, &[]) == Priority::Medium);
+        assert!(super::map_priority(Category::Editorial, 10, &[0]) == Priority::Medium);
+    }
+
+    #[test]
+    fn me_too_keeps_manual_priority() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let creator = insert_user(&conn, "creator", Role::Student);
+        let first = insert_user(&conn, "first", Role::Student);
+        let second = insert_user(&conn, "second", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, creator), text_medium(), &[])
+            .unwrap();
+        assert!(repo.get(id).unwrap().priority == Priority::High);
+
+        service.me_too(id, first, Role::Student, &[1]).unwrap();
+        assert!(repo.get(id).unwrap().priority == Priority::Critical);
+
+        repo.update(EditTicket {
+            id,
+            priority: Priority::Critical,
+        })
+        .unwrap();
+        service.me_too(id, second, Role::Student, &[5]).unwrap();
+        assert!(repo.get(id).unwrap().priority == Priority::Critical);
+        assert_eq!(2, repo.get(id).unwrap().votes);
+    }
+
+    #[test]
+    fn vote_grants_no_access() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let creator = insert_user(&conn, "creator", Role::Student);
+        let voter = insert_user(&conn, "voter", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+
+        let id = service
+            .create(new_ticket(course, creator), text_medium(), &[])
+            .unwrap();
+        service.me_too(id, voter, Role::Student, &[]).unwrap();
+
+        assert!(service.has_voted(id, voter).unwrap());
+        assert!(!service.can_open(id, voter, Role::Student).unwrap());
+    }
+
+    #[test]
+    fn watching_grants_no_access() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let creator = insert_user(&conn, "creator", Role::Student);
+        let voter = insert_user(&conn, "voter", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+
+        let id = service
+            .create(new_ticket(course, creator), text_medium(), &[])
+            .unwrap();
+        service.me_too(id, voter, Role::Student, &[]).unwrap();
+        assert!(service.is_watching(id, voter).unwrap());
+        assert!(!service.can_open(id, voter, Role::Student).unwrap());
+
+        service.watch(id, voter, Role::Student).unwrap_err();
+        service
+            .add_comment(id, voter, Role::Student, "Ich auch".to_owned(), false)
+            .unwrap_err();
+        service
+            .add_comment(id, tutor, Role::Tutor, "Wird behoben".to_owned(), false)
+            .unwrap();
+
+        assert_eq!(
+            vec!["creator@iubh-fernstudium.de".to_owned()],
+            sender.recipients()
+        );
+    }
+
+    #[test]
+    fn edit_content_keeps_revision() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let service = ticket_service(&conn, RecordingSender::default());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = service
+            .create(new_ticket(course, student), text_medium(), &[])
+            .unwrap();
+        service
+            .edit_content(
+                EditTicketContent {
+                    id,
+                    title: "Tippfehler auf Seite 4".to_owned(),
+                    description: "Test".to_owned(),
+                    medium: NewMedium::Text {
+                        locations: vec![Span::new(TextPosition { page: 4, line: 1 }, None).unwrap()],
+                    },
+                },
+                student,
+                Role::Student,
+            )
+            .unwrap();
+
+        let ticket = repo.get_with_rels(id).unwrap();
+        assert_eq!("Tippfehler auf Seite 4", ticket.ticket.title);
+        assert_eq!(1, repo.list_revisions(id).unwrap().len());
+
+        let events = ticket
+            .timeline
+            .into_iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Event(e) => Some(e.event.field),
+                TimelineEntry::Comment(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(events == [EventField::Edited]);
+    }
+
+    #[test]
+    fn due_dates_by_priority() {
+        let conn = connection();
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let repo = repositories::ticket_repo(&conn);
+        let sla = SlaConfig::default();
+
+        let created_at = Utc::now() - Duration::hours(1);
+        let id = repo
+            .create(
+                new_ticket(course, student),
+                Priority::Critical,
+                created_at,
+                text_medium(),
+            )
+            .unwrap();
+        let mut ticket = repo.get(id).unwrap();
+
+        let due = super::due_dates(&ticket, false, &sla);
+        assert_eq!(Some(ticket.created_at + Duration::hours(4)), due.response);
+        assert_eq!(
+            Some(ticket.created_at + Duration::hours(24)),
+            due.resolution
+        );
+        assert!(!due.is_overdue());
+
+        ticket.priority = Priority::Low;
+        let due = super::due_dates(&ticket, false, &sla);
+        assert_eq!(Some(ticket.created_at + Duration::hours(72)), due.response);
+        assert_eq!(
+            Some(ticket.created_at + Duration::hours(336)),
+            due.resolution
+        );
+
+        // Once answered, only the resolution is still due.
+        ticket.first_response_at = Some(Utc::now());
+        let due = super::due_dates(&ticket, false, &sla);
+        assert_eq!(None, due.response);
+        assert_eq!(
+            Some(ticket.created_at + Duration::hours(336)),
+            due.resolution
+        );
+
+        // Closed tickets aren't due anymore.
+        ticket.first_response_at = None;
+        let due = super::due_dates(&ticket, true, &sla);
+        assert_eq!(None, due.response);
+        assert_eq!(None, due.resolution);
+    }
+
+    #[test]
+    fn escalate_assignee_first() {
+        let conn = connection();
+        insert_user(&conn, "admin", Role::Admin);
+        let author = insert_user(&conn, "author", Role::Author);
+        let tutor = insert_user(&conn, "tutor", Role::Tutor);
+        let student = insert_user(&conn, "student", Role::Student);
+        let course = insert_course(&conn, "C1", author, tutor);
+        let sender = RecordingSender::default();
+        let service = ticket_service(&conn, sender.clone());
+        let repo = repositories::ticket_repo(&conn);
+
+        let id = repo
+            .create(
+                new_ticket(course, student),
+                Priority::High,
+                Utc::now() - Duration::days(30),
+                text_medium(),
+            )
+            .unwrap();
+        repo.assign(id, tutor).unwrap();
+
+        let target = SlaTarget {
+            first_response: 1,
+            resolution: 2,
+        };
+        let sla = SlaConfig {
+            critical: target.clone(),
+            high: target.clone(),
+            medium: target.clone(),
+            low: target,
+            check_interval: 15,
+            escalation_delay: 0,
+        };
+
+        service.escalate_overdue(&sla).unwrap();
+        assert_eq!(
+            vec!["tutor@iubh-fernstudium.de".to_owned()],
+            sender.recipients()
+        );
+        assert_eq!(1, repo.get(id).unwrap().escalation_level);
+
+        service.escalate_overdue(&sla).unwrap();
+        let mut managers = sender.recipients().split_off(1);
+        managers.sort();
+        assert_eq!(
+            vec![
+                "admin@iubh-fernstudium.de".to_owned(),
+                "author@iubh-fernstudium.de".to_owned(),
+            ],
+            managers
+        );
+        assert_eq!(2, repo.get(id).unwrap().escalation_level);
+
+        // The last level is only reached once.
+        service.escalate_overdue(&sla).unwrap();
+        assert_eq!(3, sender.recipients().len());
+    }
+
+    #[test]
+    fn submit_limits_reports_per_address() {
+        let conn = connection();
+        let course = report_course(&conn);
+        let service = super::public_report_service(
+            repositories::public_report_repo(&conn),
+            repositories::course_repo(&conn),
+        );
+
+        assert!(service.submit(new_report(course), 2).unwrap());
+        assert!(service.submit(new_report(course), 2).unwrap());
+        assert!(!service.submit(new_report(course), 2).unwrap());
+
+        let mut other = new_report(course);
+        other.ip_address = "127.0.0.2".to_owned();
+        assert!(service.submit(other, 2).unwrap());
+
+        assert_eq!(3, service.list_pending(0, Role::Admin).unwrap().len());
+    }
+
+    #[test]
+    fn approve_report_keeps_fields() {
+        let conn = connection();
+        let course = report_course(&conn);
+        let admin = insert_user(&conn, "admin", Role::Admin);
+        let reports = super::public_report_service(
+            repositories::public_report_repo(&conn),
+            repositories::course_repo(&conn),
+        );
+        let tickets = ticket_service(&conn, RecordingSender::default());
+
+        reports.submit(new_report(course), 5).unwrap();
+        let id = reports.list_pending(admin, Role::Admin).unwrap()[0]
+            .report
+            .id;
+
+        let mut ticket_id = 0;
+        reports
+            .approve(id, admin, Role::Admin, &mut |report| {
+                let (ticket, medium) = report.into_ticket(admin);
+                ticket_id = tickets.create(ticket, medium, &[])?;
+                Ok(ticket_id)
+            })
+            .unwrap();
+
+        let ticket = repositories::ticket_repo(&conn)
+            .get_with_rels(ticket_id)
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("Auflage", "2"),
+                (PublicReport::CONTACT_EMAIL_LABEL, "jane@example.com"),
+            ],
+            ticket
+                .fields
+                .iter()
+                .map(|f| (f.label.as_str(), f.value.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        // An approved report can't be approved a second time.
+        reports
+            .approve(id, admin, Role::Admin, &mut |_| unreachable!())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn approve_report_rolls_back_ticket() {
+        let conn = connection();
+        let course = report_course(&conn);
+        let admin = insert_user(&conn, "admin", Role::Admin);
+        let reports = super::public_report_service(
+            repositories::public_report_repo(&conn),
+            repositories::course_repo(&conn),
+        );
+        let tickets = ticket_service(&conn, RecordingSender::default());
+
+        reports.submit(new_report(course), 5).unwrap();
+        let id = reports.list_pending(admin, Role::Admin).unwrap()[0]
+            .report
+            .id;
+
+        // Another moderator rejects the report while the ticket is being created.
+        reports
+            .approve(id, admin, Role::Admin, &mut |report| {
+                let (ticket, medium) = report.into_ticket(admin);
+                let ticket_id = tickets.create(ticket, medium, &[])?;
+                repositories::public_report_repo(&conn).reject(id)?;
+                Ok(ticket_id)
+            })
+            .unwrap_err();
+
+        assert!(repositories::ticket_repo(&conn)
+            .list_with_names()
+            .unwrap()
+            .is_empty());
+    }
+}
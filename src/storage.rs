@@ -0,0 +1,83 @@
+//! Storage for the content of uploaded files.
+//!
+//! Only the metadata of attachments is kept in the database, while the actual file content is
+//! stored separately, as large binary blobs don't fit well into the database.
+
+use std::fs;
+
+use anyhow::{Context, Result};
+use unidirs::{Utf8Path, Utf8PathBuf};
+
+use crate::models::Id;
+
+/// A file store saves and loads the content of attachments by their ID.
+pub trait FileStore {
+    /// Save the content of an attachment.
+    fn save(&self, id: Id, data: &[u8]) -> Result<()>;
+    /// Load the content of an attachment.
+    fn load(&self, id: Id) -> Result<Vec<u8>>;
+    /// Save the thumbnail of an image attachment.
+    fn save_thumbnail(&self, id: Id, data: &[u8]) -> Result<()>;
+    /// Load the thumbnail of an image attachment.
+    fn load_thumbnail(&self, id: Id) -> Result<Vec<u8>>;
+    /// Remove the content and thumbnail of an attachment, if they exist.
+    fn remove(&self, id: Id) -> Result<()>;
+}
+
+/// Main implementation of [`FileStore`] that keeps each file in a local directory.
+struct DiskStore {
+    dir: Utf8PathBuf,
+}
+
+impl DiskStore {
+    fn file_path(&self, id: Id) -> Utf8PathBuf {
+        self.dir.join(id.to_string())
+    }
+
+    fn thumbnail_path(&self, id: Id) -> Utf8PathBuf {
+        self.dir.join(format!("{}_thumb.png", id))
+    }
+
+    fn write(&self, path: &Utf8Path, data: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed creating attachment directory {}", self.dir))?;
+        fs::write(path, data).with_context(|| format!("Failed writing file {}", path))
+    }
+}
+
+impl FileStore for DiskStore {
+    fn save(&self, id: Id, data: &[u8]) -> Result<()> {
+        self.write(&self.file_path(id), data)
+    }
+
+    fn load(&self, id: Id) -> Result<Vec<u8>> {
+        let path = self.file_path(id);
+        fs::read(&path).with_context(|| format!("Failed reading file {}", path))
+    }
+
+    fn save_thumbnail(&self, id: Id, data: &[u8]) -> Result<()> {
+        self.write(&self.thumbnail_path(id), data)
+    }
+
+    fn load_thumbnail(&self, id: Id) -> Result<Vec<u8>> {
+        let path = self.thumbnail_path(id);
+        fs::read(&path).with_context(|| format!("Failed reading file {}", path))
+    }
+
+    fn remove(&self, id: Id) -> Result<()> {
+        for path in [self.file_path(id), self.thumbnail_path(id)] {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed removing file {}", path))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Create a new file store that saves files in the given directory.
+pub fn new_disk_store(dir: &Utf8Path) -> impl FileStore {
+    DiskStore {
+        dir: dir.to_owned(),
+    }
+}
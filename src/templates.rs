@@ -2,12 +2,16 @@
 
 use askama::Template;
 use chrono::Timelike;
+use fnv::FnvHashMap;
 use strum::{AsRefStr, EnumString};
 
 use crate::language::Translate;
 use crate::models::{
-    Category, Course, CourseWithNames, Id, Medium, MediumType, Priority, Role, Status,
-    TicketSearch, TicketType, TicketWithNames, TicketWithRels, User,
+    BulkOutcome, Category, CommentRevisionWithNames, Course, CourseTree, DiffKind, DueDates,
+    EventField, Faculty, Id, Label, LabelWithNames, LinkType, Medium, MediumType, Priority,
+    ProgramWithRels, PublicReportWithNames, RevisionDiff, Role, RuleOutcome, RuleWithNames,
+    StatusColor, TicketOrder, TicketSearch, TicketTemplate, TicketType, TicketWithNames,
+    TicketWithRels, TimelineEntry, User, Workflow, WorkflowStatus,
 };
 
 mod filters {
@@ -62,27 +66,46 @@ mod filters {
 
     /// Compare two values and return ` selected` if they match, an empty string otherwise. This is
     /// helpful in pre-selecting a value in HTML `<select>` elements.
-    pub fn select<T: Copy + Eq>(value: &T, other: T) -> askama::Result<&'static str> {
-        Ok(if *value == other { " selected" } else { "" })
+    pub fn select<T: Eq>(value: &T, other: T) -> askama::Result<&'static str> {
+        select_ref(value, &other)
     }
 
     /// Same as [`select`], but takes `other` as a reference.
-    pub fn select_ref<T: Copy + Eq>(value: &T, other: &T) -> askama::Result<&'static str> {
-        select(value, *other)
+    pub fn select_ref<T: Eq>(value: &T, other: &T) -> askama::Result<&'static str> {
+        Ok(if value == other { " selected" } else { "" })
     }
 
     /// Compare two values exactly as [`select`] but with the first value being optional.
     /// If the first value is [`None`], an empty string is returned.
-    pub fn opt_select<T: Copy + Eq>(opt: &Option<T>, other: T) -> askama::Result<&'static str> {
-        opt.as_ref().map_or(Ok(""), |value| select(value, other))
+    pub fn opt_select<T: Eq>(opt: &Option<T>, other: T) -> askama::Result<&'static str> {
+        opt_select_ref(opt, &other)
     }
 
     /// Same as [`opt_select`], but takes `other` as a reference.
-    pub fn opt_select_ref<T: Copy + Eq>(
-        opt: &Option<T>,
-        other: &T,
-    ) -> askama::Result<&'static str> {
-        opt_select(opt, *other)
+    pub fn opt_select_ref<T: Eq>(opt: &Option<T>, other: &T) -> askama::Result<&'static str> {
+        opt.as_ref()
+            .map_or(Ok(""), |value| select_ref(value, other))
+    }
+
+    /// Render Markdown formatted user content as sanitized HTML.
+    pub fn render_markdown(input: &str) -> askama::Result<String> {
+        Ok(crate::markdown::to_html(input))
+    }
+
+    /// Same as [`markdown`], but additionally links mentions of the given users, which are pairs
+    /// of username and name.
+    pub fn markdown_mentions(input: &str, users: &[(String, String)]) -> askama::Result<String> {
+        Ok(crate::markdown::to_html_with_mentions(input, users))
+    }
+
+    /// Same as [`select_ref`], but checks whether `other` is contained in a list of values. This
+    /// is helpful for pre-selecting values in HTML `<select multiple>` elements.
+    pub fn multi_select<T: Eq>(values: &[T], other: &T) -> askama::Result<&'static str> {
+        Ok(if values.contains(other) {
+            " selected"
+        } else {
+            ""
+        })
     }
 }
 
@@ -95,18 +118,33 @@ trait Color {
     fn tag(&self) -> &'static str;
 }
 
-impl Color for Status {
+impl Color for StatusColor {
     fn tag(&self) -> &'static str {
         match self {
-            Self::Open => "is-primary",
-            Self::InProgress => "is-info",
-            Self::Accepted => "is-success",
-            Self::Refused => "is-danger",
-            Self::Completed => "is-light",
+            Self::Primary => "is-primary",
+            Self::Link => "is-link",
+            Self::Info => "is-info",
+            Self::Success => "is-success",
+            Self::Warning => "is-warning",
+            Self::Danger => "is-danger",
+            Self::Light => "is-light",
+            Self::Dark => "is-dark",
         }
     }
 }
 
+impl Color for WorkflowStatus {
+    fn tag(&self) -> &'static str {
+        self.color.tag()
+    }
+}
+
+impl Color for Label {
+    fn tag(&self) -> &'static str {
+        self.color.tag()
+    }
+}
+
 /// The icon trait allows to show an icon representation of the implementing object within a
 /// template.
 ///
@@ -114,18 +152,12 @@ impl Color for Status {
 /// different icons. The values are CSS classes and bound tot the used icon font.
 trait Icon {
     /// The icon to be shown.
-    fn icon(&self) -> &'static str;
+    fn icon(&self) -> &str;
 }
 
-impl Icon for Status {
-    fn icon(&self) -> &'static str {
-        match self {
-            Self::Open => "fa-envelope",
-            Self::InProgress => "fa-cogs",
-            Self::Accepted => "fa-check",
-            Self::Refused => "fa-times",
-            Self::Completed => "fa-archive",
-        }
+impl Icon for WorkflowStatus {
+    fn icon(&self) -> &str {
+        &self.icon
     }
 }
 
@@ -144,6 +176,39 @@ pub enum MessageCode {
     FailedTicketCreation,
     FailedTicketUpdate,
     FailedCommentCreation,
+    FailedTicketAssignment,
+    FailedTicketReopen,
+    FailedFacultyCreation,
+    FailedProgramCreation,
+    FailedProgramUpdate,
+    FailedStatusCreation,
+    FailedStatusUpdate,
+    FailedTransitionCreation,
+    FailedTransitionDeletion,
+    FailedReopenRequest,
+    FailedReopenRefusal,
+    FailedAttachmentUpload,
+    FailedTicketMerge,
+    FailedTicketJoin,
+    FailedLinkCreation,
+    FailedLinkDeletion,
+    FailedTicketWatch,
+    FailedTicketUnwatch,
+    FailedCommentUpdate,
+    FailedCommentDeletion,
+    FailedRuleCreation,
+    FailedRuleDeletion,
+    FailedLabelCreation,
+    FailedLabelDeletion,
+    FailedTicketLabelUpdate,
+    FailedTemplateUpdate,
+    MissingTicketFields,
+    FailedReportSubmission,
+    TooManyReports,
+    FailedReportApproval,
+    FailedReportRejection,
+    FailedTicketMove,
+    FailedTicketReturn,
     // Success codes
     UserCreated,
     UserUpdated,
@@ -153,6 +218,36 @@ pub enum MessageCode {
     TicketCreated,
     TicketUpdated,
     CommentCreated,
+    TicketAssigned,
+    TicketReopened,
+    FacultyCreated,
+    ProgramCreated,
+    ProgramUpdated,
+    StatusCreated,
+    StatusUpdated,
+    TransitionCreated,
+    TransitionDeleted,
+    ReopenRequested,
+    ReopenRefused,
+    TicketMerged,
+    TicketJoined,
+    LinkCreated,
+    LinkDeleted,
+    TicketWatched,
+    TicketUnwatched,
+    CommentUpdated,
+    CommentDeleted,
+    RuleCreated,
+    RuleDeleted,
+    LabelCreated,
+    LabelDeleted,
+    TicketLabelsUpdated,
+    TemplateUpdated,
+    ReportSubmitted,
+    ReportApproved,
+    ReportRejected,
+    TicketMoved,
+    TicketReturned,
     // Unknown
     Unknown,
 }
@@ -169,6 +264,41 @@ impl Translate for MessageCode {
             Self::FailedTicketCreation => "Ticketerstellung fehlgeschlagen",
             Self::FailedTicketUpdate => "Ticketbearbeitung fehlgeschlagen",
             Self::FailedCommentCreation => "Kommentarerstellung fehlgeschlagen",
+            Self::FailedTicketAssignment => "Ticketzuweisung fehlgeschlagen",
+            Self::FailedTicketReopen => "Wiederer\u{00f6}ffnung des Tickets fehlgeschlagen",
+            Self::FailedFacultyCreation => "Fakult\u{00e4}tserstellung fehlgeschlagen",
+            Self::FailedProgramCreation => "Studiengangserstellung fehlgeschlagen",
+            Self::FailedProgramUpdate => "Studiengangsbearbeitung fehlgeschlagen",
+            Self::FailedStatusCreation => "Statuserstellung fehlgeschlagen",
+            Self::FailedStatusUpdate => "Statusbearbeitung fehlgeschlagen",
+            Self::FailedTransitionCreation => "\u{00dc}bergangserstellung fehlgeschlagen",
+            Self::FailedTransitionDeletion => "\u{00dc}bergangsentfernung fehlgeschlagen",
+            Self::FailedReopenRequest => "Antrag auf Wiederer\u{00f6}ffnung fehlgeschlagen",
+            Self::FailedReopenRefusal => "Ablehnen der Wiederer\u{00f6}ffnung fehlgeschlagen",
+            Self::FailedAttachmentUpload => "Hochladen der Anh\u{00e4}nge fehlgeschlagen",
+            Self::FailedTicketMerge => "Zusammenf\u{00fc}hrung der Tickets fehlgeschlagen",
+            Self::FailedTicketJoin => "Beitritt zum Ticket fehlgeschlagen",
+            Self::FailedLinkCreation => "Verkn\u{00fc}pfung der Tickets fehlgeschlagen",
+            Self::FailedLinkDeletion => "Entfernen der Verkn\u{00fc}pfung fehlgeschlagen",
+            Self::FailedTicketWatch => "Beobachten des Tickets fehlgeschlagen",
+            Self::FailedTicketUnwatch => "Beenden der Beobachtung fehlgeschlagen",
+            Self::FailedCommentUpdate => "Kommentarbearbeitung fehlgeschlagen",
+            Self::FailedCommentDeletion => "Entfernen des Kommentars fehlgeschlagen",
+            Self::FailedRuleCreation => "Regelerstellung fehlgeschlagen",
+            Self::FailedRuleDeletion => "Entfernen der Regel fehlgeschlagen",
+            Self::FailedLabelCreation => "Labelerstellung fehlgeschlagen",
+            Self::FailedLabelDeletion => "Entfernen des Labels fehlgeschlagen",
+            Self::FailedTicketLabelUpdate => "Aktualisierung der Labels fehlgeschlagen",
+            Self::FailedTemplateUpdate => "Vorlagenbearbeitung fehlgeschlagen",
+            Self::MissingTicketFields => "Bitte alle Pflichtfelder der Vorlage ausf\u{00fc}llen",
+            Self::FailedReportSubmission => "Senden der Meldung fehlgeschlagen",
+            Self::TooManyReports => {
+                "Zu viele Meldungen in kurzer Zeit, bitte sp\u{00e4}ter erneut versuchen"
+            }
+            Self::FailedReportApproval => "Freigabe der Meldung fehlgeschlagen",
+            Self::FailedReportRejection => "Ablehnung der Meldung fehlgeschlagen",
+            Self::FailedTicketMove => "Verschieben des Tickets fehlgeschlagen",
+            Self::FailedTicketReturn => "R\u{00fc}ckgabe des Tickets fehlgeschlagen",
             Self::UserCreated => "Account erfolgreich erstellt",
             Self::UserUpdated => "Account erfolgreich bearbeitet",
             Self::UserActivated => "Account erfolgreich aktiviert",
@@ -177,6 +307,36 @@ impl Translate for MessageCode {
             Self::TicketCreated => "Ticket erfolgreich erstellt",
             Self::TicketUpdated => "Ticket erfolgreich bearbeitet",
             Self::CommentCreated => "Kommentar erfolgreich erstellt",
+            Self::TicketAssigned => "Ticket erfolgreich zugewiesen",
+            Self::TicketReopened => "Ticket erfolgreich wiederer\u{00f6}ffnet",
+            Self::FacultyCreated => "Fakult\u{00e4}t erfolgreich erstellt",
+            Self::ProgramCreated => "Studiengang erfolgreich erstellt",
+            Self::ProgramUpdated => "Studiengang erfolgreich bearbeitet",
+            Self::StatusCreated => "Status erfolgreich erstellt",
+            Self::StatusUpdated => "Status erfolgreich bearbeitet",
+            Self::TransitionCreated => "\u{00dc}bergang erfolgreich erstellt",
+            Self::TransitionDeleted => "\u{00dc}bergang erfolgreich entfernt",
+            Self::ReopenRequested => "Wiederer\u{00f6}ffnung erfolgreich beantragt",
+            Self::ReopenRefused => "Wiederer\u{00f6}ffnung erfolgreich abgelehnt",
+            Self::TicketMerged => "Ticket erfolgreich als Duplikat zusammengef\u{00fc}hrt",
+            Self::TicketJoined => "Du wirst \u{00fc}ber \u{00c4}nderungen am Ticket benachrichtigt",
+            Self::LinkCreated => "Tickets erfolgreich verkn\u{00fc}pft",
+            Self::LinkDeleted => "Verkn\u{00fc}pfung erfolgreich entfernt",
+            Self::TicketWatched => "Du beobachtest das Ticket jetzt",
+            Self::TicketUnwatched => "Du beobachtest das Ticket nicht mehr",
+            Self::CommentUpdated => "Kommentar erfolgreich bearbeitet",
+            Self::CommentDeleted => "Kommentar erfolgreich entfernt",
+            Self::RuleCreated => "Regel erfolgreich erstellt",
+            Self::RuleDeleted => "Regel erfolgreich entfernt",
+            Self::LabelCreated => "Label erfolgreich erstellt",
+            Self::LabelDeleted => "Label erfolgreich entfernt",
+            Self::TicketLabelsUpdated => "Labels erfolgreich aktualisiert",
+            Self::TemplateUpdated => "Vorlage erfolgreich bearbeitet",
+            Self::ReportSubmitted => "Vielen Dank, die Meldung wurde erfolgreich gesendet",
+            Self::ReportApproved => "Meldung erfolgreich als Ticket freigegeben",
+            Self::ReportRejected => "Meldung erfolgreich abgelehnt",
+            Self::TicketMoved => "Ticket erfolgreich verschoben",
+            Self::TicketReturned => "Ticket erfolgreich an den Tutor zur\u{00fc}ckgegeben",
             Self::Unknown => "Unbekannter Fehler",
         }
     }
@@ -190,6 +350,8 @@ pub struct Index {
     pub name: String,
     pub created_tickets: Vec<TicketWithNames>,
     pub assigned_tickets: Vec<TicketWithNames>,
+    pub program_manager: bool,
+    pub overdue: Vec<Id>,
 }
 
 /// Template for the login page.
@@ -248,7 +410,7 @@ pub struct EditUser {
 pub struct Courses {
     pub role: Role,
     pub flash: Option<(String, MessageCode)>,
-    pub courses: Vec<CourseWithNames>,
+    pub tree: CourseTree,
 }
 
 /// Template for the new course page.
@@ -272,22 +434,174 @@ pub struct EditCourse {
     pub course: Course,
 }
 
+/// Template for the program management page.
+#[derive(Template)]
+#[template(path = "programs/index.html")]
+pub struct Programs {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub tree: CourseTree,
+}
+
+/// Template for the new program page.
+#[derive(Template)]
+#[template(path = "programs/new.html")]
+pub struct NewProgram {
+    pub role: Role,
+    pub flash: Option<MessageCode>,
+    pub faculties: Vec<Faculty>,
+    pub courses: Vec<(Id, String)>,
+    pub staff: Vec<(Id, String)>,
+}
+
+/// Template for the label management page.
+#[derive(Template)]
+#[template(path = "labels/index.html")]
+pub struct Labels {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub labels: Vec<LabelWithNames>,
+    pub courses: Vec<(Id, String)>,
+}
+
+/// Template for the intake template management page.
+#[derive(Template)]
+#[template(path = "intake/index.html")]
+pub struct TicketTemplates {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub templates: Vec<TicketTemplate>,
+}
+
+/// Template for the edit program page.
+#[derive(Template)]
+#[template(path = "programs/edit.html")]
+pub struct EditProgram {
+    pub role: Role,
+    pub flash: Option<MessageCode>,
+    pub faculties: Vec<Faculty>,
+    pub courses: Vec<(Id, String)>,
+    pub staff: Vec<(Id, String)>,
+    pub program: ProgramWithRels,
+}
+
+/// Template for the workflow management page.
+#[derive(Template)]
+#[template(path = "workflow/index.html")]
+pub struct EditWorkflow {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub workflow: Workflow,
+}
+
+/// Template for the rule management page.
+#[derive(Template)]
+#[template(path = "rules/index.html")]
+pub struct Rules {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub rules: Vec<RuleWithNames>,
+    pub courses: Vec<(Id, String)>,
+    pub staff: Vec<(Id, String)>,
+    pub labels: Vec<LabelWithNames>,
+}
+
+/// Template for the rule test page.
+#[derive(Template)]
+#[template(path = "rules/test.html")]
+pub struct TestRules {
+    pub role: Role,
+    pub rules: Vec<RuleWithNames>,
+    pub courses: Vec<(Id, String)>,
+    pub course_id: Option<Id>,
+    pub ty: Option<TicketType>,
+    pub category: Option<Category>,
+    pub title: String,
+    pub description: String,
+    /// Result of the evaluation, if a complete sample ticket was given.
+    pub outcome: Option<RuleOutcome>,
+    pub assignee_name: Option<String>,
+}
+
 /// Template for the new ticket page.
 #[derive(Template)]
 #[template(path = "tickets/new/index.html")]
 pub struct NewTicket {
     pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub ty: TicketType,
+    pub courses: Vec<(Id, String)>,
+    pub template: TicketTemplate,
+}
+
+/// Template for the ticket move page, where staff picks a new course or ticket type. The medium
+/// has to be entered again if the new type uses a different kind of medium.
+#[derive(Template)]
+#[template(path = "tickets/move.html")]
+pub struct MoveTicket {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub ticket: TicketWithRels,
     pub ty: TicketType,
     pub courses: Vec<(Id, String)>,
 }
 
+/// Template for the public report page of a course, where the ticket type is chosen.
+#[derive(Template)]
+#[template(path = "reports/course.html")]
+pub struct PublicReportCourse {
+    pub flash: Option<(String, MessageCode)>,
+    pub course: Course,
+}
+
+/// Template for the public report form of a course and ticket type.
+#[derive(Template)]
+#[template(path = "reports/new.html")]
+pub struct NewPublicReport {
+    pub flash: Option<(String, MessageCode)>,
+    pub course: Course,
+    pub ty: TicketType,
+    pub template: TicketTemplate,
+}
+
+/// Template for the moderation queue of public reports.
+#[derive(Template)]
+#[template(path = "reports/index.html")]
+pub struct PublicReports {
+    pub role: Role,
+    pub flash: Option<(String, MessageCode)>,
+    pub reports: Vec<PublicReportWithNames>,
+}
+
 /// Template for the ticket detail page.
 #[derive(Template)]
 #[template(path = "tickets/edit/index.html")]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TicketDetail {
     pub role: Role,
+    pub user_id: Id,
     pub flash: Option<(String, MessageCode)>,
     pub ticket: TicketWithRels,
+    pub assignees: Vec<(Id, String)>,
+    pub next_statuses: Vec<WorkflowStatus>,
+    /// Labels that can be assigned to the ticket.
+    pub labels: Vec<Label>,
+    /// IDs of the labels that are currently assigned to the ticket.
+    pub label_ids: Vec<Id>,
+    pub can_reopen: bool,
+    /// Whether the ticket's creator asked for the ticket to be reopened.
+    pub reopen_requested: bool,
+    pub has_voted: bool,
+    pub is_watching: bool,
+    pub can_edit: bool,
+    /// Whether the user can return the forwarded ticket to the course's tutor.
+    pub can_return: bool,
+    pub revisions: Vec<RevisionDiff>,
+    pub editable_comments: Vec<Id>,
+    pub comment_revisions: Vec<CommentRevisionWithNames>,
+    pub mentioned_users: Vec<(String, String)>,
+    pub due: DueDates,
+    pub link_types: [LinkType; 7],
 }
 
 /// Template for the ticket search page.
@@ -298,7 +612,37 @@ pub struct SearchTickets {
     pub user_id: Id,
     pub tickets: Vec<TicketWithNames>,
     pub courses: Vec<(Id, String)>,
+    pub programs: Vec<(Id, String)>,
+    pub statuses: Vec<WorkflowStatus>,
+    /// Staff members that tickets can be assigned to with a bulk action.
+    pub assignees: Vec<(Id, String)>,
+    pub labels: Vec<Label>,
     pub search: TicketSearch,
+    /// Raw query of the current search, to return to the same results after a bulk action.
+    pub query: String,
+    pub overdue: Vec<Id>,
+    /// Media of the found tickets, mapped by the ticket ID.
+    pub media: FnvHashMap<Id, Medium>,
+}
+
+/// Template for the outcome of a bulk action on the ticket search page.
+#[derive(Template)]
+#[template(path = "tickets/bulk.html")]
+pub struct BulkTickets {
+    pub role: Role,
+    pub outcome: BulkOutcome,
+    pub query: String,
+}
+
+/// Template for the queue of unassigned tickets.
+#[derive(Template)]
+#[template(path = "tickets/unassigned.html")]
+pub struct UnassignedTickets {
+    pub role: Role,
+    pub tickets: Vec<TicketWithNames>,
+    pub courses: Vec<(Id, String)>,
+    pub course_id: Option<Id>,
+    pub overdue: Vec<Id>,
 }
 
 /// Template for the _403 Forbidden_ error.